@@ -8,15 +8,17 @@ fn main() {
     // artefacts to target/<triple>/release/ — not target/release/.  tauri-build
     // validates the resource path in tauri.conf.json against target/release/, so we
     // copy libonnxruntime there from the sherpa-rs download cache before the check.
-    #[cfg(target_os = "macos")]
+    #[cfg(all(target_os = "macos", feature = "app"))]
     stage_onnxruntime_for_tauri_resources();
 
-    tauri_build::build()
+    // The headless CLI (--no-default-features) has no Tauri context to generate
+    #[cfg(feature = "app")]
+    tauri_build::build();
 }
 
 /// Copy libonnxruntime.1.17.1.dylib into target/release/ so tauri-build's
 /// resource-existence check passes for both native and cross-compilation builds.
-#[cfg(target_os = "macos")]
+#[cfg(all(target_os = "macos", feature = "app"))]
 fn stage_onnxruntime_for_tauri_resources() {
     use std::path::PathBuf;
 
@@ -49,7 +51,7 @@ fn stage_onnxruntime_for_tauri_resources() {
     }
 }
 
-#[cfg(target_os = "macos")]
+#[cfg(all(target_os = "macos", feature = "app"))]
 fn find_dylib(dir: &std::path::Path, name: &str) -> Option<std::path::PathBuf> {
     fn search(
         dir: &std::path::Path,
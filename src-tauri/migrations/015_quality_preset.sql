@@ -0,0 +1,7 @@
+-- Migration 015: Whisper decoding quality presets
+-- The active preset name lives in settings; each transcript records the preset
+-- it was produced with so results from different presets can be told apart.
+
+ALTER TABLE transcripts ADD COLUMN quality_preset TEXT;
+
+INSERT OR IGNORE INTO settings (key, value) VALUES ('whisper_quality_preset', 'fast');
@@ -0,0 +1,8 @@
+-- Migration 016: Per-episode Whisper language override
+-- NULL means "use the global whisper_language setting" (which may be 'auto').
+-- transcripts.language now stores the language actually used, including the
+-- result of automatic detection.
+
+ALTER TABLE episodes ADD COLUMN language_override TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_transcripts_language ON transcripts(language);
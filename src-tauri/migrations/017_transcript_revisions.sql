@@ -0,0 +1,59 @@
+-- Migration 017: Editable transcripts with revision history
+--
+-- diarization_segments.edited marks rows whose text or boundaries were changed
+-- by hand. Re-transcription, re-diarization and the Whisper text backfill skip
+-- these rows so manual edits are never silently overwritten.
+--
+-- transcript_revisions stores a before/after snapshot of every affected
+-- segment row per change, which is enough to undo or compare revisions.
+
+ALTER TABLE diarization_segments ADD COLUMN edited INTEGER DEFAULT 0;
+
+CREATE TABLE IF NOT EXISTS transcript_revisions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    episode_id INTEGER NOT NULL,
+    action TEXT NOT NULL,        -- 'edit' | 'split' | 'merge'
+    before_json TEXT NOT NULL,   -- JSON array of segment snapshots before the change
+    after_json TEXT NOT NULL,    -- JSON array of segment snapshots after the change
+    reverted INTEGER DEFAULT 0,
+    created_at TEXT DEFAULT (datetime('now')),
+    FOREIGN KEY (episode_id) REFERENCES episodes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_transcript_revisions_episode ON transcript_revisions(episode_id);
+
+-- ─── FTS consistency for segment edits ────────────────────────────────────────
+-- Split and merge change start_ms/end_ms and delete rows, neither of which the
+-- triggers from migration 013 cover.
+
+DROP TRIGGER IF EXISTS si_diarization_au;
+
+CREATE TRIGGER si_diarization_au
+AFTER UPDATE OF text, corrected_speaker, start_ms, end_ms ON diarization_segments
+WHEN NEW.text IS NOT NULL AND NEW.text != ''
+BEGIN
+    DELETE FROM search_index WHERE rowid = OLD.id;
+    INSERT INTO search_index(rowid, episode_id, episode_title, speaker, segment_text, segment_type, start_ms, end_ms)
+    SELECT NEW.id,
+           NEW.episode_id,
+           e.title,
+           COALESCE(NEW.corrected_speaker, NEW.speaker_label),
+           NEW.text,
+           'transcript',
+           NEW.start_ms,
+           NEW.end_ms
+    FROM episodes e WHERE e.id = NEW.episode_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS si_diarization_au_cleared
+AFTER UPDATE OF text ON diarization_segments
+WHEN NEW.text IS NULL OR NEW.text = ''
+BEGIN
+    DELETE FROM search_index WHERE rowid = OLD.id AND segment_type = 'transcript';
+END;
+
+CREATE TRIGGER IF NOT EXISTS si_diarization_ad
+AFTER DELETE ON diarization_segments
+BEGIN
+    DELETE FROM search_index WHERE rowid = OLD.id AND segment_type = 'transcript';
+END;
@@ -0,0 +1,20 @@
+-- Migration 018: Persistent job queue
+-- TranscriptionQueue and DiarizationQueue live in memory only. Every queued
+-- or running job is mirrored here so a force-quit mid-backlog does not lose
+-- the plan: on startup the remaining jobs are resumed in their original order,
+-- or marked 'interrupted' when they cannot be resumed (e.g. model missing).
+--
+-- Rows are deleted once a job finishes (done, error or cancelled).
+
+CREATE TABLE IF NOT EXISTS jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,                -- 'transcription' | 'diarization'
+    episode_id INTEGER NOT NULL,
+    audio_url TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'queued', -- 'queued' | 'active' | 'interrupted'
+    created_at TEXT DEFAULT (datetime('now')),
+    UNIQUE (kind, episode_id),
+    FOREIGN KEY (episode_id) REFERENCES episodes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_jobs_kind_status ON jobs(kind, status);
@@ -0,0 +1,10 @@
+-- Migration 019: Queue order and priority for persisted jobs
+-- position mirrors the in-memory queue order (0 = next) and is rewritten on
+-- every reorder; priority is kept so resumed jobs sort the same way again.
+-- queue_paused_* settings keep a paused queue paused across restarts.
+
+ALTER TABLE jobs ADD COLUMN priority INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE jobs ADD COLUMN position INTEGER;
+
+INSERT OR IGNORE INTO settings (key, value) VALUES ('queue_paused_transcription', 'false');
+INSERT OR IGNORE INTO settings (key, value) VALUES ('queue_paused_diarization', 'false');
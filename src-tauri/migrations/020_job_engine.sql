@@ -0,0 +1,65 @@
+-- Migration 020: Unified job engine
+-- Replaces the per-queue `jobs` rows (kind = 'transcription' | 'diarization')
+-- with one pipeline job per episode plus one status row per stage:
+--   download → transcribe → diarize → backfill_text → analyze_topics → index
+--
+-- pipeline_jobs holds only queued/running pipelines and is deleted when a pipeline
+-- finishes. job_stages keeps the last status of every stage per episode so
+-- the UI can show what happened (and retry failed stages) afterwards.
+
+CREATE TABLE IF NOT EXISTS pipeline_jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    episode_id INTEGER NOT NULL UNIQUE,
+    audio_url TEXT NOT NULL,
+    priority INTEGER NOT NULL DEFAULT 0,
+    position INTEGER,
+    created_at TEXT DEFAULT (datetime('now')),
+    FOREIGN KEY (episode_id) REFERENCES episodes(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS job_stages (
+    episode_id INTEGER NOT NULL,
+    stage TEXT NOT NULL,              -- see models::pipeline::Stage
+    status TEXT NOT NULL DEFAULT 'pending',
+                                      -- 'pending' | 'running' | 'done' | 'skipped'
+                                      -- | 'failed' | 'blocked' | 'cancelled'
+    attempts INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    updated_at TEXT DEFAULT (datetime('now')),
+    PRIMARY KEY (episode_id, stage),
+    FOREIGN KEY (episode_id) REFERENCES episodes(id) ON DELETE CASCADE
+);
+
+-- Carry over the backlog persisted by migration 018. Interrupted jobs were
+-- already surfaced as errors on the episode and are not resumed.
+INSERT OR IGNORE INTO pipeline_jobs (episode_id, audio_url, priority, position)
+SELECT episode_id, audio_url, priority, position FROM jobs
+WHERE status != 'interrupted'
+ORDER BY CASE kind WHEN 'transcription' THEN 0 ELSE 1 END, position IS NULL, position, id;
+
+-- A transcription job chained into diarization, so both map to the full chain.
+INSERT OR IGNORE INTO job_stages (episode_id, stage)
+SELECT j.episode_id, s.stage FROM jobs j
+JOIN (SELECT 'download' AS stage UNION ALL SELECT 'transcribe' UNION ALL SELECT 'diarize'
+      UNION ALL SELECT 'backfill_text' UNION ALL SELECT 'index') s
+WHERE j.kind = 'transcription' AND j.status != 'interrupted';
+
+INSERT OR IGNORE INTO job_stages (episode_id, stage)
+SELECT j.episode_id, s.stage FROM jobs j
+JOIN (SELECT 'download' AS stage UNION ALL SELECT 'diarize'
+      UNION ALL SELECT 'backfill_text' UNION ALL SELECT 'index') s
+WHERE j.kind = 'diarization' AND j.status != 'interrupted';
+
+DROP TABLE IF EXISTS jobs;
+
+-- One pause flag for the whole engine
+INSERT OR IGNORE INTO settings (key, value)
+SELECT 'queue_paused',
+       CASE WHEN EXISTS (
+           SELECT 1 FROM settings
+           WHERE key IN ('queue_paused_transcription', 'queue_paused_diarization') AND value = 'true'
+       ) THEN 'true' ELSE 'false' END;
+DELETE FROM settings WHERE key IN ('queue_paused_transcription', 'queue_paused_diarization');
+
+-- Topic analysis calls the OpenAI API (costs money) — opt-in for automatic runs
+INSERT OR IGNORE INTO settings (key, value) VALUES ('pipeline_analyze_topics', 'false');
@@ -0,0 +1,8 @@
+-- Migration 021: CPU budget for local processing
+-- worker_slots: pipelines processed in parallel by the job engine
+-- whisper_threads: threads per Whisper context (0 = auto)
+-- sherpa_threads: threads for sherpa-onnx diarization
+
+INSERT OR IGNORE INTO settings (key, value) VALUES ('cpu_worker_slots', '1');
+INSERT OR IGNORE INTO settings (key, value) VALUES ('cpu_whisper_threads', '0');
+INSERT OR IGNORE INTO settings (key, value) VALUES ('cpu_sherpa_threads', '1');
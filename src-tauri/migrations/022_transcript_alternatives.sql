@@ -0,0 +1,52 @@
+-- Migration 022: Alternative transcripts per episode
+--
+-- transcripts holds one active transcript per episode and is replaced by every
+-- new run (Whisper or AssemblyAI). transcript_alternatives keeps each run's
+-- output as it was produced, so engines, models and presets can be compared on
+-- the same episode (WER, word diff).
+
+CREATE TABLE IF NOT EXISTS transcript_alternatives (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    episode_id INTEGER NOT NULL,
+    engine TEXT NOT NULL,        -- 'whisper' | 'assemblyai'
+    model TEXT,                  -- Whisper model name, 'assemblyai' for AssemblyAI
+    quality_preset TEXT,
+    language TEXT,
+    full_text TEXT NOT NULL,
+    segments_json TEXT,
+    created_at TEXT DEFAULT (datetime('now')),
+    FOREIGN KEY (episode_id) REFERENCES episodes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_transcript_alternatives_episode ON transcript_alternatives(episode_id);
+
+-- Every transcript written (INSERT OR REPLACE fires the insert trigger) is kept.
+-- Later in-place updates (re-transcribed ranges) only change the active one.
+CREATE TRIGGER IF NOT EXISTS transcripts_keep_alternative
+AFTER INSERT ON transcripts
+BEGIN
+    INSERT INTO transcript_alternatives
+        (episode_id, engine, model, quality_preset, language, full_text, segments_json)
+    VALUES (
+        NEW.episode_id,
+        CASE WHEN NEW.whisper_model = 'assemblyai' THEN 'assemblyai' ELSE 'whisper' END,
+        NEW.whisper_model,
+        NEW.quality_preset,
+        NEW.language,
+        NEW.full_text,
+        NEW.segments_json
+    );
+END;
+
+-- Existing transcripts become the first alternative of their episode
+INSERT INTO transcript_alternatives
+    (episode_id, engine, model, quality_preset, language, full_text, segments_json, created_at)
+SELECT episode_id,
+       CASE WHEN whisper_model = 'assemblyai' THEN 'assemblyai' ELSE 'whisper' END,
+       whisper_model,
+       quality_preset,
+       language,
+       full_text,
+       segments_json,
+       created_at
+FROM transcripts;
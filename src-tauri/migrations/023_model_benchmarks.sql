@@ -0,0 +1,20 @@
+-- Migration 023: Whisper model benchmarks
+-- One row per model and benchmark run. The latest row of each model is used
+-- to recommend a model for a target processing time per episode.
+
+CREATE TABLE IF NOT EXISTS model_benchmarks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    model TEXT NOT NULL,
+    clip TEXT NOT NULL,
+    clip_ms INTEGER NOT NULL,
+    load_ms INTEGER NOT NULL,
+    processing_ms INTEGER NOT NULL,
+    real_time_factor REAL NOT NULL,
+    peak_memory_bytes INTEGER,
+    wer REAL,                    -- NULL when the clip has no reference transcript
+    quality_preset TEXT,
+    n_threads INTEGER NOT NULL,
+    created_at TEXT DEFAULT (datetime('now'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_model_benchmarks_model ON model_benchmarks(model);
@@ -0,0 +1,6 @@
+-- Migration 024: Per-episode expected speaker count for diarization
+-- NULL means the two hosts (the previous fixed setting), 'auto' clusters by
+-- threshold, '1'..'10' forces that many speakers. Speakers are labelled by
+-- speaking time: SPEAKER_0/SPEAKER_1 are the hosts, SPEAKER_2+ are guests.
+
+ALTER TABLE episodes ADD COLUMN speaker_count TEXT;
@@ -0,0 +1,9 @@
+-- Migration 025: Host voiceprints
+-- The global host_profiles row of a host (episode_id IS NULL, speaker_label
+-- SPEAKER_0 or SPEAKER_1) stores the mean wespeaker embedding of confirmed
+-- segments as little-endian f32s. Diarization maps clusters to hosts by cosine
+-- similarity and stores it in diarization_segments.confidence.
+
+ALTER TABLE host_profiles ADD COLUMN voiceprint BLOB;
+ALTER TABLE host_profiles ADD COLUMN voiceprint_segments INTEGER;
+ALTER TABLE host_profiles ADD COLUMN voiceprint_updated_at TEXT;
@@ -0,0 +1,7 @@
+-- Migration 026: Per-segment speaker embeddings
+-- Diarization stores the wespeaker embedding of every segment long enough to
+-- embed (little-endian f32s, like host_profiles.voiceprint) so an episode can
+-- be re-clustered without decoding its audio again. NULL for short segments,
+-- manual edits and episodes diarized before this migration.
+
+ALTER TABLE diarization_segments ADD COLUMN embedding BLOB;
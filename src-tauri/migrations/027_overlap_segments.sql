@@ -0,0 +1,18 @@
+-- Migration 027: Overlapping speech
+-- Stretches where two or more speakers talk at once, derived from the
+-- multi-speaker frames of the pyannote segmentation that survive as
+-- overlapping diarization segments. Kept apart from diarization_segments so
+-- text backfill, search and export only ever see speaker turns.
+-- speakers: comma-separated labels, e.g. "SPEAKER_0,SPEAKER_1".
+
+CREATE TABLE IF NOT EXISTS overlap_segments (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    episode_id INTEGER NOT NULL,
+    start_ms INTEGER NOT NULL,
+    end_ms INTEGER NOT NULL,
+    speakers TEXT NOT NULL,
+    created_at TEXT DEFAULT (datetime('now')),
+    FOREIGN KEY (episode_id) REFERENCES episodes(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_overlap_segments_episode ON overlap_segments(episode_id);
@@ -0,0 +1,19 @@
+-- Migration 028: Diarization evaluations
+-- One row per evaluation run: the current diarization config re-run on every
+-- manually corrected episode and scored against the corrected speakers.
+-- Times are summed over all episodes; der = (missed + false alarm + confusion)
+-- / reference speech. episode_ids: comma-separated, e.g. "3,17,21".
+
+CREATE TABLE IF NOT EXISTS diarization_evaluations (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    segmentation_model TEXT NOT NULL,
+    embedding_model TEXT NOT NULL,
+    threshold REAL NOT NULL,
+    episode_ids TEXT NOT NULL,
+    reference_ms INTEGER NOT NULL,
+    missed_ms INTEGER NOT NULL,
+    false_alarm_ms INTEGER NOT NULL,
+    confusion_ms INTEGER NOT NULL,
+    der REAL NOT NULL,
+    created_at TEXT DEFAULT (datetime('now'))
+);
@@ -0,0 +1,9 @@
+-- Migration 029: Embedding model of host voiceprints
+-- Embeddings of different models are not comparable, so a voiceprint records
+-- the registry id of the model that computed it (see diarization_models.rs).
+-- Voiceprints so far were all computed with the WeSpeaker ResNet34 model.
+
+ALTER TABLE host_profiles ADD COLUMN voiceprint_model TEXT;
+
+UPDATE host_profiles SET voiceprint_model = 'wespeaker_en_voxceleb_resnet34_LM'
+WHERE voiceprint IS NOT NULL;
@@ -0,0 +1,31 @@
+-- Migration 030: Turn-taking statistics cache
+-- Turns, interruptions and reply gaps per episode, computed from the
+-- diarization segments by turn_taking.rs and stored as JSON. The triggers drop
+-- an episode's row whenever its segments change (diarization, re-clustering,
+-- edits, speaker corrections from the Analytics page), so the next request
+-- recomputes it.
+
+CREATE TABLE IF NOT EXISTS turn_taking_stats (
+    episode_id INTEGER PRIMARY KEY,
+    stats TEXT NOT NULL,
+    computed_at TEXT DEFAULT (datetime('now')),
+    FOREIGN KEY (episode_id) REFERENCES episodes(id) ON DELETE CASCADE
+);
+
+CREATE TRIGGER IF NOT EXISTS tt_diarization_ai
+AFTER INSERT ON diarization_segments
+BEGIN
+    DELETE FROM turn_taking_stats WHERE episode_id = NEW.episode_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS tt_diarization_au
+AFTER UPDATE OF start_ms, end_ms, speaker_label, corrected_speaker ON diarization_segments
+BEGIN
+    DELETE FROM turn_taking_stats WHERE episode_id = NEW.episode_id;
+END;
+
+CREATE TRIGGER IF NOT EXISTS tt_diarization_ad
+AFTER DELETE ON diarization_segments
+BEGIN
+    DELETE FROM turn_taking_stats WHERE episode_id = OLD.episode_id;
+END;
@@ -0,0 +1,5 @@
+-- Migration 031: Drop self-referential benchmark WERs
+-- Episode clips used to be scored against the episode's stored Whisper
+-- transcript, which measures agreement with that model rather than errors.
+-- Their WER is cleared; file clips kept their user-supplied reference.
+UPDATE model_benchmarks SET wer = NULL WHERE clip LIKE 'Episode % @ % s';
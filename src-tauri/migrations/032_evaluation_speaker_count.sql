@@ -0,0 +1,9 @@
+-- Migration 032: Speaker count mode of diarization evaluations
+-- A tried threshold clusters every episode by it ("auto"); otherwise each
+-- episode uses its own speaker count ("episode"), so equal thresholds alone
+-- don't mean equal setups. Earlier runs also scored segments nobody reviewed,
+-- which compares the diarizer with itself; they are dropped.
+
+DELETE FROM diarization_evaluations;
+
+ALTER TABLE diarization_evaluations ADD COLUMN speaker_count TEXT NOT NULL DEFAULT 'episode';
@@ -0,0 +1,6 @@
+// Headless CLI (see src/cli.rs). A console program on every platform, unlike
+// the app binary. Without Tauri and the webview:
+//   cargo build --release --no-default-features --bin binky-cli
+fn main() {
+    std::process::exit(binky::cli::main())
+}
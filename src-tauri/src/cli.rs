@@ -0,0 +1,492 @@
+use crate::commands::export::{ExportFormat, ExportOptions};
+use crate::models::diarization::DiarizationEvent;
+use crate::models::pipeline::Stage;
+use crate::models::transcript::TranscriptionEvent;
+use crate::paths::BinkyPaths;
+use crate::progress::ProgressSink;
+use crate::state::job_engine::{EngineState, PipelineJob};
+use clap::{Args, Parser, Subcommand};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Headless CLI
+//
+// The `binky-cli` binary (src/bin/binky-cli.rs). Built with
+// `--no-default-features` it links neither Tauri nor the webview, so it runs
+// on servers without a display. Paths come from arguments instead of an
+// AppHandle; transcription and diarization go through the same job engine as
+// the app (stage statuses land in job_stages and the episode columns), with
+// progress printed to stderr instead of sent to the webview.
+//
+// Pipelines of an interrupted run stay in pipeline_jobs, so the app resumes
+// them when it is next started on the same database.
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Parser)]
+#[command(name = "binky-cli", version, about = "Binky without a window: batch processing for servers and scripts")]
+struct Cli {
+    /// SQLite database; created and migrated if needed
+    #[arg(long, global = true, env = "BINKY_DB", default_value = "binky.db")]
+    db: PathBuf,
+    /// Directory with ggml-*.bin Whisper models and diarization/
+    #[arg(long, global = true, env = "BINKY_MODELS", default_value = "models")]
+    models: PathBuf,
+    /// Directory for downloaded episode audio [default: system temp dir]
+    #[arg(long, global = true, env = "BINKY_CACHE")]
+    cache: Option<PathBuf>,
+    /// Print results as JSON
+    #[arg(long, global = true)]
+    json: bool,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Fetch the RSS feed and add new episodes
+    Sync,
+    /// Download, transcribe, diarize and index episodes
+    Transcribe {
+        #[command(flatten)]
+        selection: Selection,
+        /// Skip the diarization stage
+        #[arg(long)]
+        no_diarize: bool,
+    },
+    /// Diarize transcribed episodes
+    Diarize {
+        #[command(flatten)]
+        selection: Selection,
+    },
+    /// Full-text search over transcripts, titles and topics
+    Search {
+        query: String,
+        #[arg(long, default_value_t = 20)]
+        limit: i64,
+        /// Only transcripts in this language, e.g. "de"
+        #[arg(long)]
+        language: Option<String>,
+    },
+    /// Write transcripts to files, one per episode
+    Export {
+        /// Episode ids [default: all transcribed episodes]
+        episodes: Vec<i64>,
+        /// srt, vtt, txt, md or json
+        #[arg(long, default_value = "srt")]
+        format: ExportFormat,
+        /// Output directory
+        #[arg(long, short, default_value = ".")]
+        out: PathBuf,
+        /// Leave timestamps out of TXT and Markdown
+        #[arg(long)]
+        no_timestamps: bool,
+        /// Join consecutive segments of the same speaker
+        #[arg(long)]
+        merge_turns: bool,
+    },
+    /// Episode, transcription and speaker statistics
+    Stats,
+}
+
+#[derive(Args)]
+struct Selection {
+    /// Episode ids
+    episodes: Vec<i64>,
+    /// Every episode that has not been processed yet or failed
+    #[arg(long, conflicts_with = "episodes")]
+    all: bool,
+}
+
+/// Parse the arguments, run the subcommand and return the exit code.
+pub fn main() -> i32 {
+    let cli = Cli::parse();
+    let result = tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| format!("Cannot start async runtime: {}", e))
+        .and_then(|runtime| runtime.block_on(run(cli)));
+    match result {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            1
+        }
+    }
+}
+
+async fn run(cli: Cli) -> Result<i32, String> {
+    let paths = BinkyPaths {
+        db_path: cli.db,
+        models_dir: cli.models,
+        cache_dir: cli.cache.unwrap_or_else(|| std::env::temp_dir().join("binky")),
+    };
+    open_database(&paths.db_path)?;
+
+    match cli.command {
+        Command::Sync => {
+            let feed = crate::commands::episodes::fetch_feed().await?;
+            let added = crate::commands::episodes::store_new_episodes(&paths.db_path, &feed)?;
+            println!("{} episodes in feed, {} new", feed.len(), added);
+            Ok(0)
+        }
+        Command::Transcribe { selection, no_diarize } => {
+            crate::commands::transcription::find_model(&paths.models_dir)
+                .await
+                .ok_or_else(|| format!("No ggml-*.bin Whisper model in {}", paths.models_dir.display()))?;
+            let mut stages = crate::commands::pipeline::transcription_stages(&paths.db_path);
+            if no_diarize {
+                stages.retain(|s| *s != Stage::Diarize);
+            }
+            let episodes = select_episodes(
+                &paths.db_path,
+                &selection,
+                "transcription_status IN ('not_started', 'error')",
+            )?;
+            run_pipelines(&paths, &episodes, &stages).await
+        }
+        Command::Diarize { selection } => {
+            crate::commands::diarization::find_diarization_models(&paths).await?;
+            let episodes = select_episodes(
+                &paths.db_path,
+                &selection,
+                "transcription_status = 'done' \
+                 AND COALESCE(diarization_status, 'not_started') IN ('not_started', 'error')",
+            )?;
+            run_pipelines(&paths, &episodes, crate::commands::pipeline::DIARIZATION_STAGES).await
+        }
+        Command::Search { query, limit, language } => {
+            let results =
+                crate::commands::search::search_index(&paths.db_path, &query, Some(limit), language)?;
+            if cli.json {
+                print_json(&results)?;
+            } else {
+                for r in &results {
+                    let at = r.start_ms.map(format_clock).unwrap_or_default();
+                    let speaker = r.speaker.as_deref().unwrap_or("");
+                    println!("#{} {} [{}] {} {}", r.episode_id, r.title, at, speaker, r.snippet);
+                }
+            }
+            Ok(0)
+        }
+        Command::Export {
+            episodes,
+            format,
+            out,
+            no_timestamps,
+            merge_turns,
+        } => {
+            let options = ExportOptions {
+                include_timestamps: !no_timestamps,
+                merge_speaker_turns: merge_turns,
+            };
+            let episodes = (!episodes.is_empty()).then_some(episodes.as_slice());
+            let written =
+                crate::commands::export::export_to_folder(&paths.db_path, episodes, &out, format, &options)?;
+            println!("{} files written to {}", written, out.display());
+            Ok(0)
+        }
+        Command::Stats => {
+            let stats = library_stats(&paths.db_path)?;
+            if cli.json {
+                print_json(&stats)?;
+            } else {
+                print_stats(&stats);
+            }
+            Ok(0)
+        }
+    }
+}
+
+/// Create the database if needed and bring its schema up to date.
+fn open_database(db_path: &Path) -> Result<(), String> {
+    if let Some(dir) = db_path.parent().filter(|d| !d.as_os_str().is_empty()) {
+        std::fs::create_dir_all(dir).map_err(|e| format!("Cannot create {}: {}", dir.display(), e))?;
+    }
+    let mut conn = rusqlite::Connection::open(db_path).map_err(|e| e.to_string())?;
+    let applied = crate::migrations::apply_pending(&mut conn)?;
+    if applied > 0 {
+        eprintln!("[binky] {} migrations applied to {}", applied, db_path.display());
+    }
+    Ok(())
+}
+
+fn print_json<T: Serialize>(value: &T) -> Result<(), String> {
+    println!("{}", serde_json::to_string_pretty(value).map_err(|e| e.to_string())?);
+    Ok(())
+}
+
+fn format_clock(ms: i64) -> String {
+    let secs = ms / 1000;
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Pipelines
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// (episode_id, audio_url) of the given ids, or of all episodes matching
+/// `pending_filter` with `--all`.
+fn select_episodes(
+    db_path: &Path,
+    selection: &Selection,
+    pending_filter: &str,
+) -> Result<Vec<(i64, String)>, String> {
+    if !selection.all {
+        if selection.episodes.is_empty() {
+            return Err("Give episode ids or --all".to_string());
+        }
+        return selection
+            .episodes
+            .iter()
+            .map(|&id| Ok((id, crate::commands::pipeline::episode_audio_url(db_path, id)?)))
+            .collect();
+    }
+
+    let conn = rusqlite::Connection::open(db_path).map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT id, audio_url FROM episodes \
+             WHERE audio_url IS NOT NULL AND audio_url != '' AND {} \
+             ORDER BY publish_date ASC",
+            pending_filter
+        ))
+        .map_err(|e| e.to_string())?;
+    let episodes = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(episodes)
+}
+
+/// Prints an episode's progress (in 10% steps) and errors.
+struct ProgressPrinter {
+    episode_id: i64,
+    stage: &'static str,
+    last_step: Mutex<i32>,
+}
+
+impl ProgressPrinter {
+    fn new(episode_id: i64, stage: &'static str) -> Arc<Self> {
+        Arc::new(Self {
+            episode_id,
+            stage,
+            last_step: Mutex::new(-1),
+        })
+    }
+
+    fn percent(&self, percent: i32) {
+        let mut last = self.last_step.lock().unwrap();
+        if percent / 10 != *last {
+            *last = percent / 10;
+            eprintln!("[{}] episode {}: {}%", self.stage, self.episode_id, percent);
+        }
+    }
+
+    fn error(&self, message: &str) {
+        eprintln!("[{}] episode {}: {}", self.stage, self.episode_id, message);
+    }
+}
+
+impl ProgressSink<TranscriptionEvent> for ProgressPrinter {
+    fn send(&self, event: TranscriptionEvent) {
+        match event {
+            TranscriptionEvent::Downloading { percent } | TranscriptionEvent::Progress { percent } => {
+                self.percent(percent)
+            }
+            TranscriptionEvent::Error { message } => self.error(&message),
+            _ => {}
+        }
+    }
+}
+
+impl ProgressSink<DiarizationEvent> for ProgressPrinter {
+    fn send(&self, event: DiarizationEvent) {
+        match event {
+            DiarizationEvent::Progress { percent } => self.percent(percent),
+            DiarizationEvent::Error { message } => self.error(&message),
+            _ => {}
+        }
+    }
+}
+
+/// Queue one pipeline per episode in a private job engine, wait until all are
+/// finished and print each episode's stage results. Exit code 1 if any failed.
+async fn run_pipelines(
+    paths: &BinkyPaths,
+    episodes: &[(i64, String)],
+    stages: &[Stage],
+) -> Result<i32, String> {
+    if episodes.is_empty() {
+        println!("Nothing to do");
+        return Ok(0);
+    }
+
+    let state = Arc::new(EngineState::new());
+    state.engine.lock().unwrap().worker_slots =
+        crate::commands::pipeline::read_cpu_budget(&paths.db_path).worker_slots as usize;
+
+    for (episode_id, audio_url) in episodes {
+        let mut job = PipelineJob::new(*episode_id, audio_url.clone(), stages);
+        // Download progress goes to both channels; print it once
+        if stages.contains(&Stage::Transcribe) {
+            job.transcription_events = Some(ProgressPrinter::new(*episode_id, "transcribe"));
+        } else if stages.contains(&Stage::Diarize) {
+            job.diarization_events = Some(ProgressPrinter::new(*episode_id, "diarize"));
+        }
+        crate::commands::pipeline::enqueue_job(paths, &state, job)?;
+    }
+
+    loop {
+        {
+            let engine = state.engine.lock().unwrap();
+            if engine.jobs.is_empty() && engine.workers == 0 {
+                break;
+            }
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+
+    let conn = rusqlite::Connection::open(&paths.db_path).map_err(|e| e.to_string())?;
+    let mut failed = false;
+    for (episode_id, _) in episodes {
+        let mut results = vec![];
+        for stage in stages {
+            let row: Option<(String, Option<String>)> = conn
+                .query_row(
+                    "SELECT status, error FROM job_stages WHERE episode_id = ?1 AND stage = ?2",
+                    rusqlite::params![episode_id, stage.as_str()],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .ok();
+            let (status, error) = row.unwrap_or_else(|| ("unknown".to_string(), None));
+            failed |= matches!(status.as_str(), "failed" | "blocked");
+            results.push(match error {
+                Some(e) => format!("{} {} ({})", stage.as_str(), status, e),
+                None => format!("{} {}", stage.as_str(), status),
+            });
+        }
+        println!("episode {}: {}", episode_id, results.join(", "));
+    }
+    Ok(if failed { 1 } else { 0 })
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Export and stats
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Serialize)]
+struct SpeakerTime {
+    speaker: String,
+    speaking_hours: f64,
+    turns: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct LibraryStats {
+    episodes: i64,
+    audio_hours: f64,
+    transcribed: i64,
+    transcribed_hours: f64,
+    diarized: i64,
+    /// Episode count per transcription_status
+    transcription_status: Vec<(String, i64)>,
+    /// Time in which two or more speakers talk at once
+    overlap_hours: f64,
+    /// Top 10 speaker labels by speaking time, across all episodes
+    speakers: Vec<SpeakerTime>,
+}
+
+fn library_stats(db_path: &Path) -> Result<LibraryStats, String> {
+    let conn = rusqlite::Connection::open(db_path).map_err(|e| e.to_string())?;
+
+    let (episodes, audio_hours, transcribed, transcribed_hours, diarized) = conn
+        .query_row(
+            "SELECT COUNT(*), \
+                    COALESCE(SUM(duration_minutes), 0) / 60.0, \
+                    COUNT(*) FILTER (WHERE transcription_status = 'done'), \
+                    COALESCE(SUM(duration_minutes) FILTER (WHERE transcription_status = 'done'), 0) / 60.0, \
+                    COUNT(*) FILTER (WHERE diarization_status IN ('done', 'solo')) \
+             FROM episodes",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+        )
+        .map_err(|e| e.to_string())?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(transcription_status, 'not_started'), COUNT(*) FROM episodes \
+             GROUP BY 1 ORDER BY 2 DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let transcription_status = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    // Overlapping speech is split among the speakers talking at once
+    let speaking_times = crate::commands::overlap::episode_speaking_times(&conn, None)?;
+    let overlap_hours = speaking_times.iter().map(|t| t.overlap_ms).sum::<i64>() as f64 / 3_600_000.0;
+    let mut speaking_ms: HashMap<String, i64> = HashMap::new();
+    for speaker in speaking_times.into_iter().flat_map(|t| t.speakers) {
+        *speaking_ms.entry(speaker.speaker).or_insert(0) += speaker.ms;
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT COALESCE(corrected_speaker, speaker_label), COUNT(*) \
+             FROM diarization_segments GROUP BY 1",
+        )
+        .map_err(|e| e.to_string())?;
+    let turns: HashMap<String, i64> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut speakers: Vec<SpeakerTime> = speaking_ms
+        .into_iter()
+        .map(|(speaker, ms)| SpeakerTime {
+            turns: turns.get(&speaker).copied().unwrap_or(0),
+            speaker,
+            speaking_hours: ms as f64 / 3_600_000.0,
+        })
+        .collect();
+    speakers.sort_by(|a, b| b.speaking_hours.total_cmp(&a.speaking_hours));
+    speakers.truncate(10);
+
+    Ok(LibraryStats {
+        episodes,
+        audio_hours,
+        transcribed,
+        transcribed_hours,
+        diarized,
+        transcription_status,
+        overlap_hours,
+        speakers,
+    })
+}
+
+fn print_stats(stats: &LibraryStats) {
+    println!("Episodes:     {} ({:.1} h audio)", stats.episodes, stats.audio_hours);
+    println!(
+        "Transcribed:  {} ({:.1} h)",
+        stats.transcribed, stats.transcribed_hours
+    );
+    println!("Diarized:     {}", stats.diarized);
+    for (status, count) in &stats.transcription_status {
+        println!("  {:<14} {}", status, count);
+    }
+    if !stats.speakers.is_empty() {
+        println!("Overlap:      {:.1} h", stats.overlap_hours);
+        println!("Speakers:");
+        for s in &stats.speakers {
+            println!("  {:<14} {:>7.1} h  {} turns", s.speaker, s.speaking_hours, s.turns);
+        }
+    }
+}
@@ -568,7 +568,7 @@ fn write_results_to_db(
 
     let full_text = poll.text.as_deref().unwrap_or("");
 
-    // Insert transcript
+    // Insert transcript (earlier runs stay in transcript_alternatives, migration 022)
     conn.execute(
         "INSERT OR REPLACE INTO transcripts (episode_id, full_text, segments_json, whisper_model, language) \
          VALUES (?1, ?2, NULL, 'assemblyai', 'de')",
@@ -576,21 +576,31 @@ fn write_results_to_db(
     )
     .map_err(|e| format!("Transkript konnte nicht gespeichert werden: {}", e))?;
 
-    // Clear existing diarization segments
+    // Clear existing diarization segments (manual edits are kept, relabels
+    // carry over to the new segments)
+    let corrected = crate::commands::editing::load_corrected_ranges(&conn, episode_id);
     conn.execute(
-        "DELETE FROM diarization_segments WHERE episode_id = ?1",
+        "DELETE FROM diarization_segments WHERE episode_id = ?1 AND edited = 0",
         rusqlite::params![episode_id],
     )
     .map_err(|e| e.to_string())?;
 
+    let edited = crate::commands::editing::load_edited_ranges(&conn, episode_id);
+
     // Insert diarization segments
     if let Some(utterances) = &poll.utterances {
         for utterance in utterances {
+            if crate::commands::editing::overlaps_edited(&edited, utterance.start, utterance.end) {
+                continue;
+            }
             let speaker_label = map_speaker_label(&utterance.speaker);
+            let corrected_speaker =
+                crate::commands::editing::carried_correction(&corrected, utterance.start, utterance.end)
+                    .filter(|speaker| *speaker != speaker_label);
             conn.execute(
-                "INSERT INTO diarization_segments (episode_id, start_ms, end_ms, speaker_label, confidence, text) \
-                 VALUES (?1, ?2, ?3, ?4, 1.0, ?5)",
-                rusqlite::params![episode_id, utterance.start, utterance.end, speaker_label, utterance.text],
+                "INSERT INTO diarization_segments (episode_id, start_ms, end_ms, speaker_label, corrected_speaker, confidence, text) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, 1.0, ?6)",
+                rusqlite::params![episode_id, utterance.start, utterance.end, speaker_label, corrected_speaker, utterance.text],
             )
             .map_err(|e| format!("Segment konnte nicht gespeichert werden: {}", e))?;
         }
@@ -0,0 +1,254 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Word-level speaker attribution
+//
+// Whisper segments are sentences, not speaker turns: a segment often runs
+// across a speaker change. Whisper's token timestamps give every word its own
+// time, so text is attributed word by word and a sentence spanning a turn
+// boundary is split there. Transcripts stored before word timestamps (and
+// AssemblyAI transcripts) fall back to attributing whole segments.
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// A word (or, without word timestamps, a whole segment) with its time span.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct TimedWord {
+    pub text: String,
+    pub start_ms: i64,
+    pub end_ms: i64,
+}
+
+/// Group Whisper text tokens `(bytes, start_ms, end_ms)` into words. A token
+/// starting with a space begins a word; word pieces and punctuation extend the
+/// current one. Bytes are decoded per word, since a multi-byte character (ä,
+/// ß) can be split across tokens.
+pub(crate) fn words_from_tokens(tokens: impl IntoIterator<Item = (Vec<u8>, i64, i64)>) -> Vec<TimedWord> {
+    let mut words: Vec<(Vec<u8>, i64, i64)> = Vec::new();
+    for (bytes, start_ms, end_ms) in tokens {
+        let starts_word = bytes.first().is_some_and(|b| b.is_ascii_whitespace());
+        match words.last_mut() {
+            Some((word, _, word_end)) if !starts_word => {
+                word.extend_from_slice(&bytes);
+                *word_end = (*word_end).max(end_ms);
+            }
+            _ => words.push((bytes, start_ms, end_ms.max(start_ms))),
+        }
+    }
+    words
+        .into_iter()
+        .filter_map(|(bytes, start_ms, end_ms)| {
+            let text = String::from_utf8_lossy(&bytes).trim().to_string();
+            if text.is_empty() {
+                None
+            } else {
+                Some(TimedWord { text, start_ms, end_ms })
+            }
+        })
+        .collect()
+}
+
+/// Text pieces of one stored Whisper segment: its words, or the whole segment
+/// if it has none. Hallucination-flagged segments never reach speaker text.
+pub(crate) fn timed_pieces(segment: &serde_json::Value) -> Vec<TimedWord> {
+    let text = segment["text"].as_str().unwrap_or("").trim();
+    if text.is_empty() || segment["hallucination"].is_string() {
+        return vec![];
+    }
+    let words: Vec<TimedWord> = segment["words"]
+        .as_array()
+        .map(|words| {
+            words
+                .iter()
+                .filter_map(|w| serde_json::from_value::<TimedWord>(w.clone()).ok())
+                .filter(|w| !w.text.trim().is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    if !words.is_empty() {
+        return words;
+    }
+    match (segment["start_ms"].as_i64(), segment["end_ms"].as_i64()) {
+        (Some(start_ms), Some(end_ms)) if end_ms > start_ms => vec![TimedWord {
+            text: text.to_string(),
+            start_ms,
+            end_ms,
+        }],
+        _ => vec![],
+    }
+}
+
+/// The turn `(id, start_ms, end_ms)` a piece of text belongs to.
+///
+/// The SHORTEST turn containing the piece's midpoint wins. sherpa-rs produces
+/// large SPEAKER_0 windows (30-60s) that temporally envelop short SPEAKER_1
+/// windows (300-1000ms); a word said inside the short window belongs to it,
+/// while a word merely grazing its edge stays with the window around it.
+///
+/// When no turn contains the midpoint, the turn with the greatest PROPORTIONAL
+/// overlap wins: overlap_ms / turn_duration_ms, since by absolute overlap the
+/// big window would always win. Scores are integers (overlap * 1e6 / duration)
+/// to keep ordering exact.
+///
+/// Text overlapping no turn (a pause between turns, a zero-length word) goes
+/// to the nearest turn, the shorter one on a tie.
+fn best_turn(piece: &TimedWord, turns: &[(i64, i64, i64)]) -> Option<i64> {
+    let midpoint = (piece.start_ms + piece.end_ms) / 2;
+    let by_midpoint = turns
+        .iter()
+        .filter(|(_, start, end)| *start <= midpoint && midpoint < *end)
+        .min_by_key(|(_, start, end)| end - start)
+        .map(|(id, _, _)| *id);
+    if by_midpoint.is_some() {
+        return by_midpoint;
+    }
+
+    let by_overlap = turns
+        .iter()
+        .filter_map(|(id, start, end)| {
+            let overlap = piece.end_ms.min(*end) - piece.start_ms.max(*start);
+            let duration = end - start;
+            if overlap > 0 && duration > 0 {
+                Some((overlap * 1_000_000 / duration, *id))
+            } else {
+                None
+            }
+        })
+        .max_by_key(|(score, _)| *score)
+        .map(|(_, id)| id);
+
+    by_overlap.or_else(|| {
+        turns
+            .iter()
+            .min_by_key(|(_, start, end)| {
+                let distance = (start - piece.end_ms).max(piece.start_ms - end).max(0);
+                (distance, end - start)
+            })
+            .map(|(id, _, _)| *id)
+    })
+}
+
+/// Attribute every piece to a turn and join each turn's text in time order.
+/// Returns turn id → text; turns without text are left out.
+pub(crate) fn attribute_text(pieces: &[TimedWord], turns: &[(i64, i64, i64)]) -> HashMap<i64, String> {
+    let mut texts: HashMap<i64, Vec<&str>> = HashMap::new();
+    for piece in pieces {
+        if let Some(id) = best_turn(piece, turns) {
+            texts.entry(id).or_default().push(piece.text.trim());
+        }
+    }
+    texts
+        .into_iter()
+        .map(|(id, words)| (id, words.join(" ")))
+        .filter(|(_, text)| !text.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(text: &str, start_ms: i64, end_ms: i64) -> TimedWord {
+        TimedWord {
+            text: text.to_string(),
+            start_ms,
+            end_ms,
+        }
+    }
+
+    fn token(text: &[u8], start_ms: i64, end_ms: i64) -> (Vec<u8>, i64, i64) {
+        (text.to_vec(), start_ms, end_ms)
+    }
+
+    #[test]
+    fn tokens_group_into_words_with_their_punctuation() {
+        let tokens = vec![
+            token(b" Der", 0, 200),
+            token(b" Kran", 200, 400),
+            token(b"ich", 400, 600),
+            token(b" zieht", 600, 900),
+            token(b".", 900, 950),
+            // "über" with the ü split across two tokens
+            token(b" \xc3", 1_000, 1_100),
+            token(b"\xbcber", 1_100, 1_400),
+        ];
+        assert_eq!(
+            words_from_tokens(tokens),
+            vec![
+                word("Der", 0, 200),
+                word("Kranich", 200, 600),
+                word("zieht.", 600, 950),
+                word("über", 1_000, 1_400),
+            ]
+        );
+    }
+
+    #[test]
+    fn sentence_spanning_a_speaker_change_is_split_at_the_turn() {
+        // Whisper: "Wie war dein Urlaub? Super, danke." (0–4 s); the speaker
+        // changes at 2 s
+        let words = vec![
+            word("Wie", 0, 300),
+            word("war", 300, 600),
+            word("dein", 600, 1_000),
+            word("Urlaub?", 1_000, 1_900),
+            word("Super,", 2_100, 2_800),
+            word("danke.", 2_800, 3_600),
+        ];
+        let turns = vec![(1, 0, 2_000), (2, 2_000, 4_000)];
+        let texts = attribute_text(&words, &turns);
+        assert_eq!(texts[&1], "Wie war dein Urlaub?");
+        assert_eq!(texts[&2], "Super, danke.");
+    }
+
+    #[test]
+    fn interjection_inside_an_enveloping_window_goes_to_the_short_turn() {
+        let words = vec![word("Also", 10_000, 10_400), word("genau", 20_100, 20_600), word("weiter", 30_000, 30_500)];
+        let turns = vec![(1, 0, 48_000), (2, 20_000, 21_000)];
+        let texts = attribute_text(&words, &turns);
+        assert_eq!(texts[&1], "Also weiter");
+        assert_eq!(texts[&2], "genau");
+    }
+
+    #[test]
+    fn word_on_the_edge_of_a_short_turn_goes_by_its_midpoint() {
+        // Both words overlap the short turn, but only "ja" is mostly inside it
+        let words = vec![word("ja", 19_800, 20_400), word("und", 20_700, 21_500)];
+        let turns = vec![(1, 0, 48_000), (2, 20_000, 21_000)];
+        let texts = attribute_text(&words, &turns);
+        assert_eq!(texts[&1], "und");
+        assert_eq!(texts[&2], "ja");
+    }
+
+    #[test]
+    fn words_in_a_pause_go_to_the_nearest_turn() {
+        let words = vec![word("äh", 5_100, 5_300), word("ja", 7_700, 7_900)];
+        let turns = vec![(1, 0, 5_000), (2, 8_000, 9_000)];
+        let texts = attribute_text(&words, &turns);
+        assert_eq!(texts[&1], "äh");
+        assert_eq!(texts[&2], "ja");
+    }
+
+    #[test]
+    fn segments_without_words_are_attributed_whole() {
+        let old = serde_json::json!({ "text": " Hallo zusammen.", "start_ms": 0, "end_ms": 3_000 });
+        let flagged = serde_json::json!({ "text": "Untertitel", "start_ms": 3_000, "end_ms": 4_000, "hallucination": "stock_phrase" });
+        let with_words = serde_json::json!({
+            "text": " Moin. Hi.", "start_ms": 4_000, "end_ms": 6_000,
+            "words": [
+                { "text": "Moin.", "start_ms": 4_000, "end_ms": 4_800 },
+                { "text": "Hi.", "start_ms": 5_200, "end_ms": 5_800 }
+            ]
+        });
+        let pieces: Vec<TimedWord> = [old, flagged, with_words].iter().flat_map(timed_pieces).collect();
+        assert_eq!(
+            pieces,
+            vec![word("Hallo zusammen.", 0, 3_000), word("Moin.", 4_000, 4_800), word("Hi.", 5_200, 5_800)]
+        );
+
+        let turns = vec![(1, 0, 5_000), (2, 5_000, 6_000)];
+        let texts = attribute_text(&pieces, &turns);
+        assert_eq!(texts[&1], "Hallo zusammen. Moin.");
+        assert_eq!(texts[&2], "Hi.");
+    }
+}
@@ -0,0 +1,586 @@
+use crate::commands::transcription::{
+    build_full_params, decode_mp3_to_pcm, detect_language, installed_models, read_language_setting,
+    read_quality_preset, resolve_episode_language,
+};
+use crate::commands::whisper_callbacks::{full_with_callbacks, WhisperRunError};
+use crate::models::transcript::{BenchmarkClip, BenchmarkEvent, ModelBenchmark, ModelRecommendation};
+use crate::paths::BinkyPaths;
+use crate::state::job_engine::EngineState;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tauri::ipc::Channel;
+use tokio_util::sync::CancellationToken;
+use whisper_rs::{WhisperContext, WhisperContextParameters};
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Model benchmark
+//
+// Runs every downloaded Whisper model on the same short clip with the active
+// quality preset and CPU budget, and stores load time, real-time factor, peak
+// memory and (with a human reference transcript) WER in model_benchmarks. The
+// latest result per model is used to recommend a model for a target processing
+// time. There is no bundled sample clip: the clip comes from an episode or a
+// file the user picks.
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Default clip: two minutes starting five minutes into the most recently
+/// transcribed episode (past intro and jingle).
+const DEFAULT_CLIP_START_MS: i64 = 5 * 60_000;
+const DEFAULT_CLIP_MS: i64 = 2 * 60_000;
+
+/// Longer clips make the benchmark slower, not more telling.
+const MAX_CLIP_MS: i64 = 10 * 60_000;
+
+/// Episode length assumed when no episode has a duration.
+const DEFAULT_EPISODE_MS: i64 = 60 * 60_000;
+
+const BENCHMARK_COLUMNS: &str = "id, model, clip, clip_ms, load_ms, processing_ms, real_time_factor, \
+     peak_memory_bytes, wer, quality_preset, n_threads, created_at";
+
+/// Decoded clip with what is needed to score it.
+struct PreparedClip {
+    label: String,
+    audio: Vec<f32>,
+    reference: Option<String>,
+    /// Whisper language code or "auto".
+    language: String,
+}
+
+/// Samples the process's resident memory in the background and reports how
+/// far it rose above the level at `start`.
+struct MemorySampler {
+    baseline: u64,
+    peak: Arc<AtomicU64>,
+    stop: Arc<AtomicBool>,
+    handle: std::thread::JoinHandle<()>,
+}
+
+fn resident_memory() -> Option<u64> {
+    memory_stats::memory_stats().map(|stats| stats.physical_mem as u64)
+}
+
+impl MemorySampler {
+    fn start() -> Option<Self> {
+        let baseline = resident_memory()?;
+        let peak = Arc::new(AtomicU64::new(baseline));
+        let stop = Arc::new(AtomicBool::new(false));
+        let handle = {
+            let peak = peak.clone();
+            let stop = stop.clone();
+            std::thread::spawn(move || {
+                while !stop.load(Ordering::Relaxed) {
+                    if let Some(current) = resident_memory() {
+                        peak.fetch_max(current, Ordering::Relaxed);
+                    }
+                    std::thread::sleep(Duration::from_millis(20));
+                }
+            })
+        };
+        Some(Self { baseline, peak, stop, handle })
+    }
+
+    fn finish(self) -> i64 {
+        self.stop.store(true, Ordering::Relaxed);
+        let _ = self.handle.join();
+        if let Some(current) = resident_memory() {
+            self.peak.fetch_max(current, Ordering::Relaxed);
+        }
+        self.peak.load(Ordering::Relaxed).saturating_sub(self.baseline) as i64
+    }
+}
+
+fn benchmark_from_row(row: &rusqlite::Row) -> rusqlite::Result<ModelBenchmark> {
+    Ok(ModelBenchmark {
+        id: row.get(0)?,
+        model: row.get(1)?,
+        clip: row.get(2)?,
+        clip_ms: row.get(3)?,
+        load_ms: row.get(4)?,
+        processing_ms: row.get(5)?,
+        real_time_factor: row.get(6)?,
+        peak_memory_bytes: row.get(7)?,
+        wer: row.get(8)?,
+        quality_preset: row.get(9)?,
+        n_threads: row.get(10)?,
+        created_at: row.get(11)?,
+    })
+}
+
+/// Expected time to transcribe an episode of `episode_ms` with this model.
+fn estimated_ms(benchmark: &ModelBenchmark, episode_ms: i64) -> i64 {
+    benchmark.load_ms + (benchmark.real_time_factor * episode_ms as f64).round() as i64
+}
+
+/// Pick the most accurate model that transcribes an episode of `episode_ms`
+/// within `target_ms`: lowest WER when every candidate has one, otherwise the
+/// slowest candidate (larger models are slower and more accurate). If no model
+/// is fast enough, the fastest one is returned with `meets_target: false`.
+pub(crate) fn pick_model(
+    benchmarks: &[ModelBenchmark],
+    episode_ms: i64,
+    target_ms: i64,
+) -> Option<ModelRecommendation> {
+    let fitting: Vec<&ModelBenchmark> = benchmarks
+        .iter()
+        .filter(|b| estimated_ms(b, episode_ms) <= target_ms)
+        .collect();
+
+    let chosen = if fitting.is_empty() {
+        benchmarks.iter().min_by_key(|b| estimated_ms(b, episode_ms))?
+    } else if fitting.iter().all(|b| b.wer.is_some()) {
+        fitting.iter().copied().min_by(|a, b| {
+            a.wer
+                .unwrap_or(f64::MAX)
+                .total_cmp(&b.wer.unwrap_or(f64::MAX))
+                .then(estimated_ms(a, episode_ms).cmp(&estimated_ms(b, episode_ms)))
+        })?
+    } else {
+        fitting.iter().copied().max_by_key(|b| estimated_ms(b, episode_ms))?
+    };
+
+    Some(ModelRecommendation {
+        model: chosen.model.clone(),
+        estimated_ms: estimated_ms(chosen, episode_ms),
+        episode_ms,
+        target_ms,
+        meets_target: !fitting.is_empty(),
+        wer: chosen.wer,
+    })
+}
+
+/// Hand-corrected text of an episode within `[start_ms, end_ms)`, by segment
+/// center. None unless every segment in the range was edited: the stored
+/// transcript is itself the output of a Whisper model, and scoring models
+/// against it would favour that model instead of measuring errors.
+fn reference_text(
+    conn: &rusqlite::Connection,
+    episode_id: i64,
+    start_ms: i64,
+    end_ms: i64,
+) -> Option<String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT text, COALESCE(edited, 0) FROM diarization_segments \
+             WHERE episode_id = ?1 AND (start_ms + end_ms) / 2 >= ?2 AND (start_ms + end_ms) / 2 < ?3 \
+             ORDER BY start_ms",
+        )
+        .ok()?;
+    let segments: Vec<(Option<String>, i64)> = stmt
+        .query_map(rusqlite::params![episode_id, start_ms, end_ms], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })
+        .ok()?
+        .filter_map(|r| r.ok())
+        .collect();
+    if segments.is_empty() || segments.iter().any(|(_, edited)| *edited == 0) {
+        return None;
+    }
+    let text = segments
+        .iter()
+        .filter_map(|(text, _)| text.as_deref())
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    Some(text).filter(|t| !t.is_empty())
+}
+
+async fn prepare_episode_clip(
+    paths: &BinkyPaths,
+    episode_id: i64,
+    start_ms: i64,
+    duration_ms: i64,
+    on_event: &Channel<BenchmarkEvent>,
+) -> Result<PreparedClip, String> {
+    let db_path = paths.db_path.as_path();
+    let conn = rusqlite::Connection::open(db_path).map_err(|e| e.to_string())?;
+    let (audio_url, language): (Option<String>, Option<String>) = conn
+        .query_row(
+            "SELECT e.audio_url, t.language \
+             FROM episodes e LEFT JOIN transcripts t ON t.episode_id = e.id WHERE e.id = ?1",
+            rusqlite::params![episode_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|_| "Episode nicht gefunden".to_string())?;
+    let audio_url = audio_url.ok_or_else(|| "Episode hat keine Audio-URL".to_string())?;
+
+    let audio = crate::commands::pipeline::decode_episode_audio(paths, episode_id, &audio_url, |percent| {
+        let _ = on_event.send(BenchmarkEvent::Downloading { percent: percent * 2 });
+    })
+    .await?;
+
+    // Short episodes: move the clip back so it still fits
+    let audio_ms = (audio.len() / 16) as i64;
+    let duration_ms = duration_ms.min(audio_ms);
+    let start_ms = start_ms.clamp(0, audio_ms - duration_ms);
+    let end_ms = start_ms + duration_ms;
+
+    Ok(PreparedClip {
+        label: format!("Episode {} @ {} s", episode_id, start_ms / 1000),
+        audio: audio[(start_ms * 16) as usize..(end_ms * 16) as usize].to_vec(),
+        reference: reference_text(&conn, episode_id, start_ms, end_ms),
+        language: language.unwrap_or_else(|| resolve_episode_language(db_path, episode_id)),
+    })
+}
+
+fn prepare_file_clip(
+    db_path: &Path,
+    path: &str,
+    reference_text: Option<String>,
+) -> Result<PreparedClip, String> {
+    let path = PathBuf::from(path);
+    let mut audio = decode_mp3_to_pcm(&path).map_err(|e| format!("Audio decode failed: {}", e))?;
+    audio.truncate((MAX_CLIP_MS * 16) as usize);
+
+    Ok(PreparedClip {
+        label: path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.to_string_lossy().to_string()),
+        audio,
+        reference: reference_text.filter(|t| !t.trim().is_empty()),
+        language: read_language_setting(db_path),
+    })
+}
+
+/// Clip of the most recently transcribed episode (see `DEFAULT_CLIP_START_MS`).
+fn default_clip(db_path: &Path) -> Result<BenchmarkClip, String> {
+    let conn = rusqlite::Connection::open(db_path).map_err(|e| e.to_string())?;
+    let episode_id: i64 = conn
+        .query_row(
+            "SELECT episode_id FROM transcripts WHERE segments_json IS NOT NULL \
+             ORDER BY created_at DESC, id DESC LIMIT 1",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|_| {
+            "Keine transkribierte Episode für den Benchmark gefunden. Bitte eine Audiodatei wählen."
+                .to_string()
+        })?;
+    Ok(BenchmarkClip::Episode {
+        episode_id,
+        start_ms: DEFAULT_CLIP_START_MS,
+        duration_ms: DEFAULT_CLIP_MS,
+    })
+}
+
+/// Load and run one model on the clip. Returns (load_ms, processing_ms,
+/// peak_memory_bytes, text).
+fn run_model(
+    model_path: &Path,
+    audio: &[f32],
+    language: &str,
+    preset: &crate::models::transcript::QualityPreset,
+    on_progress: &mut dyn FnMut(i32),
+) -> Result<(i64, i64, Option<i64>, String), String> {
+    let sampler = MemorySampler::start();
+    let load_started = Instant::now();
+    let ctx = WhisperContext::new_with_params(
+        &model_path.to_string_lossy(),
+        WhisperContextParameters::default(),
+    )
+    .map_err(|e| format!("Failed to load Whisper model: {}", e))?;
+    let load_ms = load_started.elapsed().as_millis() as i64;
+
+    let started = Instant::now();
+    let language = if language == "auto" {
+        detect_language(&ctx, audio, preset.n_threads.unwrap_or(1) as usize)?
+    } else {
+        language.to_string()
+    };
+    let mut whisper_state = ctx
+        .create_state()
+        .map_err(|e| format!("Failed to create Whisper state: {}", e))?;
+    let params = build_full_params(preset, &language);
+    full_with_callbacks(&mut whisper_state, params, audio, &CancellationToken::new(), on_progress)
+        .map_err(|e| match e {
+            WhisperRunError::Cancelled => "Abgebrochen".to_string(),
+            WhisperRunError::Failed(e) => format!("Whisper failed: {}", e),
+        })?;
+    let processing_ms = started.elapsed().as_millis() as i64;
+
+    let text: String = whisper_state.as_iter().map(|segment| segment.to_string()).collect();
+    let peak_memory = sampler.map(MemorySampler::finish);
+    Ok((load_ms, processing_ms, peak_memory, text))
+}
+
+/// Benchmark downloaded Whisper models (all, or the named ones) on a clip and
+/// store the results. Refused while the job engine is processing, since
+/// parallel work would distort the timings.
+#[tauri::command]
+pub async fn run_model_benchmark(
+    clip: Option<BenchmarkClip>,
+    models: Option<Vec<String>>,
+    on_event: Channel<BenchmarkEvent>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<EngineState>>,
+) -> Result<Vec<ModelBenchmark>, String> {
+    if state.engine.lock().unwrap().is_processing() {
+        return Err("Der Benchmark kann erst laufen, wenn keine Episoden verarbeitet werden".to_string());
+    }
+
+    let paths = BinkyPaths::from_app(&app)?;
+    let db_path = paths.db_path.as_path();
+
+    let mut installed = installed_models(&paths.models_dir).await;
+    if let Some(names) = &models {
+        installed.retain(|(name, _)| names.contains(name));
+    }
+    if installed.is_empty() {
+        return Err("Kein Whisper-Modell zum Testen heruntergeladen".to_string());
+    }
+
+    let clip = match clip {
+        Some(clip) => clip,
+        None => default_clip(db_path)?,
+    };
+    let prepared = match clip {
+        BenchmarkClip::Episode { episode_id, start_ms, duration_ms } => {
+            if duration_ms <= 0 || duration_ms > MAX_CLIP_MS {
+                return Err("Der Ausschnitt muss zwischen 1 Sekunde und 10 Minuten lang sein".to_string());
+            }
+            prepare_episode_clip(&paths, episode_id, start_ms, duration_ms, &on_event).await?
+        }
+        BenchmarkClip::File { path, reference_text } => {
+            prepare_file_clip(db_path, &path, reference_text)?
+        }
+    };
+    let clip_ms = (prepared.audio.len() / 16) as i64;
+    if clip_ms < 1_000 {
+        return Err("Der Ausschnitt ist zu kurz".to_string());
+    }
+
+    // Same decoding settings and threads as a real transcription
+    let mut preset = read_quality_preset(db_path);
+    let budget = crate::commands::pipeline::read_cpu_budget(db_path);
+    preset.n_threads = Some(
+        budget.whisper_threads_for(preset.n_threads, crate::commands::pipeline::available_cores()),
+    );
+    let n_threads = preset.n_threads.unwrap_or(1);
+
+    let label = prepared.label.clone();
+    let reference = prepared.reference.clone();
+    let language = prepared.language.clone();
+    let audio = Arc::new(prepared.audio);
+    let total = installed.len();
+    let mut results = Vec::with_capacity(total);
+
+    for (index, (model_name, model_path)) in installed.into_iter().enumerate() {
+        let _ = on_event.send(BenchmarkEvent::ModelStarted {
+            model: model_name.clone(),
+            index,
+            total,
+        });
+
+        let run = {
+            let audio = audio.clone();
+            let language = language.clone();
+            let preset = preset.clone();
+            let on_event = on_event.clone();
+            let model = model_name.clone();
+            tauri::async_runtime::spawn_blocking(move || {
+                let mut on_progress = |percent: i32| {
+                    let _ = on_event.send(BenchmarkEvent::Progress {
+                        model: model.clone(),
+                        percent,
+                    });
+                };
+                run_model(&model_path, &audio, &language, &preset, &mut on_progress)
+            })
+            .await
+            .map_err(|e| format!("Whisper task panicked: {}", e))
+            .and_then(|run| run)
+        };
+        let (load_ms, processing_ms, peak_memory_bytes, text) = match run {
+            Ok(run) => run,
+            Err(e) => {
+                let message = format!("{}: {}", model_name, e);
+                let _ = on_event.send(BenchmarkEvent::Error { message: message.clone() });
+                return Err(message);
+            }
+        };
+
+        let wer = reference
+            .as_deref()
+            .map(|reference| crate::commands::comparison::compare_texts(0, 0, reference, &text).wer);
+        let result = {
+            let conn = rusqlite::Connection::open(db_path).map_err(|e| e.to_string())?;
+            conn.execute(
+                "INSERT INTO model_benchmarks \
+                 (model, clip, clip_ms, load_ms, processing_ms, real_time_factor, peak_memory_bytes, \
+                  wer, quality_preset, n_threads) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                rusqlite::params![
+                    model_name,
+                    label,
+                    clip_ms,
+                    load_ms,
+                    processing_ms,
+                    processing_ms as f64 / clip_ms as f64,
+                    peak_memory_bytes,
+                    wer,
+                    preset.name,
+                    n_threads
+                ],
+            )
+            .map_err(|e| format!("Benchmark konnte nicht gespeichert werden: {}", e))?;
+            conn.query_row(
+                &format!("SELECT {} FROM model_benchmarks WHERE id = ?1", BENCHMARK_COLUMNS),
+                rusqlite::params![conn.last_insert_rowid()],
+                benchmark_from_row,
+            )
+            .map_err(|e| e.to_string())?
+        };
+        eprintln!(
+            "[benchmark] {} on {}: RTF {:.2}, load {} ms",
+            result.model, result.clip, result.real_time_factor, result.load_ms
+        );
+        let _ = on_event.send(BenchmarkEvent::ModelDone { result: result.clone() });
+        results.push(result);
+    }
+
+    let _ = on_event.send(BenchmarkEvent::Done);
+    Ok(results)
+}
+
+/// All stored benchmark results, newest first.
+#[tauri::command]
+pub async fn list_model_benchmarks(app: tauri::AppHandle) -> Result<Vec<ModelBenchmark>, String> {
+    let paths = BinkyPaths::from_app(&app)?;
+    let conn = rusqlite::Connection::open(&paths.db_path).map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM model_benchmarks ORDER BY created_at DESC, id DESC",
+            BENCHMARK_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], benchmark_from_row)
+        .map_err(|e| e.to_string())?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+/// Recommend a model that transcribes an episode within `target_minutes`,
+/// based on the latest benchmark of each model. The episode length defaults to
+/// the average episode duration. None when no model has been benchmarked.
+#[tauri::command]
+pub async fn recommend_model(
+    target_minutes: f64,
+    episode_minutes: Option<f64>,
+    app: tauri::AppHandle,
+) -> Result<Option<ModelRecommendation>, String> {
+    if target_minutes.is_nan() || target_minutes <= 0.0 {
+        return Err("Die Zielzeit muss größer als 0 sein".to_string());
+    }
+    let paths = BinkyPaths::from_app(&app)?;
+    let conn = rusqlite::Connection::open(&paths.db_path).map_err(|e| e.to_string())?;
+
+    let episode_ms = match episode_minutes.filter(|m| *m > 0.0) {
+        Some(minutes) => (minutes * 60_000.0) as i64,
+        None => conn
+            .query_row(
+                "SELECT AVG(duration_minutes) FROM episodes WHERE duration_minutes > 0",
+                [],
+                |row| row.get::<_, Option<f64>>(0),
+            )
+            .ok()
+            .flatten()
+            .map(|minutes| (minutes * 60_000.0) as i64)
+            .unwrap_or(DEFAULT_EPISODE_MS),
+    };
+
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM model_benchmarks \
+             WHERE id IN (SELECT MAX(id) FROM model_benchmarks GROUP BY model)",
+            BENCHMARK_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let latest: Vec<ModelBenchmark> = stmt
+        .query_map([], benchmark_from_row)
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    Ok(pick_model(&latest, episode_ms, (target_minutes * 60_000.0) as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bench(model: &str, real_time_factor: f64, wer: Option<f64>) -> ModelBenchmark {
+        ModelBenchmark {
+            id: 0,
+            model: model.to_string(),
+            clip: "test".to_string(),
+            clip_ms: 120_000,
+            load_ms: 1_000,
+            processing_ms: (real_time_factor * 120_000.0) as i64,
+            real_time_factor,
+            peak_memory_bytes: None,
+            wer,
+            quality_preset: None,
+            n_threads: 4,
+            created_at: None,
+        }
+    }
+
+    const HOUR_MS: i64 = 60 * 60_000;
+
+    #[test]
+    fn picks_lowest_wer_within_target() {
+        let benchmarks = vec![
+            bench("base", 0.05, Some(0.21)),
+            bench("small", 0.15, Some(0.12)),
+            bench("large-v3", 0.9, Some(0.06)),
+        ];
+        let pick = pick_model(&benchmarks, HOUR_MS, 15 * 60_000).unwrap();
+        assert_eq!(pick.model, "small");
+        assert!(pick.meets_target);
+        assert_eq!(pick.estimated_ms, 1_000 + 540_000);
+    }
+
+    #[test]
+    fn without_wer_picks_slowest_model_within_target() {
+        let benchmarks = vec![
+            bench("base", 0.05, None),
+            bench("small", 0.15, Some(0.12)),
+            bench("medium", 0.3, None),
+        ];
+        assert_eq!(pick_model(&benchmarks, HOUR_MS, 20 * 60_000).unwrap().model, "medium");
+    }
+
+    #[test]
+    fn falls_back_to_fastest_model_when_none_meets_target() {
+        let benchmarks = vec![bench("small", 0.15, Some(0.12)), bench("medium", 0.3, Some(0.09))];
+        let pick = pick_model(&benchmarks, HOUR_MS, 60_000).unwrap();
+        assert_eq!(pick.model, "small");
+        assert!(!pick.meets_target);
+        assert!(pick_model(&[], HOUR_MS, 60_000).is_none());
+    }
+
+    #[test]
+    fn reference_only_from_hand_corrected_ranges() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::migrations::apply_pending(&mut conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO episodes (id, title) VALUES (1, 'Folge 1');
+             INSERT INTO diarization_segments (episode_id, start_ms, end_ms, speaker_label, text, edited)
+             VALUES (1, 0, 4000, 'SPEAKER_0', 'hallo zusammen', 1),
+                    (1, 4000, 8000, 'SPEAKER_1', ' danke schön ', 1),
+                    (1, 8000, 12000, 'SPEAKER_0', 'wie whisper es hörte', 0);",
+        )
+        .unwrap();
+
+        assert_eq!(
+            reference_text(&conn, 1, 0, 8_000).as_deref(),
+            Some("hallo zusammen danke schön")
+        );
+        // One uncorrected segment in the range: no reference, no WER
+        assert_eq!(reference_text(&conn, 1, 0, 12_000), None);
+        assert_eq!(reference_text(&conn, 1, 20_000, 30_000), None);
+    }
+}
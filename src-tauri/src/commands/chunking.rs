@@ -0,0 +1,201 @@
+use crate::commands::attribution::TimedWord;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Overlapping Whisper chunks
+//
+// Long episodes are transcribed in 20-minute chunks (memory, see
+// run_transcription_stage). Each chunk reaches CHUNK_OVERLAP_SAMPLES into the
+// next one, so a word cut at a chunk edge is heard in full by the neighbour.
+// The overlap is then stitched: an identical segment decoded by both chunks
+// anchors the seam; without one, the seam is the middle of the overlap and each
+// segment goes to the chunk that heard it furthest from an edge.
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Chunk length without overlap: 20 min at 16 kHz.
+pub(crate) const CHUNK_SAMPLES: usize = 20 * 60 * 16_000;
+
+/// Audio shared by neighbouring chunks: 30 s at 16 kHz.
+pub(crate) const CHUNK_OVERLAP_SAMPLES: usize = 30 * 16_000;
+
+/// Two decodes of the same words start at most this far apart.
+const ANCHOR_MAX_START_DIFF_MS: i64 = 1_000;
+
+/// One Whisper segment with episode-absolute timestamps.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct ChunkSegment {
+    pub text: String,
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub avg_logprob: f32,
+    pub no_speech_prob: f32,
+    /// Words with token timestamps, for word-level speaker attribution.
+    pub words: Vec<TimedWord>,
+}
+
+impl ChunkSegment {
+    fn center_ms(&self) -> i64 {
+        (self.start_ms + self.end_ms) / 2
+    }
+}
+
+/// Sample ranges `[start, end)` of all chunks. Every chunk but the last extends
+/// `overlap` samples into its successor. A remainder no longer than the
+/// overlap is already heard by the previous chunk and gets no chunk of its own.
+pub(crate) fn chunk_ranges(total_samples: usize, chunk: usize, overlap: usize) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<(usize, usize)> = (0..total_samples.div_ceil(chunk))
+        .map(|i| {
+            let start = i * chunk;
+            (start, (start + chunk + overlap).min(total_samples))
+        })
+        .collect();
+    if ranges.len() > 1 && total_samples - ranges[ranges.len() - 1].0 <= overlap {
+        ranges.pop();
+        if let Some(last) = ranges.last_mut() {
+            last.1 = total_samples;
+        }
+    }
+    ranges
+}
+
+fn normalized(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Append the segments of the next chunk to `stitched`. Both chunks decoded
+/// the overlap `[overlap_start_ms, overlap_end_ms)`; afterwards every part of
+/// it is covered exactly once.
+pub(crate) fn stitch_chunk(
+    stitched: &mut Vec<ChunkSegment>,
+    next: Vec<ChunkSegment>,
+    overlap_start_ms: i64,
+    overlap_end_ms: i64,
+) {
+    let seam = (overlap_start_ms + overlap_end_ms) / 2;
+
+    // Anchor: the same text decoded by both chunks at (nearly) the same time,
+    // preferably close to the seam where both chunks had the most context.
+    let anchor = stitched
+        .iter()
+        .enumerate()
+        .filter(|(_, prev)| prev.end_ms > overlap_start_ms)
+        .flat_map(|(i, prev)| {
+            next.iter()
+                .enumerate()
+                .filter(move |(_, cur)| {
+                    cur.start_ms < overlap_end_ms
+                        && (cur.start_ms - prev.start_ms).abs() <= ANCHOR_MAX_START_DIFF_MS
+                        && !normalized(&cur.text).is_empty()
+                        && normalized(&cur.text) == normalized(&prev.text)
+                })
+                .map(move |(j, cur)| (i, j, (cur.center_ms() - seam).abs()))
+        })
+        .min_by_key(|(_, _, distance)| *distance);
+
+    match anchor {
+        Some((i, j, _)) => {
+            // Keep the earlier chunk up to the anchor, the next one after it
+            stitched.truncate(i + 1);
+            stitched.extend(next.into_iter().skip(j + 1));
+        }
+        None => {
+            // Cut at the seam; a segment belongs to the chunk holding its center
+            stitched.retain(|prev| prev.center_ms() < seam);
+            stitched.extend(next.into_iter().filter(|cur| cur.center_ms() >= seam));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seg(text: &str, start_ms: i64, end_ms: i64) -> ChunkSegment {
+        ChunkSegment {
+            text: text.to_string(),
+            start_ms,
+            end_ms,
+            avg_logprob: -0.2,
+            no_speech_prob: 0.0,
+            words: vec![],
+        }
+    }
+
+    fn texts(segments: &[ChunkSegment]) -> Vec<&str> {
+        segments.iter().map(|s| s.text.as_str()).collect()
+    }
+
+    #[test]
+    fn chunks_overlap_except_the_last() {
+        assert_eq!(chunk_ranges(250, 100, 10), vec![(0, 110), (100, 210), (200, 250)]);
+        assert_eq!(chunk_ranges(100, 100, 10), vec![(0, 100)]);
+        // Remainder inside the previous chunk's overlap
+        assert_eq!(chunk_ranges(205, 100, 10), vec![(0, 110), (100, 205)]);
+        assert_eq!(chunk_ranges(210, 100, 10), vec![(0, 110), (100, 210)]);
+        assert_eq!(chunk_ranges(211, 100, 10), vec![(0, 110), (100, 210), (200, 211)]);
+        assert!(chunk_ranges(0, 100, 10).is_empty());
+    }
+
+    #[test]
+    fn shared_segment_anchors_the_seam() {
+        // Overlap 100 000–130 000 ms
+        let mut stitched = vec![
+            seg("Vorher.", 95_000, 99_000),
+            seg("Der Kranich zieht", 101_000, 104_000),
+            seg("im Herbst nach Süden.", 114_000, 118_000),
+            seg("Dann kommen", 126_000, 129_900), // cut at the chunk end
+        ];
+        let next = vec![
+            seg("n Kranich zieht", 100_000, 104_000), // cut at the chunk start
+            seg("im Herbst nach Süden.", 114_200, 118_000),
+            seg("Dann kommen die Gänse.", 126_000, 131_000),
+            seg("Danach.", 132_000, 134_000),
+        ];
+        stitch_chunk(&mut stitched, next, 100_000, 130_000);
+        assert_eq!(
+            texts(&stitched),
+            vec![
+                "Vorher.",
+                "Der Kranich zieht",
+                "im Herbst nach Süden.",
+                "Dann kommen die Gänse.",
+                "Danach."
+            ]
+        );
+    }
+
+    #[test]
+    fn without_anchor_each_segment_kept_once_by_its_center() {
+        let mut stitched = vec![
+            seg("Erster Teil", 98_000, 104_000),
+            seg("Zweiter Teil", 108_000, 113_000),
+            seg("Dritter Teil, abgeschn", 120_000, 130_000),
+        ];
+        let next = vec![
+            seg("eiter Teil", 100_000, 113_000),
+            seg("Dritter Teil, abgeschnitten", 120_000, 126_000),
+            seg("Vierter Teil", 131_000, 135_000),
+        ];
+        stitch_chunk(&mut stitched, next, 100_000, 130_000);
+        assert_eq!(
+            texts(&stitched),
+            vec!["Erster Teil", "Zweiter Teil", "Dritter Teil, abgeschnitten", "Vierter Teil"]
+        );
+    }
+
+    #[test]
+    fn anchor_requires_close_timestamps() {
+        // Same text twice in the overlap, but far apart in time: no anchor.
+        let mut stitched = vec![seg("Genau.", 102_000, 103_000)];
+        let next = vec![seg("Genau.", 127_000, 128_000)];
+        stitch_chunk(&mut stitched, next, 100_000, 130_000);
+        assert_eq!(texts(&stitched), vec!["Genau.", "Genau."]);
+    }
+
+    #[test]
+    fn silent_overlap_keeps_both_sides() {
+        let mut stitched = vec![seg("Ende der Folge", 90_000, 95_000)];
+        let next = vec![seg("Neuer Abschnitt", 140_000, 142_000)];
+        stitch_chunk(&mut stitched, next, 100_000, 130_000);
+        assert_eq!(texts(&stitched), vec!["Ende der Folge", "Neuer Abschnitt"]);
+    }
+}
@@ -0,0 +1,269 @@
+use crate::commands::diarization::{label_speakers, read_speaker_count, SpeakerCount, AUTO_CLUSTER_THRESHOLD};
+use crate::commands::editing::{load_snapshot, record_revision, write_snapshot};
+use crate::commands::voiceprint::{
+    cluster_centroids, cosine_similarity, decode_embedding, load_voiceprints, match_hosts, normalized,
+};
+use crate::models::diarization::{DiarizationSegment, ReclusterSummary, SegmentSnapshot};
+use crate::paths::BinkyPaths;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Re-clustering from stored embeddings
+//
+// sherpa-onnx discards its embeddings, so diarization stores one per segment
+// (migration 026). Re-clustering groups those embeddings again with another
+// speaker count or threshold and relabels the automatic segments in place —
+// text, corrections and manual edits stay untouched, no audio is decoded.
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Nearest active cluster of `i` by centroid cosine similarity.
+fn nearest(i: usize, sums: &[Vec<f32>], active: &[bool]) -> Option<(usize, f32)> {
+    (0..sums.len())
+        .filter(|&j| j != i && active[j])
+        .map(|j| (j, cosine_similarity(&sums[i], &sums[j])))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// Agglomerative clustering like sherpa-onnx's: merge the two most similar
+/// clusters (cosine similarity of their centroids) until `speaker_count`
+/// clusters remain or, for Auto, no pair is closer than the cosine distance
+/// `threshold`. Returns a cluster per embedding, numbered by first appearance.
+pub(crate) fn cluster_embeddings(
+    embeddings: &[Vec<f32>],
+    speaker_count: SpeakerCount,
+    threshold: f32,
+) -> Vec<usize> {
+    let n = embeddings.len();
+    let (target, min_similarity) = match speaker_count {
+        SpeakerCount::Auto => (1, 1.0 - threshold),
+        SpeakerCount::Fixed(k) => (k.max(1) as usize, f32::NEG_INFINITY),
+    };
+
+    // Sums of normalised embeddings point in the direction of the centroid
+    let mut sums: Vec<Vec<f32>> = embeddings.iter().map(|e| normalized(e)).collect();
+    let mut active = vec![true; n];
+    let mut cluster_of: Vec<usize> = (0..n).collect();
+    let mut best: Vec<Option<(usize, f32)>> = (0..n).map(|i| nearest(i, &sums, &active)).collect();
+
+    let mut clusters = n;
+    while clusters > target {
+        let closest = best
+            .iter()
+            .enumerate()
+            .filter_map(|(i, b)| b.map(|(j, similarity)| (i, j, similarity)))
+            .max_by(|a, b| a.2.total_cmp(&b.2));
+        let (i, j) = match closest {
+            Some((i, j, similarity)) if similarity >= min_similarity => (i, j),
+            _ => break,
+        };
+
+        let merged = std::mem::take(&mut sums[j]);
+        for (s, v) in sums[i].iter_mut().zip(merged) {
+            *s += v;
+        }
+        active[j] = false;
+        best[j] = None;
+        for c in cluster_of.iter_mut().filter(|c| **c == j) {
+            *c = i;
+        }
+        clusters -= 1;
+
+        // Only neighbours of the two merged clusters have to be searched again
+        best[i] = nearest(i, &sums, &active);
+        for k in (0..n).filter(|&k| k != i && active[k]) {
+            match best[k] {
+                Some((b, _)) if b == i || b == j => best[k] = nearest(k, &sums, &active),
+                Some((_, similarity)) => {
+                    let to_i = cosine_similarity(&sums[k], &sums[i]);
+                    if to_i > similarity {
+                        best[k] = Some((i, to_i));
+                    }
+                }
+                None => best[k] = nearest(k, &sums, &active),
+            }
+        }
+    }
+
+    let mut numbers: HashMap<usize, usize> = HashMap::new();
+    cluster_of
+        .iter()
+        .map(|c| {
+            let next = numbers.len();
+            *numbers.entry(*c).or_insert(next)
+        })
+        .collect()
+}
+
+/// Segments without an embedding follow the embedded segment before them (or
+/// after, at the start of the episode).
+fn fill_clusters(clusters: &[Option<usize>]) -> Vec<usize> {
+    let first = clusters.iter().flatten().next().copied().unwrap_or(0);
+    let mut previous = first;
+    clusters
+        .iter()
+        .map(|c| {
+            if let Some(c) = c {
+                previous = *c;
+            }
+            previous
+        })
+        .collect()
+}
+
+fn recluster(
+    db_path: &Path,
+    episode_id: i64,
+    speaker_count: SpeakerCount,
+    threshold: f32,
+) -> Result<ReclusterSummary, String> {
+    let mut conn = rusqlite::Connection::open(db_path).map_err(|e| e.to_string())?;
+
+    // (segment id, segment with its raw embedding), in start order
+    let rows: Vec<(i64, DiarizationSegment)> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, start_ms, end_ms, speaker_label, embedding FROM diarization_segments \
+                 WHERE episode_id = ?1 AND edited = 0 ORDER BY start_ms, id",
+            )
+            .map_err(|e| e.to_string())?;
+        let collected = stmt
+            .query_map(rusqlite::params![episode_id], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    DiarizationSegment {
+                        start_ms: row.get(1)?,
+                        end_ms: row.get(2)?,
+                        speaker_label: row.get(3)?,
+                        confidence: None,
+                        embedding: row.get::<_, Option<Vec<u8>>>(4)?.map(|b| decode_embedding(&b)),
+                    },
+                ))
+            })
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        collected
+    };
+
+    let embeddings: Vec<Vec<f32>> = rows.iter().filter_map(|(_, s)| s.embedding.clone()).collect();
+    if embeddings.is_empty() {
+        return Err(
+            "Für diese Episode sind keine Sprecher-Embeddings gespeichert, bitte neu diarisieren".to_string(),
+        );
+    }
+
+    let mut clusters = cluster_embeddings(&embeddings, speaker_count, threshold).into_iter();
+    let raw: Vec<Option<usize>> = rows
+        .iter()
+        .map(|(_, s)| s.embedding.as_ref().and_then(|_| clusters.next()))
+        .collect();
+    let (ids, mut segments): (Vec<i64>, Vec<DiarizationSegment>) = rows.into_iter().unzip();
+    for (segment, cluster) in segments.iter_mut().zip(fill_clusters(&raw)) {
+        segment.speaker_label = format!("SPEAKER_{}", cluster);
+    }
+
+    let voiceprints = load_voiceprints(db_path);
+    let host_matches = if voiceprints.is_empty() {
+        HashMap::new()
+    } else {
+        match_hosts(&cluster_centroids(&segments), &voiceprints)
+    };
+    // label_speakers sorts by start_ms with a stable sort; the rows are already
+    // in that order, so the ids still line up with the segments.
+    let enrolled_hosts: Vec<usize> = voiceprints.iter().map(|(host, _)| *host).collect();
+    let (segments, status) = label_speakers(segments, &host_matches, &enrolled_hosts, speaker_count);
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut before: Vec<SegmentSnapshot> = Vec::new();
+    let mut after: Vec<SegmentSnapshot> = Vec::new();
+    for (id, segment) in ids.iter().zip(&segments) {
+        let current = load_snapshot(&tx, *id)?;
+        if current.speaker_label == segment.speaker_label && current.confidence == segment.confidence {
+            continue;
+        }
+        let updated = SegmentSnapshot {
+            speaker_label: segment.speaker_label.clone(),
+            confidence: segment.confidence,
+            ..current.clone()
+        };
+        write_snapshot(&tx, &updated)?;
+        before.push(current);
+        after.push(updated);
+    }
+    if !after.is_empty() {
+        record_revision(&tx, episode_id, "recluster", &before, &after)?;
+    }
+    tx.execute(
+        "UPDATE episodes SET diarization_status = ?1, updated_at = datetime('now') WHERE id = ?2",
+        rusqlite::params![status, episode_id],
+    )
+    .map_err(|e| e.to_string())?;
+    tx.commit().map_err(|e| e.to_string())?;
+    crate::commands::overlap::refresh_overlap_segments(db_path, episode_id)?;
+
+    Ok(ReclusterSummary {
+        speakers: segments.iter().map(|s| s.speaker_label.as_str()).collect::<HashSet<_>>().len(),
+        changed_segments: after.len(),
+        status: status.to_string(),
+    })
+}
+
+/// Re-cluster an episode's automatic segments from their stored embeddings
+/// with another speaker count ("auto" or 1 to 10, default: the episode's) or
+/// clustering threshold (cosine distance, lower = more speakers). The change
+/// is recorded as a revision and can be undone.
+#[tauri::command]
+pub async fn recluster_episode(
+    episode_id: i64,
+    speaker_count: Option<String>,
+    threshold: Option<f32>,
+    app: tauri::AppHandle,
+) -> Result<ReclusterSummary, String> {
+    let db_path = BinkyPaths::from_app(&app)?.db_path;
+    let speaker_count = match speaker_count.filter(|c| !c.trim().is_empty()) {
+        Some(value) => SpeakerCount::parse(&value)?,
+        None => read_speaker_count(&db_path, episode_id),
+    };
+    let threshold = threshold.unwrap_or(AUTO_CLUSTER_THRESHOLD);
+    if !(threshold > 0.0 && threshold < 1.0) {
+        return Err("Schwellwert muss zwischen 0 und 1 liegen".to_string());
+    }
+
+    tauri::async_runtime::spawn_blocking(move || recluster(&db_path, episode_id, speaker_count, threshold))
+        .await
+        .map_err(|e| format!("Re-clustering task panicked: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn voices() -> Vec<Vec<f32>> {
+        vec![
+            vec![1.0, 0.1, 0.0],
+            vec![0.0, 1.0, 0.1],
+            vec![0.9, 0.2, 0.0],
+            vec![0.1, 0.9, 0.0],
+            vec![0.0, 0.1, 1.0],
+        ]
+    }
+
+    #[test]
+    fn threshold_decides_the_number_of_speakers() {
+        assert_eq!(cluster_embeddings(&voices(), SpeakerCount::Auto, 0.5), vec![0, 1, 0, 1, 2]);
+        // A tiny threshold keeps every segment apart
+        assert_eq!(cluster_embeddings(&voices(), SpeakerCount::Auto, 0.001), vec![0, 1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn fixed_count_merges_down_to_that_many_speakers() {
+        assert_eq!(cluster_embeddings(&voices(), SpeakerCount::Fixed(2), 0.5).iter().max(), Some(&1));
+        assert_eq!(cluster_embeddings(&voices(), SpeakerCount::Fixed(1), 0.5), vec![0; 5]);
+    }
+
+    #[test]
+    fn unembedded_segments_follow_their_neighbour() {
+        assert_eq!(fill_clusters(&[None, Some(1), None, Some(0), None]), vec![1, 1, 1, 0, 0]);
+    }
+}
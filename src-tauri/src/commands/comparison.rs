@@ -0,0 +1,350 @@
+use crate::models::transcript::{TranscriptAlternative, TranscriptComparison, WordDiffChunk};
+use std::collections::HashMap;
+use std::iter::repeat_n;
+use tauri::Manager;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Comparing transcripts
+//
+// Every transcription run is kept in transcript_alternatives (migration 022).
+// Two runs are compared word by word: words are lowercased and stripped of
+// punctuation, aligned with a minimum edit distance alignment and counted as
+// substitutions, deletions and insertions. The alignment uses Hirschberg's
+// algorithm so two-hour episodes (20k+ words each) fit in linear memory.
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EditOp {
+    Equal,
+    Substitute,
+    Delete,
+    Insert,
+}
+
+impl EditOp {
+    fn as_str(&self) -> &'static str {
+        match self {
+            EditOp::Equal => "equal",
+            EditOp::Substitute => "substitute",
+            EditOp::Delete => "delete",
+            EditOp::Insert => "insert",
+        }
+    }
+}
+
+/// Comparison key of a word: lowercase letters and digits only ("Kranich," → "kranich").
+fn normalize_word(word: &str) -> String {
+    word.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// Words of `text` as (as written, interned key). Tokens without letters or
+/// digits ("–", "...") are skipped.
+fn tokenize(text: &str, vocab: &mut HashMap<String, u32>) -> Vec<(String, u32)> {
+    text.split_whitespace()
+        .filter_map(|word| {
+            let key = normalize_word(word);
+            if key.is_empty() {
+                return None;
+            }
+            let next_id = vocab.len() as u32;
+            let id = *vocab.entry(key).or_insert(next_id);
+            Some((word.to_string(), id))
+        })
+        .collect()
+}
+
+/// Edit distances between all of `a` and every prefix of `b`.
+fn last_row(a: &[u32], b: &[u32]) -> Vec<usize> {
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, y) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if x == y {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row
+}
+
+/// Minimum edit alignment of `a` (reference) and `b` (hypothesis), appended to `ops`.
+fn align(a: &[u32], b: &[u32], ops: &mut Vec<EditOp>) {
+    if a.is_empty() {
+        ops.extend(repeat_n(EditOp::Insert, b.len()));
+        return;
+    }
+    if b.is_empty() {
+        ops.extend(repeat_n(EditOp::Delete, a.len()));
+        return;
+    }
+    if a.len() == 1 {
+        match b.iter().position(|y| *y == a[0]) {
+            Some(k) => {
+                ops.extend(repeat_n(EditOp::Insert, k));
+                ops.push(EditOp::Equal);
+                ops.extend(repeat_n(EditOp::Insert, b.len() - k - 1));
+            }
+            None => {
+                ops.push(EditOp::Substitute);
+                ops.extend(repeat_n(EditOp::Insert, b.len() - 1));
+            }
+        }
+        return;
+    }
+
+    // Split `a` in the middle and find where the optimal path crosses it
+    let mid = a.len() / 2;
+    let forward = last_row(&a[..mid], b);
+    let a_rev: Vec<u32> = a[mid..].iter().rev().copied().collect();
+    let b_rev: Vec<u32> = b.iter().rev().copied().collect();
+    let backward = last_row(&a_rev, &b_rev);
+    let split = (0..=b.len())
+        .min_by_key(|&j| forward[j] + backward[b.len() - j])
+        .unwrap_or(0);
+    align(&a[..mid], &b[..split], ops);
+    align(&a[mid..], &b[split..], ops);
+}
+
+/// Alignment of two word sequences. Identical beginnings and endings (the
+/// common case for two runs over the same audio) skip the quadratic part.
+fn align_words(a: &[u32], b: &[u32]) -> Vec<EditOp> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let mut ops = vec![EditOp::Equal; prefix];
+    align(&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix], &mut ops);
+    ops.extend(repeat_n(EditOp::Equal, suffix));
+    ops
+}
+
+/// WER and word diff of `hypothesis` against `reference`.
+pub(crate) fn compare_texts(
+    reference_id: i64,
+    hypothesis_id: i64,
+    reference: &str,
+    hypothesis: &str,
+) -> TranscriptComparison {
+    let mut vocab = HashMap::new();
+    let reference = tokenize(reference, &mut vocab);
+    let hypothesis = tokenize(hypothesis, &mut vocab);
+    let a: Vec<u32> = reference.iter().map(|(_, id)| *id).collect();
+    let b: Vec<u32> = hypothesis.iter().map(|(_, id)| *id).collect();
+
+    let mut comparison = TranscriptComparison {
+        reference_id,
+        hypothesis_id,
+        reference_words: reference.len(),
+        hypothesis_words: hypothesis.len(),
+        substitutions: 0,
+        deletions: 0,
+        insertions: 0,
+        wer: 0.0,
+        diff: vec![],
+    };
+
+    let (mut i, mut j) = (0, 0);
+    for op in align_words(&a, &b) {
+        let ref_word = matches!(op, EditOp::Equal | EditOp::Substitute | EditOp::Delete)
+            .then(|| reference[i].0.clone());
+        let hyp_word = matches!(op, EditOp::Equal | EditOp::Substitute | EditOp::Insert)
+            .then(|| hypothesis[j].0.clone());
+        match op {
+            EditOp::Equal => {}
+            EditOp::Substitute => comparison.substitutions += 1,
+            EditOp::Delete => comparison.deletions += 1,
+            EditOp::Insert => comparison.insertions += 1,
+        }
+        i += ref_word.is_some() as usize;
+        j += hyp_word.is_some() as usize;
+
+        if comparison.diff.last().map(|c| c.op != op.as_str()).unwrap_or(true) {
+            comparison.diff.push(WordDiffChunk {
+                op: op.as_str().to_string(),
+                reference: vec![],
+                hypothesis: vec![],
+            });
+        }
+        if let Some(chunk) = comparison.diff.last_mut() {
+            chunk.reference.extend(ref_word);
+            chunk.hypothesis.extend(hyp_word);
+        }
+    }
+
+    let errors = comparison.substitutions + comparison.deletions + comparison.insertions;
+    comparison.wer = errors as f64 / comparison.reference_words.max(1) as f64;
+    comparison
+}
+
+/// All stored transcription runs of an episode, newest first.
+#[tauri::command]
+pub async fn list_transcript_alternatives(
+    episode_id: i64,
+    app: tauri::AppHandle,
+) -> Result<Vec<TranscriptAlternative>, String> {
+    let db_path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Cannot resolve app data dir: {}", e))?
+        .join("binky.db");
+
+    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, episode_id, engine, model, quality_preset, language, full_text, created_at \
+             FROM transcript_alternatives WHERE episode_id = ?1 ORDER BY id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+    let alternatives = stmt
+        .query_map(rusqlite::params![episode_id], |row| {
+            let full_text: String = row.get(6)?;
+            Ok(TranscriptAlternative {
+                id: row.get(0)?,
+                episode_id: row.get(1)?,
+                engine: row.get(2)?,
+                model: row.get(3)?,
+                quality_preset: row.get(4)?,
+                language: row.get(5)?,
+                word_count: full_text.split_whitespace().count(),
+                created_at: row.get(7)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(alternatives)
+}
+
+/// Compare two transcription runs of the same episode. `reference_id` is the
+/// run taken as correct; the WER is that of `hypothesis_id`.
+#[tauri::command]
+pub async fn compare_transcripts(
+    reference_id: i64,
+    hypothesis_id: i64,
+    app: tauri::AppHandle,
+) -> Result<TranscriptComparison, String> {
+    let db_path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Cannot resolve app data dir: {}", e))?
+        .join("binky.db");
+
+    let (reference, hypothesis) = {
+        let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
+        let load = |id: i64| -> Result<(i64, String), String> {
+            conn.query_row(
+                "SELECT episode_id, full_text FROM transcript_alternatives WHERE id = ?1",
+                rusqlite::params![id],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|_| format!("Transkript {} nicht gefunden", id))
+        };
+        (load(reference_id)?, load(hypothesis_id)?)
+    };
+    if reference.0 != hypothesis.0 {
+        return Err("Die Transkripte gehören zu verschiedenen Episoden".to_string());
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        compare_texts(reference_id, hypothesis_id, &reference.1, &hypothesis.1)
+    })
+    .await
+    .map_err(|e| format!("Vergleich fehlgeschlagen: {}", e))
+}
+
+/// Delete a stored transcription run. The active transcript is not affected.
+#[tauri::command]
+pub async fn delete_transcript_alternative(id: i64, app: tauri::AppHandle) -> Result<(), String> {
+    let db_path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Cannot resolve app data dir: {}", e))?
+        .join("binky.db");
+
+    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
+    conn.execute(
+        "DELETE FROM transcript_alternatives WHERE id = ?1",
+        rusqlite::params![id],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ops(chunks: &[WordDiffChunk]) -> Vec<&str> {
+        chunks.iter().map(|c| c.op.as_str()).collect()
+    }
+
+    /// Plain O(n·m) Levenshtein distance for cross-checking the alignment.
+    fn distance(a: &[u32], b: &[u32]) -> usize {
+        last_row(a, b)[b.len()]
+    }
+
+    #[test]
+    fn identical_texts_ignore_case_and_punctuation() {
+        let c = compare_texts(1, 2, "Der Kranich zieht.", "der Kranich – zieht");
+        assert_eq!(c.wer, 0.0);
+        assert_eq!(ops(&c.diff), vec!["equal"]);
+        assert_eq!(c.diff[0].hypothesis, vec!["der", "Kranich", "zieht"]);
+    }
+
+    #[test]
+    fn counts_each_kind_of_error() {
+        let c = compare_texts(
+            1,
+            2,
+            "heute geht es um den Eisvogel im Winter",
+            "heute geht um den großen Eisvogel im Sommer",
+        );
+        assert_eq!((c.substitutions, c.deletions, c.insertions), (1, 1, 1));
+        assert!((c.wer - 3.0 / 8.0).abs() < 1e-9);
+        assert_eq!(
+            ops(&c.diff),
+            vec!["equal", "delete", "equal", "insert", "equal", "substitute"]
+        );
+        assert_eq!(c.diff[5].reference, vec!["Winter"]);
+        assert_eq!(c.diff[5].hypothesis, vec!["Sommer"]);
+    }
+
+    #[test]
+    fn empty_reference_counts_insertions() {
+        let c = compare_texts(1, 2, "", "Untertitel im Auftrag");
+        assert_eq!(c.insertions, 3);
+        assert_eq!(c.wer, 3.0);
+        assert_eq!(compare_texts(1, 2, "", "").wer, 0.0);
+    }
+
+    #[test]
+    fn alignment_is_minimal() {
+        // Pseudo-random sequences over a small vocabulary
+        let mut seed = 7u32;
+        let mut next = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+            (seed >> 16) % 5
+        };
+        for len in [1, 2, 7, 30, 61] {
+            let a: Vec<u32> = (0..len).map(|_| next()).collect();
+            let b: Vec<u32> = (0..len + 3).map(|_| next()).collect();
+            let ops = align_words(&a, &b);
+            let cost = ops.iter().filter(|op| **op != EditOp::Equal).count();
+            assert_eq!(cost, distance(&a, &b));
+            let consumed_a = ops.iter().filter(|op| **op != EditOp::Insert).count();
+            let consumed_b = ops.iter().filter(|op| **op != EditOp::Delete).count();
+            assert_eq!((consumed_a, consumed_b), (a.len(), b.len()));
+        }
+    }
+}
@@ -1,38 +1,39 @@
+use crate::commands::diarization_models::{
+    is_downloaded, model_path, read_provider, selected_models, DiarizationSetup,
+};
+#[cfg(feature = "app")]
+use crate::commands::diarization_models::verify_download;
+use crate::models::diarization::DiarizationEvent;
+#[cfg(feature = "app")]
 use crate::models::diarization::{
-    DiarizationEvent, DiarizationModelDownloadEvent, DiarizationModelStatus, DiarizationQueueStatus,
+    DiarizationModelDownloadEvent, DiarizationModelStatus, DiarizationQueueStatus,
 };
-use crate::state::diarization_queue::{DiarizationJob, DiarizationState};
+#[cfg(feature = "app")]
+use crate::models::pipeline::Stage;
+use crate::paths::BinkyPaths;
+use crate::progress::Progress;
+use crate::state::job_engine::StageResult;
+#[cfg(feature = "app")]
+use crate::state::job_engine::{EngineState, PipelineJob};
+#[cfg(feature = "app")]
 use futures_util::StreamExt;
+#[cfg(feature = "app")]
 use std::sync::Arc;
+#[cfg(feature = "app")]
 use tauri::ipc::Channel;
+#[cfg(feature = "app")]
 use tauri::Manager;
-use tauri_plugin_http::reqwest;
+#[cfg(feature = "app")]
 use tokio::io::AsyncWriteExt;
 use tokio_util::sync::CancellationToken;
 
-// ─────────────────────────────────────────────────────────────────────────────
-// Model URLs (verified 2026-02-16)
-// Segmentation: tar.bz2 archive (no standalone .onnx available)
-// Embedding: direct .onnx file
-//
-// NOTE (2026-03-11): nemo_en_titanet_large.onnx (101 MB) was tested as the
-// embedding model but is unusably slow: sherpa-onnx 1.12.9 hard-codes
-// num_threads=1 and the bundled libonnxruntime has no CoreML/Metal backend.
-// Single-threaded CPU inference on NeMo TitaNet Large takes 6+ hours for a
-// 60-minute episode.  Reverted to wespeaker_en_voxceleb_resnet34_LM.onnx
-// (26 MB) which completes diarization in ~3-5 minutes on the same hardware.
-// ─────────────────────────────────────────────────────────────────────────────
-
-const SEGMENTATION_URL: &str = "https://github.com/k2-fsa/sherpa-onnx/releases/download/speaker-segmentation-models/sherpa-onnx-pyannote-segmentation-3-0.tar.bz2";
-const EMBEDDING_URL: &str = "https://github.com/k2-fsa/sherpa-onnx/releases/download/speaker-recongition-models/wespeaker_en_voxceleb_resnet34_LM.onnx";
-const EMBEDDING_FILENAME: &str = "wespeaker_en_voxceleb_resnet34_LM.onnx";
-
 // ─────────────────────────────────────────────────────────────────────────────
 // Helper: download a URL with streaming progress, writing to a tmp file,
 // then atomically rename to dest_path on success.
 // Progress is mapped to [base_offset, base_offset + 50].
 // ─────────────────────────────────────────────────────────────────────────────
 
+#[cfg(feature = "app")]
 async fn download_with_progress(
     url: &str,
     tmp_path: &std::path::Path,
@@ -105,109 +106,97 @@ async fn download_with_progress(
 // Model management commands
 // ─────────────────────────────────────────────────────────────────────────────
 
-/// Check which diarization models are present in the app local data directory.
-/// Segmentation model: models/diarization/segmentation/model.onnx (size > 0)
-/// Embedding model: models/diarization/embedding/*.onnx (any .onnx file, size > 0)
+/// Check whether the selected diarization models are downloaded (see
+/// diarization_models.rs for where they live).
+#[cfg(feature = "app")]
 #[tauri::command]
 pub async fn get_diarization_model_status(
     app: tauri::AppHandle,
 ) -> Result<DiarizationModelStatus, String> {
-    let models_dir = app
-        .path()
-        .app_local_data_dir()
-        .map_err(|e| format!("Cannot resolve app local data dir: {}", e))?
-        .join("models")
-        .join("diarization");
-
-    let models_dir_str = models_dir.to_string_lossy().to_string();
-
-    // Check segmentation model
-    let seg_model = models_dir.join("segmentation").join("model.onnx");
-    let segmentation_downloaded = if seg_model.exists() {
-        match tokio::fs::metadata(&seg_model).await {
-            Ok(m) => m.len() > 0,
-            Err(_) => false,
-        }
-    } else {
-        false
-    };
-
-    // Check embedding model: the specific expected file must exist with size > 0
-    let emb_path = models_dir.join("embedding").join(EMBEDDING_FILENAME);
-    let embedding_downloaded = if emb_path.exists() {
-        match tokio::fs::metadata(&emb_path).await {
-            Ok(m) => m.len() > 0,
-            Err(_) => false,
-        }
-    } else {
-        false
-    };
+    let paths = BinkyPaths::from_app(&app)?;
+    let models_dir = paths.diarization_models_dir();
+    let (segmentation, embedding) = selected_models(&paths.db_path);
 
     Ok(DiarizationModelStatus {
-        segmentation_downloaded,
-        embedding_downloaded,
-        models_dir: models_dir_str,
+        segmentation_downloaded: is_downloaded(&models_dir, segmentation),
+        embedding_downloaded: is_downloaded(&models_dir, embedding),
+        models_dir: models_dir.to_string_lossy().to_string(),
     })
 }
 
-/// Download both diarization models with streaming progress events.
+/// Download the selected diarization models with streaming progress events,
+/// skipping one that is already there. Downloads with a pinned hash are
+/// verified before they are installed.
 /// Segmentation (progress 0–50%): downloaded as tar.bz2 and extracted.
 /// Embedding (progress 50–100%): direct .onnx file.
+#[cfg(feature = "app")]
 #[tauri::command]
 pub async fn download_diarization_models(
     app: tauri::AppHandle,
     on_event: Channel<DiarizationModelDownloadEvent>,
 ) -> Result<(), String> {
-    let base_dir = app
-        .path()
-        .app_local_data_dir()
-        .map_err(|e| format!("Cannot resolve app local data dir: {}", e))?
-        .join("models")
-        .join("diarization");
+    let paths = BinkyPaths::from_app(&app)?;
+    let base_dir = paths.diarization_models_dir();
+    let (segmentation, embedding) = selected_models(&paths.db_path);
+
+    let fail = |msg: String| {
+        let _ = on_event.send(DiarizationModelDownloadEvent::Error { message: msg.clone() });
+        msg
+    };
 
     // ── Segmentation model (0–50%) ─────────────────────────────────────────
 
-    let seg_dir = base_dir.join("segmentation");
-    tokio::fs::create_dir_all(&seg_dir)
-        .await
-        .map_err(|e| format!("Failed to create segmentation dir: {}", e))?;
-
-    let seg_tmp = seg_dir.join("model.tar.bz2.tmp");
-
-    download_with_progress(SEGMENTATION_URL, &seg_tmp, 0, &on_event).await?;
-
-    // Extract the tar.bz2: strip top-level directory, extract model.onnx into seg_dir
-    let seg_tmp_str = seg_tmp.to_string_lossy().to_string();
-    let seg_dir_str = seg_dir.to_string_lossy().to_string();
-
-    let extract_result = tokio::process::Command::new("tar")
-        .args([
-            "-xjf",
-            &seg_tmp_str,
-            "--strip-components=1",
-            "-C",
-            &seg_dir_str,
-        ])
-        .output()
-        .await
-        .map_err(|e| format!("Failed to run tar: {}", e))?;
-
-    // Clean up tmp archive regardless of extraction outcome
-    let _ = tokio::fs::remove_file(&seg_tmp).await;
-
-    if !extract_result.status.success() {
-        let stderr = String::from_utf8_lossy(&extract_result.stderr);
-        let msg = format!("Tar extraction failed: {}", stderr);
-        let _ = on_event.send(DiarizationModelDownloadEvent::Error { message: msg.clone() });
-        return Err(msg);
-    }
+    if !is_downloaded(&base_dir, segmentation) {
+        let seg_model = model_path(&base_dir, segmentation);
+        let seg_dir = seg_model
+            .parent()
+            .map(|p| p.to_path_buf())
+            .unwrap_or_else(|| base_dir.join("segmentation"));
+        tokio::fs::create_dir_all(&seg_dir)
+            .await
+            .map_err(|e| format!("Failed to create segmentation dir: {}", e))?;
 
-    // Verify extracted model.onnx exists
-    let seg_model = seg_dir.join("model.onnx");
-    if !seg_model.exists() {
-        let msg = "Segmentation model.onnx not found after extraction".to_string();
-        let _ = on_event.send(DiarizationModelDownloadEvent::Error { message: msg.clone() });
-        return Err(msg);
+        let seg_tmp = seg_dir.join("model.tar.bz2.tmp");
+
+        download_with_progress(segmentation.url, &seg_tmp, 0, &on_event).await?;
+
+        let verify_path = seg_tmp.clone();
+        let verified = tauri::async_runtime::spawn_blocking(move || verify_download(&verify_path, segmentation))
+            .await
+            .map_err(|e| e.to_string())?;
+        if let Err(e) = verified {
+            let _ = tokio::fs::remove_file(&seg_tmp).await;
+            return Err(fail(e));
+        }
+
+        // Extract the tar.bz2: strip top-level directory, extract model.onnx into seg_dir
+        let seg_tmp_str = seg_tmp.to_string_lossy().to_string();
+        let seg_dir_str = seg_dir.to_string_lossy().to_string();
+
+        let extract_result = tokio::process::Command::new("tar")
+            .args([
+                "-xjf",
+                &seg_tmp_str,
+                "--strip-components=1",
+                "-C",
+                &seg_dir_str,
+            ])
+            .output()
+            .await
+            .map_err(|e| format!("Failed to run tar: {}", e))?;
+
+        // Clean up tmp archive regardless of extraction outcome
+        let _ = tokio::fs::remove_file(&seg_tmp).await;
+
+        if !extract_result.status.success() {
+            let stderr = String::from_utf8_lossy(&extract_result.stderr);
+            return Err(fail(format!("Tar extraction failed: {}", stderr)));
+        }
+
+        // Verify extracted model.onnx exists
+        if !seg_model.exists() {
+            return Err(fail("Segmentation model.onnx not found after extraction".to_string()));
+        }
     }
 
     // Send progress at 50% after extraction
@@ -218,23 +207,30 @@ pub async fn download_diarization_models(
 
     // ── Embedding model (50–100%) ──────────────────────────────────────────
 
-    let emb_dir = base_dir.join("embedding");
-    tokio::fs::create_dir_all(&emb_dir)
-        .await
-        .map_err(|e| format!("Failed to create embedding dir: {}", e))?;
+    if !is_downloaded(&base_dir, embedding) {
+        let emb_dest = model_path(&base_dir, embedding);
+        let emb_dir = base_dir.join("embedding");
+        tokio::fs::create_dir_all(&emb_dir)
+            .await
+            .map_err(|e| format!("Failed to create embedding dir: {}", e))?;
 
-    let emb_tmp = emb_dir.join(format!("{}.tmp", EMBEDDING_FILENAME));
-    let emb_dest = emb_dir.join(EMBEDDING_FILENAME);
+        let emb_tmp = emb_dir.join(format!("{}.onnx.tmp", embedding.id));
 
-    download_with_progress(EMBEDDING_URL, &emb_tmp, 50, &on_event).await?;
+        download_with_progress(embedding.url, &emb_tmp, 50, &on_event).await?;
 
-    tokio::fs::rename(&emb_tmp, &emb_dest)
-        .await
-        .map_err(|e| {
-            let msg = format!("Failed to rename embedding model: {}", e);
-            let _ = on_event.send(DiarizationModelDownloadEvent::Error { message: msg.clone() });
-            msg
-        })?;
+        let verify_path = emb_tmp.clone();
+        let verified = tauri::async_runtime::spawn_blocking(move || verify_download(&verify_path, embedding))
+            .await
+            .map_err(|e| e.to_string())?;
+        if let Err(e) = verified {
+            let _ = tokio::fs::remove_file(&emb_tmp).await;
+            return Err(fail(e));
+        }
+
+        tokio::fs::rename(&emb_tmp, &emb_dest)
+            .await
+            .map_err(|e| fail(format!("Failed to rename embedding model: {}", e)))?;
+    }
 
     let _ = on_event.send(DiarizationModelDownloadEvent::Done);
 
@@ -242,6 +238,7 @@ pub async fn download_diarization_models(
 }
 
 /// Delete all downloaded diarization models.
+#[cfg(feature = "app")]
 #[tauri::command]
 pub async fn delete_diarization_models(app: tauri::AppHandle) -> Result<(), String> {
     let models_dir = app
@@ -264,32 +261,31 @@ pub async fn delete_diarization_models(app: tauri::AppHandle) -> Result<(), Stri
 // Diarization engine helpers
 // ─────────────────────────────────────────────────────────────────────────────
 
-/// Find the diarization model paths. Returns (segmentation_path, embedding_path).
-async fn find_diarization_models(
-    app: &tauri::AppHandle,
-) -> Result<(std::path::PathBuf, std::path::PathBuf), String> {
-    let models_dir = app
-        .path()
-        .app_local_data_dir()
-        .map_err(|e| format!("Cannot resolve app local data dir: {}", e))?
-        .join("models")
-        .join("diarization");
-
-    let seg_path = models_dir.join("segmentation").join("model.onnx");
-    let emb_path = models_dir.join("embedding").join(EMBEDDING_FILENAME);
+/// The selected diarization models with their paths and the runtime options.
+/// Fails if a model is not downloaded.
+pub(crate) async fn find_diarization_models(paths: &BinkyPaths) -> Result<DiarizationSetup, String> {
+    let models_dir = paths.diarization_models_dir();
+    let (segmentation, embedding) = selected_models(&paths.db_path);
 
-    if !seg_path.exists() {
+    if !is_downloaded(&models_dir, segmentation) {
         return Err("Segmentierungsmodell nicht heruntergeladen. Bitte zuerst die Diarisierungsmodelle in den Einstellungen herunterladen.".to_string());
     }
-    if !emb_path.exists() {
+    if !is_downloaded(&models_dir, embedding) {
         return Err("Erkennungsmodell nicht heruntergeladen. Bitte zuerst die Diarisierungsmodelle in den Einstellungen herunterladen.".to_string());
     }
 
-    Ok((seg_path, emb_path))
+    Ok(DiarizationSetup {
+        segmentation,
+        embedding,
+        seg_path: model_path(&models_dir, segmentation),
+        emb_path: model_path(&models_dir, embedding),
+        num_threads: crate::commands::pipeline::read_cpu_budget(&paths.db_path).sherpa_threads,
+        provider: read_provider(&paths.db_path),
+    })
 }
 
 /// Update diarization_status and diarization_error columns for an episode.
-fn update_diarization_status(
+pub(crate) fn update_diarization_status(
     db_path: &std::path::Path,
     episode_id: i64,
     status: &str,
@@ -315,6 +311,7 @@ fn update_diarization_status(
 /// Runs unconditionally (not just for text IS NULL) so that episodes already
 /// backfilled with the old buggy overlap logic are also corrected.
 /// Called once at app startup. Idempotent — safe to re-run.
+#[cfg(feature = "app")]
 pub(crate) fn backfill_all_whisper_segment_text(db_path: &std::path::Path) {
     let conn = match rusqlite::Connection::open(db_path) {
         Ok(c) => c,
@@ -344,10 +341,21 @@ pub(crate) fn backfill_all_whisper_segment_text(db_path: &std::path::Path) {
     }
 }
 
-/// After storing diarization segments, populate the `text` column by joining
-/// with Whisper's `transcripts.segments_json` on time overlap.
+/// After storing diarization segments, populate the `text` column from
+/// Whisper's `transcripts.segments_json`, word by word (see attribution.rs).
 /// Silent no-op if no Whisper transcript exists (AssemblyAI path or not yet transcribed).
-fn backfill_segment_text_from_whisper(db_path: &std::path::Path, episode_id: i64) {
+pub(crate) fn backfill_segment_text_from_whisper(db_path: &std::path::Path, episode_id: i64) {
+    backfill_segment_text_in_range(db_path, episode_id, None);
+}
+
+/// Like backfill_segment_text_from_whisper, but with `Some((start_ms, end_ms))`
+/// only diarization segments overlapping that range get new text; the words
+/// are still attributed against all segments so the range edges stay stable.
+pub(crate) fn backfill_segment_text_in_range(
+    db_path: &std::path::Path,
+    episode_id: i64,
+    range: Option<(i64, i64)>,
+) {
     let conn = match rusqlite::Connection::open(db_path) {
         Ok(c) => c,
         Err(_) => return,
@@ -367,27 +375,23 @@ fn backfill_segment_text_from_whisper(db_path: &std::path::Path, episode_id: i64
         None => return,
     };
 
-    // Parse [{text, start_ms, end_ms}, ...] from Whisper output
-    let whisper_segs: Vec<(i64, i64, String)> =
+    // Parse [{text, start_ms, end_ms, words?}, ...] from Whisper output into
+    // timed words (whole segments for transcripts without word timestamps)
+    let pieces: Vec<crate::commands::attribution::TimedWord> =
         match serde_json::from_str::<serde_json::Value>(&segments_json) {
             Ok(serde_json::Value::Array(arr)) => arr
-                .into_iter()
-                .filter_map(|v| {
-                    let start = v["start_ms"].as_i64()?;
-                    let end = v["end_ms"].as_i64()?;
-                    let text = v["text"].as_str()?.trim().to_string();
-                    if text.is_empty() { None } else { Some((start, end, text)) }
-                })
+                .iter()
+                .flat_map(crate::commands::attribution::timed_pieces)
                 .collect(),
             _ => return,
         };
 
-    if whisper_segs.is_empty() {
+    if pieces.is_empty() {
         return;
     }
 
     // Fetch ALL diarization segments (regardless of text status) so we can
-    // assign each Whisper segment to exactly one speaker window.
+    // assign each word to exactly one speaker window.
     let diar_segs: Vec<(i64, i64, i64)> = {
         let mut stmt = match conn.prepare(
             "SELECT id, start_ms, end_ms FROM diarization_segments \
@@ -414,590 +418,576 @@ fn backfill_segment_text_from_whisper(db_path: &std::path::Path, episode_id: i64
 
     // Clear existing text on all diarization segments for this episode so that
     // segments which receive no assignment (no overlapping Whisper segment) don't
-    // retain stale text from a previous buggy backfill run.
+    // retain stale text from a previous buggy backfill run. Manually edited
+    // segments keep their text.
+    let (range_start, range_end) = match range {
+        Some((start_ms, end_ms)) => (Some(start_ms), Some(end_ms)),
+        None => (None, None),
+    };
     let _ = conn.execute(
-        "UPDATE diarization_segments SET text = NULL WHERE episode_id = ?1",
-        rusqlite::params![episode_id],
+        "UPDATE diarization_segments SET text = NULL WHERE episode_id = ?1 AND edited = 0 \
+         AND (?2 IS NULL OR (start_ms < ?3 AND end_ms > ?2))",
+        rusqlite::params![episode_id, range_start, range_end],
     );
+    let in_range = |seg_id: i64| match range {
+        Some((start_ms, end_ms)) => diar_segs
+            .iter()
+            .any(|(id, start, end)| *id == seg_id && *start < end_ms && *end > start_ms),
+        None => true,
+    };
 
-    // Winner-takes-all assignment: each Whisper segment belongs to exactly one
-    // diarization window — the one with the greatest millisecond overlap.
-    // This prevents the same sentence from appearing under both speakers when a
-    // Whisper segment straddles a speaker boundary.
-    //
-    // Build a map: diarization segment id → Vec<text>
-    let mut text_map: std::collections::HashMap<i64, Vec<String>> = std::collections::HashMap::new();
-
-    for (ws_start, ws_end, ws_text) in &whisper_segs {
-        let ws_duration = ws_end - ws_start;
-        if ws_duration <= 0 {
-            continue;
-        }
-
-        // Find the diarization segment with the maximum PROPORTIONAL overlap.
-        //
-        // Proportional = overlap_ms / diarization_window_duration_ms
-        //
-        // Why proportional instead of absolute?
-        // sherpa-rs produces large SPEAKER_0 windows (30-60s) that temporally
-        // envelop many short SPEAKER_1 windows (300-1000ms).  A ~1.8s average
-        // Whisper segment always has more absolute ms overlap with the big
-        // SPEAKER_0 window than with the 1s SPEAKER_1 window it actually
-        // fills — so absolute wins always go to SPEAKER_0, leaving all
-        // SPEAKER_1 segments with NULL text.
-        //
-        // With proportional overlap a Whisper segment covering 99% of a 1s
-        // SPEAKER_1 window scores 0.99 vs ~0.04 for the 48s SPEAKER_0 window,
-        // correctly attributing it to SPEAKER_1.
-        //
-        // We store (overlap_numerator * 1_000_000 / ds_duration) as an i64
-        // to avoid floating point while preserving ordering.
-        let best = diar_segs.iter().filter_map(|(seg_id, ds_start, ds_end)| {
-            let overlap_start = (*ws_start).max(*ds_start);
-            let overlap_end = (*ws_end).min(*ds_end);
-            let overlap = overlap_end - overlap_start;
-            let ds_dur = ds_end - ds_start;
-            if overlap > 0 && ds_dur > 0 {
-                let prop_score = overlap * 1_000_000 / ds_dur;
-                Some((prop_score, seg_id))
-            } else {
-                None
-            }
-        }).max_by_key(|(prop_score, _)| *prop_score);
-
-        if let Some((_, seg_id)) = best {
-            text_map.entry(*seg_id).or_default().push(ws_text.clone());
-        }
-    }
-
-    // Write assigned texts back to each diarization segment
-    for (seg_id, texts) in &text_map {
-        let text = texts.join(" ");
-        if !text.is_empty() {
-            let _ = conn.execute(
-                "UPDATE diarization_segments SET text = ?1 WHERE id = ?2",
-                rusqlite::params![text, seg_id],
-            );
-        }
+    // Winner-takes-all assignment: each word belongs to exactly one
+    // diarization window, so a sentence spanning a speaker change is split at
+    // the turn boundary instead of appearing under one (or both) speakers.
+    let text_map = crate::commands::attribution::attribute_text(&pieces, &diar_segs);
+
+    // Write assigned texts back to each diarization segment. Edited segments
+    // still take part in the assignment above (so their Whisper text is not
+    // pushed onto a neighbour) but are never overwritten.
+    for (seg_id, text) in text_map.iter().filter(|(seg_id, _)| in_range(**seg_id)) {
+        let _ = conn.execute(
+            "UPDATE diarization_segments SET text = ?1 WHERE id = ?2 AND edited = 0",
+            rusqlite::params![text, seg_id],
+        );
     }
 }
 
-/// Store diarization segments in SQLite atomically.
-/// Deletes existing segments first (allows re-runs).
+/// Store diarization segments in SQLite atomically (see replace_automatic_segments).
 fn store_diarization_segments(
     db_path: &std::path::Path,
     episode_id: i64,
     segments: &[crate::models::diarization::DiarizationSegment],
 ) -> Result<(), String> {
-    let conn = rusqlite::Connection::open(db_path)
+    let mut conn = rusqlite::Connection::open(db_path)
         .map_err(|e| format!("Failed to open DB: {}", e))?;
-
-    conn.execute("BEGIN", [])
+    let tx = conn
+        .transaction()
         .map_err(|e| format!("Failed to begin tx: {}", e))?;
+    replace_automatic_segments(&tx, episode_id, segments)?;
+    tx.commit()
+        .map_err(|e| format!("Failed to commit tx: {}", e))
+}
 
+/// Deletes existing automatic segments first (allows re-runs); manually edited
+/// segments are kept and new segments mostly covered by them are skipped.
+/// Speaker relabels of the deleted segments carry over to the new segments
+/// that mostly lie in them.
+pub(crate) fn replace_automatic_segments(
+    conn: &rusqlite::Connection,
+    episode_id: i64,
+    segments: &[crate::models::diarization::DiarizationSegment],
+) -> Result<(), String> {
+    let corrected = crate::commands::editing::load_corrected_ranges(conn, episode_id);
     conn.execute(
-        "DELETE FROM diarization_segments WHERE episode_id = ?1",
+        "DELETE FROM diarization_segments WHERE episode_id = ?1 AND edited = 0",
         rusqlite::params![episode_id],
     )
     .map_err(|e| format!("Failed to delete existing segments: {}", e))?;
 
+    let edited = crate::commands::editing::load_edited_ranges(conn, episode_id);
+
     for seg in segments {
+        if crate::commands::editing::overlaps_edited(&edited, seg.start_ms, seg.end_ms) {
+            continue;
+        }
+        let corrected_speaker =
+            crate::commands::editing::carried_correction(&corrected, seg.start_ms, seg.end_ms)
+                .filter(|speaker| *speaker != seg.speaker_label);
         conn.execute(
-            "INSERT INTO diarization_segments (episode_id, start_ms, end_ms, speaker_label, confidence) VALUES (?1, ?2, ?3, ?4, ?5)",
-            rusqlite::params![episode_id, seg.start_ms, seg.end_ms, seg.speaker_label, seg.confidence],
+            "INSERT INTO diarization_segments (episode_id, start_ms, end_ms, speaker_label, corrected_speaker, confidence, embedding) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            rusqlite::params![
+                episode_id,
+                seg.start_ms,
+                seg.end_ms,
+                seg.speaker_label,
+                corrected_speaker,
+                seg.confidence,
+                seg.embedding.as_deref().map(crate::commands::voiceprint::encode_embedding)
+            ],
         )
         .map_err(|e| format!("Failed to insert segment: {}", e))?;
     }
-
-    conn.execute("COMMIT", [])
-        .map_err(|e| format!("Failed to commit tx: {}", e))?;
-
     Ok(())
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
-// Core processing function
+// Speaker count and labels
 //
-// Accepts Option<Channel<DiarizationEvent>> so both the user-facing command
-// (Some(channel)) and the internal chained call (None) use the same code path.
+// Episodes are diarized for the two hosts unless `episodes.speaker_count` says
+// otherwise ("auto" or a fixed number, migration 024). Hosts are SPEAKER_0 and
+// SPEAKER_1, guests SPEAKER_2 and up. Clusters matched to an enrolled host's
+// voiceprint get that host's label; an enrolled host without a match is taken
+// to be absent, so their label stays free. Speaking time is only the fallback
+// for hosts without a voiceprint: the largest remaining clusters take their
+// labels, which makes a guest who talks more than a host look like one.
+// With "auto", clusters below MIN_SPEAKER_SHARE_PERCENT of the speaking time
+// are noise and are merged into a neighbouring speaker; an explicit count
+// keeps every cluster.
 // ─────────────────────────────────────────────────────────────────────────────
 
-async fn process_diarization_episode(
-    job: &DiarizationJob,
-    app: &tauri::AppHandle,
-    state: &Arc<DiarizationState>,
-    on_event: Option<&Channel<DiarizationEvent>>,
-    seg_path: &std::path::PathBuf,
-    emb_path: &std::path::PathBuf,
-    db_path: &std::path::PathBuf,
-) {
-    let episode_id = job.episode_id;
+/// Speaking-time share below which a cluster is not counted as a speaker.
+const MIN_SPEAKER_SHARE_PERCENT: i64 = 5;
 
-    // Update status to 'processing' and register cancellation token
-    update_diarization_status(db_path, episode_id, "processing", None);
+/// Clustering threshold for "auto" (sherpa-onnx default; lower = more speakers).
+pub(crate) const AUTO_CLUSTER_THRESHOLD: f32 = 0.5;
 
-    let cancel_token = CancellationToken::new();
-    {
-        let mut q = state.queue.lock().unwrap();
-        q.active_token = Some(cancel_token.clone());
-        q.active_episode_id = Some(episode_id);
-    }
+const MAX_SPEAKER_COUNT: i32 = 10;
 
-    // Resolve cache directory for temp audio file
-    let cache_dir = match app.path().app_cache_dir() {
-        Ok(d) => d,
-        Err(e) => {
-            let msg = format!("Cannot resolve cache dir: {}", e);
-            update_diarization_status(db_path, episode_id, "error", Some(&msg));
-            if let Some(ch) = on_event {
-                let _ = ch.send(DiarizationEvent::Error { message: msg });
-            }
-            let mut q = state.queue.lock().unwrap();
-            q.active_episode_id = None;
-            q.active_token = None;
-            return;
-        }
-    };
+/// Expected number of speakers of an episode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SpeakerCount {
+    /// Threshold-based clustering decides how many speakers there are.
+    Auto,
+    Fixed(i32),
+}
 
-    if let Err(e) = tokio::fs::create_dir_all(&cache_dir).await {
-        let msg = format!("Cannot create cache dir: {}", e);
-        update_diarization_status(db_path, episode_id, "error", Some(&msg));
-        if let Some(ch) = on_event {
-            let _ = ch.send(DiarizationEvent::Error { message: msg });
+impl SpeakerCount {
+    /// The two hosts, used when the episode has no speaker count.
+    pub(crate) const HOSTS: SpeakerCount = SpeakerCount::Fixed(2);
+
+    pub(crate) fn parse(value: &str) -> Result<Self, String> {
+        match value.trim().to_lowercase().as_str() {
+            "auto" => Ok(SpeakerCount::Auto),
+            n => match n.parse::<i32>() {
+                Ok(n) if (1..=MAX_SPEAKER_COUNT).contains(&n) => Ok(SpeakerCount::Fixed(n)),
+                _ => Err(format!(
+                    "Sprecheranzahl muss \"auto\" oder 1 bis {} sein",
+                    MAX_SPEAKER_COUNT
+                )),
+            },
         }
-        let mut q = state.queue.lock().unwrap();
-        q.active_episode_id = None;
-        q.active_token = None;
-        return;
     }
 
-    let temp_path = cache_dir.join(format!("diarize_{}.mp3", episode_id));
-    let temp_path_for_cleanup = temp_path.clone();
+    /// `num_clusters` for sherpa-onnx: a value <= 0 clusters by threshold.
+    fn num_clusters(&self) -> i32 {
+        match self {
+            SpeakerCount::Auto => -1,
+            SpeakerCount::Fixed(n) => *n,
+        }
+    }
+}
 
-    // ── Download audio (progress 0–50%) ────────────────────────────────────
+pub(crate) fn read_speaker_count(db_path: &std::path::Path, episode_id: i64) -> SpeakerCount {
+    rusqlite::Connection::open(db_path)
+        .ok()
+        .and_then(|conn| {
+            conn.query_row(
+                "SELECT speaker_count FROM episodes WHERE id = ?1",
+                rusqlite::params![episode_id],
+                |row| row.get::<_, Option<String>>(0),
+            )
+            .ok()
+        })
+        .flatten()
+        .and_then(|value| SpeakerCount::parse(&value).ok())
+        .unwrap_or(SpeakerCount::HOSTS)
+}
 
-    let response = match reqwest::get(&job.audio_url).await {
-        Ok(r) => r,
-        Err(e) => {
-            let msg = format!("Audio download failed: {}", e);
-            update_diarization_status(db_path, episode_id, "error", Some(&msg));
-            if let Some(ch) = on_event {
-                let _ = ch.send(DiarizationEvent::Error { message: msg });
-            }
-            let mut q = state.queue.lock().unwrap();
-            q.active_episode_id = None;
-            q.active_token = None;
-            return;
+/// Relabel raw clusters: clusters matched to an enrolled host's voiceprint get
+/// that host's label and the similarity as confidence; the other speakers take
+/// the free labels by speaking time, skipping the labels of `enrolled_hosts`
+/// (hosts without a voiceprint first, then guests). Noise clusters (only with
+/// `SpeakerCount::Auto`) are merged into the nearest speaker before them (or
+/// after, at the start). Returns the segments and the episode status: "solo"
+/// when at most one speaker remains, otherwise "done".
+pub(crate) fn label_speakers(
+    segments: Vec<crate::models::diarization::DiarizationSegment>,
+    host_matches: &std::collections::HashMap<String, (usize, f32)>,
+    enrolled_hosts: &[usize],
+    speaker_count: SpeakerCount,
+) -> (Vec<crate::models::diarization::DiarizationSegment>, &'static str) {
+    // Overlapping speech counts half for each of two clusters talking at once
+    let turns: Vec<(i64, i64, String)> = segments
+        .iter()
+        .map(|s| (s.start_ms, s.end_ms, s.speaker_label.clone()))
+        .collect();
+    let time = crate::commands::overlap::speaking_time(0, &turns);
+    let total_ms = time.speech_ms;
+    let ranked: Vec<(String, i64)> = time.speakers.into_iter().map(|s| (s.speaker, s.ms)).collect();
+
+    let mut labels: std::collections::HashMap<String, String> = host_matches
+        .iter()
+        .map(|(raw, (host, _))| (raw.clone(), format!("SPEAKER_{}", host)))
+        .collect();
+    let mut taken: std::collections::HashSet<usize> =
+        host_matches.values().map(|(host, _)| *host).collect();
+    let mut next_slot = 0;
+    for (rank, (raw, ms)) in ranked.iter().enumerate() {
+        // The largest cluster always counts, even if everything is fragmented;
+        // with an explicit count every requested cluster is a speaker
+        let significant = rank == 0
+            || matches!(speaker_count, SpeakerCount::Fixed(_))
+            || (total_ms > 0 && ms * 100 / total_ms >= MIN_SPEAKER_SHARE_PERCENT);
+        if !significant || labels.contains_key(raw) {
+            continue;
         }
-    };
-
-    let total_bytes = response.content_length().unwrap_or(0);
-    let mut stream = response.bytes_stream();
-    let mut downloaded_bytes: u64 = 0;
-
-    let mut file = match tokio::fs::File::create(&temp_path).await {
-        Ok(f) => f,
-        Err(e) => {
-            let msg = format!("Cannot create temp audio file: {}", e);
-            update_diarization_status(db_path, episode_id, "error", Some(&msg));
-            if let Some(ch) = on_event {
-                let _ = ch.send(DiarizationEvent::Error { message: msg });
-            }
-            let mut q = state.queue.lock().unwrap();
-            q.active_episode_id = None;
-            q.active_token = None;
-            return;
+        while taken.contains(&next_slot) || enrolled_hosts.contains(&next_slot) {
+            next_slot += 1;
         }
-    };
+        taken.insert(next_slot);
+        labels.insert(raw.clone(), format!("SPEAKER_{}", next_slot));
+    }
 
-    while let Some(chunk_result) = stream.next().await {
-        if cancel_token.is_cancelled() {
-            let _ = file.flush().await;
-            drop(file);
-            let _ = tokio::fs::remove_file(&temp_path_for_cleanup).await;
-            update_diarization_status(db_path, episode_id, "not_started", None);
-            if let Some(ch) = on_event {
-                let _ = ch.send(DiarizationEvent::Cancelled);
-            }
-            let mut q = state.queue.lock().unwrap();
-            q.active_episode_id = None;
-            q.active_token = None;
-            return;
+    let mut segments = segments;
+    segments.sort_by_key(|s| s.start_ms);
+    let mut previous: Option<String> = None;
+    let mut pending_noise: Vec<usize> = Vec::new();
+    for i in 0..segments.len() {
+        if let Some((_, similarity)) = host_matches.get(&segments[i].speaker_label) {
+            segments[i].confidence = Some(*similarity as f64);
         }
-
-        let chunk = match chunk_result {
-            Ok(c) => c,
-            Err(e) => {
-                let _ = file.flush().await;
-                drop(file);
-                let _ = tokio::fs::remove_file(&temp_path_for_cleanup).await;
-                let msg = format!("Download stream error: {}", e);
-                update_diarization_status(db_path, episode_id, "error", Some(&msg));
-                if let Some(ch) = on_event {
-                    let _ = ch.send(DiarizationEvent::Error { message: msg });
+        match labels.get(&segments[i].speaker_label) {
+            Some(label) => {
+                segments[i].speaker_label = label.clone();
+                if previous.is_none() {
+                    for &j in &pending_noise {
+                        segments[j].speaker_label = label.clone();
+                    }
+                    pending_noise.clear();
                 }
-                let mut q = state.queue.lock().unwrap();
-                q.active_episode_id = None;
-                q.active_token = None;
-                return;
-            }
-        };
-
-        if let Err(e) = file.write_all(&chunk).await {
-            let _ = tokio::fs::remove_file(&temp_path_for_cleanup).await;
-            let msg = format!("Failed to write audio chunk: {}", e);
-            update_diarization_status(db_path, episode_id, "error", Some(&msg));
-            if let Some(ch) = on_event {
-                let _ = ch.send(DiarizationEvent::Error { message: msg });
+                previous = Some(label.clone());
             }
-            let mut q = state.queue.lock().unwrap();
-            q.active_episode_id = None;
-            q.active_token = None;
-            return;
-        }
-
-        downloaded_bytes += chunk.len() as u64;
-        let download_percent = if total_bytes > 0 {
-            ((downloaded_bytes * 50) / total_bytes) as i32
-        } else {
-            25
-        };
-        if let Some(ch) = on_event {
-            let _ = ch.send(DiarizationEvent::Progress { percent: download_percent });
+            None => match &previous {
+                Some(label) => segments[i].speaker_label = label.clone(),
+                None => pending_noise.push(i),
+            },
         }
     }
 
-    if let Err(e) = file.flush().await {
-        let _ = tokio::fs::remove_file(&temp_path_for_cleanup).await;
-        let msg = format!("Failed to flush audio file: {}", e);
-        update_diarization_status(db_path, episode_id, "error", Some(&msg));
-        if let Some(ch) = on_event {
-            let _ = ch.send(DiarizationEvent::Error { message: msg });
-        }
-        let mut q = state.queue.lock().unwrap();
-        q.active_episode_id = None;
-        q.active_token = None;
-        return;
-    }
-    drop(file);
+    let status = if labels.len() <= 1 { "solo" } else { "done" };
+    (segments, status)
+}
 
-    // Check cancellation after download
-    if cancel_token.is_cancelled() {
-        let _ = tokio::fs::remove_file(&temp_path_for_cleanup).await;
-        update_diarization_status(db_path, episode_id, "not_started", None);
-        if let Some(ch) = on_event {
-            let _ = ch.send(DiarizationEvent::Cancelled);
-        }
-        let mut q = state.queue.lock().unwrap();
-        q.active_episode_id = None;
-        q.active_token = None;
-        return;
-    }
+/// Set or clear (None / "") the expected speaker count of one episode:
+/// "auto" or 1 to 10. Applies to the next diarization run.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn set_episode_speaker_count(
+    episode_id: i64,
+    speaker_count: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let speaker_count = match speaker_count.filter(|c| !c.trim().is_empty()) {
+        Some(value) => Some(match SpeakerCount::parse(&value)? {
+            SpeakerCount::Auto => "auto".to_string(),
+            SpeakerCount::Fixed(n) => n.to_string(),
+        }),
+        None => None,
+    };
+
+    let paths = BinkyPaths::from_app(&app)?;
+    let conn = rusqlite::Connection::open(&paths.db_path).map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE episodes SET speaker_count = ?1, updated_at = datetime('now') WHERE id = ?2",
+        rusqlite::params![speaker_count, episode_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Diarize stage
+//
+// Runs inside the job engine after the download stage. on_event is the channel
+// of start_diarization (None for chained or resumed jobs).
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Shortest speech and pause (seconds) sherpa-onnx keeps as a turn.
+const MIN_DURATION_ON: f32 = 0.3;
+const MIN_DURATION_OFF: f32 = 0.3;
+
+/// Run sherpa-onnx diarization on 16 kHz mono samples. Returns the raw
+/// clusters as SPEAKER_n segments, without embeddings and not yet relabelled
+/// (see label_speakers). Blocking: call from spawn_blocking.
+pub(crate) fn diarize_samples(
+    setup: &DiarizationSetup,
+    samples: &[f32],
+    speaker_count: SpeakerCount,
+    threshold: f32,
+) -> Result<Vec<crate::models::diarization::DiarizationSegment>, String> {
+    let options = crate::commands::diarizer::DiarizerOptions {
+        num_clusters: speaker_count.num_clusters(),
+        threshold,
+        min_duration_on: MIN_DURATION_ON,
+        min_duration_off: MIN_DURATION_OFF,
+        num_threads: setup.num_threads as i32,
+        provider: &setup.provider,
+    };
+    let mut diarizer = crate::commands::diarizer::Diarizer::new(&setup.seg_path, &setup.emb_path, &options)
+        .map_err(|e| format!("Failed to initialize diarizer: {}", e))?;
+
+    let raw_segments = diarizer
+        .compute(samples)
+        .map_err(|e| format!("Diarization failed: {}", e))?;
+
+    Ok(raw_segments
+        .into_iter()
+        .map(|seg| {
+            // sherpa-onnx returns seconds (f32) — multiply by 1000 for milliseconds
+            let start_ms = (seg.start * 1000.0) as i64;
+            let end_ms = (seg.end * 1000.0) as i64;
+            // speaker is an i32 index (0, 1, 2...)
+            let speaker_label = format!("SPEAKER_{}", seg.speaker);
+
+            crate::models::diarization::DiarizationSegment {
+                start_ms,
+                end_ms,
+                speaker_label,
+                confidence: None,
+                embedding: None,
+            }
+        })
+        .collect())
+}
+
+pub(crate) async fn run_diarization_stage(
+    paths: &BinkyPaths,
+    episode_id: i64,
+    audio_path: &std::path::Path,
+    cancel_token: &CancellationToken,
+    on_event: Option<&Progress<DiarizationEvent>>,
+) -> StageResult {
+    let db_path = paths.db_path.as_path();
+
+    // Models not downloaded → skip (the transcription pipeline still finishes)
+    let setup = match find_diarization_models(paths).await {
+        Ok(setup) => setup,
+        Err(e) => return StageResult::Skipped(e),
+    };
 
     // ── Decode audio to 16 kHz mono f32 PCM ────────────────────────────────
 
-    let samples = match crate::commands::transcription::decode_mp3_to_pcm(&temp_path) {
+    let samples = match crate::commands::transcription::decode_mp3_to_pcm(audio_path) {
         Ok(s) => s,
-        Err(e) => {
-            let _ = tokio::fs::remove_file(&temp_path_for_cleanup).await;
-            let msg = format!("Audio decode failed: {}", e);
-            update_diarization_status(db_path, episode_id, "error", Some(&msg));
-            if let Some(ch) = on_event {
-                let _ = ch.send(DiarizationEvent::Error { message: msg });
-            }
-            let mut q = state.queue.lock().unwrap();
-            q.active_episode_id = None;
-            q.active_token = None;
-            return;
-        }
+        Err(e) => return StageResult::Failed(format!("Audio decode failed: {}", e)),
     };
 
-    // Delete temp file now that audio is decoded
-    let _ = tokio::fs::remove_file(&temp_path_for_cleanup).await;
+    if cancel_token.is_cancelled() {
+        return StageResult::Cancelled;
+    }
 
-    // ── Run sherpa-rs diarization in spawn_blocking ─────────────────────────
+    // ── Run sherpa-onnx diarization in spawn_blocking ───────────────────────
 
-    let seg_path_str = seg_path.to_string_lossy().to_string();
-    let emb_path_str = emb_path.to_string_lossy().to_string();
+    let speaker_count = read_speaker_count(db_path, episode_id);
+    let voiceprints = crate::commands::voiceprint::load_voiceprints(db_path);
 
-    let diar_result = tauri::async_runtime::spawn_blocking(move || {
-        use sherpa_rs::diarize::{Diarize, DiarizeConfig};
+    let diar_result = tokio::task::spawn_blocking(move || {
+        let mut results = diarize_samples(&setup, &samples, speaker_count, AUTO_CLUSTER_THRESHOLD)?;
 
-        let config = DiarizeConfig {
-            num_clusters: Some(2), // 2 main podcast hosts
-            threshold: Some(0.5),
-            min_duration_on: Some(0.3),
-            min_duration_off: Some(0.3),
-            provider: None,
-            debug: false,
-        };
+        // sherpa discards its own embeddings, so every segment is embedded
+        // again. A failing extractor only costs the stored embeddings and the
+        // host matching, not the diarization
+        match crate::commands::voiceprint::embedding_extractor(&setup) {
+            Ok(mut extractor) => {
+                crate::commands::voiceprint::embed_segments(&mut extractor, &samples, &mut results)
+            }
+            Err(e) => eprintln!("[diarization] segment embeddings skipped: {}", e),
+        }
 
-        let mut diarizer = Diarize::new(&seg_path_str, &emb_path_str, config)
-            .map_err(|e| format!("Failed to initialize diarizer: {:?}", e))?;
-
-        let raw_segments = diarizer
-            .compute(samples, None)
-            .map_err(|e| format!("Diarization failed: {:?}", e))?;
-
-        let results: Vec<crate::models::diarization::DiarizationSegment> = raw_segments
-            .into_iter()
-            .map(|seg| {
-                // sherpa-rs returns seconds (f32) — multiply by 1000 for milliseconds
-                let start_ms = (seg.start * 1000.0) as i64;
-                let end_ms = (seg.end * 1000.0) as i64;
-                // speaker is an i32 index (0, 1, 2...)
-                let speaker_label = format!("SPEAKER_{}", seg.speaker);
-
-                crate::models::diarization::DiarizationSegment {
-                    start_ms,
-                    end_ms,
-                    speaker_label,
-                    confidence: None,
-                }
-            })
-            .collect();
+        let host_matches = if voiceprints.is_empty() {
+            std::collections::HashMap::new()
+        } else {
+            let centroids = crate::commands::voiceprint::cluster_centroids(&results);
+            crate::commands::voiceprint::match_hosts(&centroids, &voiceprints)
+        };
+        let enrolled_hosts: Vec<usize> = voiceprints.iter().map(|(host, _)| *host).collect();
 
-        Ok::<Vec<crate::models::diarization::DiarizationSegment>, String>(results)
+        Ok::<_, String>((results, host_matches, enrolled_hosts))
     })
     .await;
 
     // Send 100% progress after inference completes
     if let Some(ch) = on_event {
-        let _ = ch.send(DiarizationEvent::Progress { percent: 100 });
+        ch.send(DiarizationEvent::Progress { percent: 100 });
+    }
+
+    if cancel_token.is_cancelled() {
+        return StageResult::Cancelled;
     }
 
     match diar_result {
-        Ok(Ok(segments)) => {
-            // ── Solo detection ──────────────────────────────────────────────
-            // A podcast is 'solo' if there's only 1 unique speaker, or if any
-            // one speaker accounts for < 5% of total speaking time (likely noise).
-            let unique_speakers: std::collections::HashSet<_> =
-                segments.iter().map(|s| &s.speaker_label).collect();
-            let total_ms: i64 = segments.iter().map(|s| s.end_ms - s.start_ms).sum();
-
-            let is_solo = unique_speakers.len() <= 1 || {
-                let speaker_ms =
-                    segments
-                        .iter()
-                        .fold(std::collections::HashMap::new(), |mut m, s| {
-                            *m.entry(&s.speaker_label).or_insert(0i64) +=
-                                s.end_ms - s.start_ms;
-                            m
-                        });
-                let min_speaker_ms = speaker_ms.values().copied().min().unwrap_or(0);
-                total_ms > 0 && (min_speaker_ms * 100 / total_ms) < 5
-            };
-
-            let final_status = if is_solo { "solo" } else { "done" };
-
-            // Store segments in DB
-            if let Err(e) = store_diarization_segments(db_path, episode_id, &segments) {
-                update_diarization_status(db_path, episode_id, "error", Some(&e));
-                if let Some(ch) = on_event {
-                    let _ = ch.send(DiarizationEvent::Error { message: e });
-                }
-            } else {
-                // Populate text from Whisper transcript (no-op for AssemblyAI episodes)
-                backfill_segment_text_from_whisper(db_path, episode_id);
-                update_diarization_status(db_path, episode_id, final_status, None);
-                if let Some(ch) = on_event {
-                    let _ = ch.send(DiarizationEvent::Done { episode_id });
+        Ok(Ok((segments, host_matches, enrolled_hosts))) => {
+            // ── Hosts, guests and solo detection ────────────────────────────
+            // A podcast is 'solo' if only one speaker has >= 5% of the total
+            // speaking time; smaller clusters are noise (see label_speakers).
+            let (segments, final_status) =
+                label_speakers(segments, &host_matches, &enrolled_hosts, speaker_count);
+
+            // Store segments in DB. Text backfill and indexing are separate stages.
+            match store_diarization_segments(db_path, episode_id, &segments) {
+                Ok(()) => {
+                    if let Err(e) = crate::commands::overlap::refresh_overlap_segments(db_path, episode_id) {
+                        eprintln!("[diarization] overlap segments not stored: {}", e);
+                    }
+                    update_diarization_status(db_path, episode_id, final_status, None);
+                    StageResult::Done
                 }
+                Err(e) => StageResult::Failed(e),
             }
         }
-        Ok(Err(e)) => {
-            update_diarization_status(db_path, episode_id, "error", Some(&e));
-            if let Some(ch) = on_event {
-                let _ = ch.send(DiarizationEvent::Error { message: e });
-            }
-        }
-        Err(e) => {
-            let msg = format!("Diarization task panicked: {}", e);
-            update_diarization_status(db_path, episode_id, "error", Some(&msg));
-            if let Some(ch) = on_event {
-                let _ = ch.send(DiarizationEvent::Error { message: msg });
-            }
-        }
+        Ok(Err(e)) => StageResult::Failed(e),
+        Err(e) => StageResult::Failed(format!("Diarization task panicked: {}", e)),
     }
-
-    // Clear active episode tracking
-    let mut q = state.queue.lock().unwrap();
-    q.active_episode_id = None;
-    q.active_token = None;
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
 // Diarization engine commands
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// Queue diarization for an episode: download → diarize → backfill text → index.
+#[cfg(feature = "app")]
 #[tauri::command]
 pub async fn start_diarization(
     episode_id: i64,
     audio_url: String,
     on_event: Channel<DiarizationEvent>,
     app: tauri::AppHandle,
-    state: tauri::State<'_, Arc<DiarizationState>>,
+    state: tauri::State<'_, Arc<EngineState>>,
 ) -> Result<(), String> {
-    let (seg_path, emb_path) = find_diarization_models(&app).await?;
+    let paths = BinkyPaths::from_app(&app)?;
+    find_diarization_models(&paths).await?;
 
-    let db_path = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Cannot resolve app data dir: {}", e))?
-        .join("binky.db");
-
-    let already_processing = {
-        let mut q = state.queue.lock().unwrap();
-        q.enqueue(DiarizationJob {
-            episode_id,
-            audio_url,
-        });
-        let was = q.is_processing;
-        if !was {
-            q.is_processing = true;
-        }
-        was
-    };
-
-    update_diarization_status(&db_path, episode_id, "queued", None);
-
-    if already_processing {
-        return Ok(());
-    }
-
-    let state_arc: Arc<DiarizationState> = state.inner().clone();
-
-    tauri::async_runtime::spawn(async move {
-        loop {
-            // Dequeue and — if the queue is empty — atomically mark as not-processing
-            // under the same lock acquisition. This eliminates the race where a new
-            // start_diarization call enqueues an episode between the moment this loop
-            // finds the queue empty and the moment it sets is_processing = false.
-            // Without this, the new call sees is_processing=true (loop "still running"),
-            // returns early, and the episode sits in the queue forever.
-            let job = {
-                let mut q = state_arc.queue.lock().unwrap();
-                match q.dequeue() {
-                    Some(j) => Some(j),
-                    None => {
-                        q.is_processing = false;
-                        None
-                    }
-                }
-            };
-
-            match job {
-                None => break,
-                Some(j) => {
-                    process_diarization_episode(
-                        &j,
-                        &app,
-                        &state_arc,
-                        Some(&on_event),
-                        &seg_path,
-                        &emb_path,
-                        &db_path,
-                    )
-                    .await;
-                }
-            }
-        }
-    });
-
-    Ok(())
-}
-
-/// Enqueue a diarization job from internal code (e.g., after transcription completes).
-/// Returns immediately — models not downloaded → silently skipped (fire-and-forget).
-pub(crate) async fn enqueue_diarization_internal(
-    episode_id: i64,
-    audio_url: String,
-    app: &tauri::AppHandle,
-    state: &Arc<DiarizationState>,
-) {
-    let (seg_path, emb_path) = match find_diarization_models(app).await {
-        Ok(paths) => paths,
-        Err(_) => return, // Models not downloaded — skip silently
-    };
-
-    let db_path = match app.path().app_data_dir() {
-        Ok(d) => d.join("binky.db"),
-        Err(_) => return,
-    };
-
-    let already_processing = {
-        let mut q = state.queue.lock().unwrap();
-        q.enqueue(DiarizationJob {
-            episode_id,
-            audio_url,
-        });
-        let was = q.is_processing;
-        if !was {
-            q.is_processing = true;
-        }
-        was
-    };
-
-    update_diarization_status(&db_path, episode_id, "queued", None);
-
-    if already_processing {
-        return;
-    }
-
-    let state_arc = state.clone();
-    let app_clone = app.clone();
-
-    tauri::async_runtime::spawn(async move {
-        loop {
-            // Dequeue and — if the queue is empty — atomically mark as not-processing
-            // under the same lock acquisition. Mirrors the same fix in start_diarization.
-            let job = {
-                let mut q = state_arc.queue.lock().unwrap();
-                match q.dequeue() {
-                    Some(j) => Some(j),
-                    None => {
-                        q.is_processing = false;
-                        None
-                    }
-                }
-            };
-
-            match job {
-                None => break,
-                Some(j) => {
-                    // No frontend channel for chained runs — pass None
-                    process_diarization_episode(
-                        &j,
-                        &app_clone,
-                        &state_arc,
-                        None,
-                        &seg_path,
-                        &emb_path,
-                        &db_path,
-                    )
-                    .await;
-                }
-            }
-        }
-    });
+    let mut job = PipelineJob::new(
+        episode_id,
+        audio_url,
+        crate::commands::pipeline::DIARIZATION_STAGES,
+    );
+    job.diarization_events = Some(Arc::new(on_event));
+    crate::commands::pipeline::enqueue_job(&paths, state.inner(), job)
 }
 
+/// Cancel the pipeline of the episode that is currently being diarized.
+#[cfg(feature = "app")]
 #[tauri::command]
 pub async fn cancel_diarization(
-    state: tauri::State<'_, Arc<DiarizationState>>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<EngineState>>,
 ) -> Result<(), String> {
-    state.queue.lock().unwrap().cancel_active();
+    let active = state.engine.lock().unwrap().active_for(Stage::Diarize);
+    if let Some(episode_id) = active {
+        crate::commands::pipeline::cancel_job(&BinkyPaths::from_app(&app)?, state.inner(), episode_id);
+    }
     Ok(())
 }
 
+#[cfg(feature = "app")]
 #[tauri::command]
 pub async fn get_diarization_queue_status(
-    state: tauri::State<'_, Arc<DiarizationState>>,
+    state: tauri::State<'_, Arc<EngineState>>,
 ) -> Result<DiarizationQueueStatus, String> {
-    let q = state.queue.lock().unwrap();
+    let engine = state.engine.lock().unwrap();
     Ok(DiarizationQueueStatus {
-        active_episode_id: q.active_episode_id,
-        queue_length: q.queue_len(),
-        is_processing: q.is_processing,
+        active_episode_id: engine.active_for(Stage::Diarize),
+        queue_length: engine.waiting_for(Stage::Diarize),
+        is_processing: engine.is_processing(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::diarization::DiarizationSegment;
+    use std::collections::HashMap;
+
+    fn seg(speaker: i32, start_s: i64, end_s: i64) -> DiarizationSegment {
+        DiarizationSegment {
+            start_ms: start_s * 1000,
+            end_ms: end_s * 1000,
+            speaker_label: format!("SPEAKER_{}", speaker),
+            confidence: None,
+            embedding: None,
+        }
+    }
+
+    fn labels(segments: &[DiarizationSegment]) -> Vec<&str> {
+        segments.iter().map(|s| s.speaker_label.as_str()).collect()
+    }
+
+    #[test]
+    fn ranks_hosts_before_guests_by_speaking_time() {
+        // Cluster 2 speaks most, cluster 0 least but above the noise share
+        let segments = vec![seg(0, 0, 10), seg(2, 10, 60), seg(1, 60, 90), seg(2, 90, 120)];
+        let (segments, status) = label_speakers(segments, &HashMap::new(), &[], SpeakerCount::Auto);
+        assert_eq!(labels(&segments), vec!["SPEAKER_2", "SPEAKER_0", "SPEAKER_1", "SPEAKER_0"]);
+        assert_eq!(status, "done");
+    }
+
+    #[test]
+    fn merges_noise_clusters_and_detects_solo() {
+        let raw = vec![seg(1, 0, 1), seg(0, 1, 60), seg(1, 60, 61), seg(0, 61, 120)];
+        let (segments, status) = label_speakers(raw.clone(), &HashMap::new(), &[], SpeakerCount::Auto);
+        assert_eq!(labels(&segments), vec!["SPEAKER_0"; 4]);
+        assert_eq!(status, "solo");
+
+        // Two speakers were asked for: the short cluster is kept
+        let (segments, status) = label_speakers(raw, &HashMap::new(), &[], SpeakerCount::Fixed(2));
+        assert_eq!(labels(&segments), vec!["SPEAKER_1", "SPEAKER_0", "SPEAKER_1", "SPEAKER_0"]);
+        assert_eq!(status, "done");
+    }
+
+    #[test]
+    fn enrolled_hosts_keep_their_label_whatever_their_speaking_time() {
+        // Cluster 2 speaks most but is host 1's voice; cluster 0 is host 0
+        let segments = vec![seg(0, 0, 30), seg(2, 30, 90), seg(1, 90, 110)];
+        let matches = HashMap::from([("SPEAKER_2".to_string(), (1, 0.8)), ("SPEAKER_0".to_string(), (0, 0.7))]);
+        let (segments, _) = label_speakers(segments, &matches, &[0, 1], SpeakerCount::Auto);
+        assert_eq!(labels(&segments), vec!["SPEAKER_0", "SPEAKER_1", "SPEAKER_2"]);
+        assert_eq!(segments[1].confidence, Some(0.8f32 as f64));
+        assert_eq!(segments[2].confidence, None);
+    }
+
+    #[test]
+    fn unmatched_voices_do_not_take_an_enrolled_hosts_label() {
+        // Host 0 matched cluster 1; host 1 is enrolled but absent, so the
+        // talkative cluster 0 is a guest, not host 1
+        let segments = vec![seg(0, 0, 60), seg(1, 60, 90)];
+        let matches = HashMap::from([("SPEAKER_1".to_string(), (0, 0.9))]);
+        let (segments, status) = label_speakers(segments, &matches, &[0, 1], SpeakerCount::Auto);
+        assert_eq!(labels(&segments), vec!["SPEAKER_2", "SPEAKER_0"]);
+        assert_eq!(status, "done");
+    }
+
+    #[test]
+    fn parses_speaker_counts() {
+        assert_eq!(SpeakerCount::parse("Auto"), Ok(SpeakerCount::Auto));
+        assert_eq!(SpeakerCount::parse(" 3 "), Ok(SpeakerCount::Fixed(3)));
+        assert!(SpeakerCount::parse("0").is_err());
+        assert!(SpeakerCount::parse("11").is_err());
+        assert_eq!(SpeakerCount::Auto.num_clusters(), -1);
+    }
+
+    #[test]
+    fn rediarizing_keeps_speaker_relabels() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::migrations::apply_pending(&mut conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO episodes (id, title) VALUES (1, 'Folge 1');
+             INSERT INTO diarization_segments (episode_id, start_ms, end_ms, speaker_label, corrected_speaker)
+             VALUES (1, 0, 10000, 'SPEAKER_0', NULL), (1, 10000, 20000, 'SPEAKER_1', 'SPEAKER_2');",
+        )
+        .unwrap();
+
+        replace_automatic_segments(&conn, 1, &[seg(0, 0, 9), seg(1, 9, 21), seg(0, 21, 25)]).unwrap();
+
+        let mut stmt = conn
+            .prepare(
+                "SELECT start_ms, COALESCE(corrected_speaker, speaker_label) FROM diarization_segments \
+                 ORDER BY start_ms",
+            )
+            .unwrap();
+        let speakers: Vec<(i64, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .map(|r| r.unwrap())
+            .collect();
+        assert_eq!(
+            speakers,
+            vec![
+                (0, "SPEAKER_0".to_string()),
+                (9_000, "SPEAKER_2".to_string()),
+                (21_000, "SPEAKER_0".to_string()),
+            ]
+        );
+    }
+}
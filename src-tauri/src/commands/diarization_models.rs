@@ -0,0 +1,355 @@
+use crate::commands::transcription::read_setting;
+#[cfg(feature = "app")]
+use crate::models::diarization::{DiarizationModelInfo, DiarizationSettings};
+#[cfg(feature = "app")]
+use crate::paths::BinkyPaths;
+#[cfg(feature = "app")]
+use sha2::{Digest, Sha256};
+#[cfg(feature = "app")]
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Diarization model registry
+//
+// Supported segmentation and embedding models from the sherpa-onnx releases.
+// One of each is selected in the settings
+// (diarization_segmentation_model, diarization_embedding_model); the diarize
+// stage, voiceprints and evaluation use the selected pair. Models live in
+// models/diarization/segmentation/<id>/model.onnx and
+// models/diarization/embedding/<id>.onnx.
+//
+// NOTE (2026-03-11): nemo_en_titanet_large.onnx (101 MB) took 6+ hours for a
+// 60-minute episode when sherpa ran single-threaded. It stays out of the
+// registry; TitaNet small is the NeMo option.
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ModelRole {
+    /// pyannote-style segmentation, shipped as a tar.bz2 with a model.onnx.
+    Segmentation,
+    /// Speaker embedding extractor, a plain .onnx file.
+    Embedding,
+}
+
+#[cfg(feature = "app")]
+impl ModelRole {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ModelRole::Segmentation => "segmentation",
+            ModelRole::Embedding => "embedding",
+        }
+    }
+}
+
+// Download and display fields are only read by the app's model commands
+#[cfg_attr(not(feature = "app"), allow(dead_code))]
+pub(crate) struct DiarizationModelSpec {
+    pub id: &'static str,
+    pub role: ModelRole,
+    pub name: &'static str,
+    pub url: &'static str,
+    /// SHA-256 of the downloaded file (for segmentation: of the archive), lower
+    /// case hex. Every entry needs one (see every_model_has_a_pinned_hash); a
+    /// None entry is downloaded unverified and logs the hash it got, which is
+    /// the one to pin after checking it against the release.
+    pub sha256: Option<&'static str>,
+    /// Language(s) of the training data.
+    pub language: &'static str,
+    pub size_mb: u32,
+}
+
+pub(crate) const DIARIZATION_MODELS: &[DiarizationModelSpec] = &[
+    DiarizationModelSpec {
+        id: "pyannote-segmentation-3-0",
+        role: ModelRole::Segmentation,
+        name: "pyannote segmentation 3.0",
+        url: "https://github.com/k2-fsa/sherpa-onnx/releases/download/speaker-segmentation-models/sherpa-onnx-pyannote-segmentation-3-0.tar.bz2",
+        sha256: None,
+        language: "multilingual",
+        size_mb: 6,
+    },
+    DiarizationModelSpec {
+        id: "reverb-diarization-v1",
+        role: ModelRole::Segmentation,
+        name: "Reverb diarization v1",
+        url: "https://github.com/k2-fsa/sherpa-onnx/releases/download/speaker-segmentation-models/sherpa-onnx-reverb-diarization-v1.tar.bz2",
+        sha256: None,
+        language: "en",
+        size_mb: 10,
+    },
+    DiarizationModelSpec {
+        id: "wespeaker_en_voxceleb_resnet34_LM",
+        role: ModelRole::Embedding,
+        name: "WeSpeaker ResNet34 (VoxCeleb)",
+        url: "https://github.com/k2-fsa/sherpa-onnx/releases/download/speaker-recongition-models/wespeaker_en_voxceleb_resnet34_LM.onnx",
+        sha256: None,
+        language: "en",
+        size_mb: 27,
+    },
+    DiarizationModelSpec {
+        id: "nemo_en_titanet_small",
+        role: ModelRole::Embedding,
+        name: "NeMo TitaNet small",
+        url: "https://github.com/k2-fsa/sherpa-onnx/releases/download/speaker-recongition-models/nemo_en_titanet_small.onnx",
+        sha256: None,
+        language: "en",
+        size_mb: 38,
+    },
+    DiarizationModelSpec {
+        id: "3dspeaker_speech_campplus_sv_zh_en_16k-common_advanced",
+        role: ModelRole::Embedding,
+        name: "3D-Speaker CAM++ (zh/en)",
+        url: "https://github.com/k2-fsa/sherpa-onnx/releases/download/speaker-recongition-models/3dspeaker_speech_campplus_sv_zh_en_16k-common_advanced.onnx",
+        sha256: None,
+        language: "zh, en",
+        size_mb: 27,
+    },
+];
+
+/// The selected models, installed, with the runtime options of the diarize
+/// stage (see find_diarization_models).
+#[derive(Clone)]
+// The specs are only read back by the app (evaluation, voiceprint enrolment)
+#[cfg_attr(not(feature = "app"), allow(dead_code))]
+pub(crate) struct DiarizationSetup {
+    pub segmentation: &'static DiarizationModelSpec,
+    pub embedding: &'static DiarizationModelSpec,
+    pub seg_path: PathBuf,
+    pub emb_path: PathBuf,
+    /// cpu_sherpa_threads of the CPU budget.
+    pub num_threads: u32,
+    pub provider: String,
+}
+
+pub(crate) const DEFAULT_SEGMENTATION_MODEL: &str = "pyannote-segmentation-3-0";
+pub(crate) const DEFAULT_EMBEDDING_MODEL: &str = "wespeaker_en_voxceleb_resnet34_LM";
+
+/// ONNX Runtime execution providers sherpa-onnx can be built with. Anything
+/// but "cpu" needs a matching sherpa-onnx build and falls back to CPU otherwise.
+const PROVIDERS: [&str; 3] = ["cpu", "cuda", "coreml"];
+
+pub(crate) fn model_spec(id: &str) -> Option<&'static DiarizationModelSpec> {
+    DIARIZATION_MODELS.iter().find(|m| m.id == id)
+}
+
+fn selected_spec(db_path: &Path, key: &str, role: ModelRole, default: &str) -> &'static DiarizationModelSpec {
+    read_setting(db_path, key)
+        .and_then(|id| model_spec(&id))
+        .filter(|spec| spec.role == role)
+        .or_else(|| model_spec(default))
+        .expect("default diarization model is registered")
+}
+
+/// The selected (segmentation, embedding) models.
+pub(crate) fn selected_models(
+    db_path: &Path,
+) -> (&'static DiarizationModelSpec, &'static DiarizationModelSpec) {
+    (
+        selected_spec(
+            db_path,
+            "diarization_segmentation_model",
+            ModelRole::Segmentation,
+            DEFAULT_SEGMENTATION_MODEL,
+        ),
+        selected_spec(
+            db_path,
+            "diarization_embedding_model",
+            ModelRole::Embedding,
+            DEFAULT_EMBEDDING_MODEL,
+        ),
+    )
+}
+
+pub(crate) fn read_provider(db_path: &Path) -> String {
+    read_setting(db_path, "diarization_provider")
+        .filter(|p| PROVIDERS.contains(&p.as_str()))
+        .unwrap_or_else(|| "cpu".to_string())
+}
+
+/// Where a model is installed. Installs from before the registry put the
+/// default segmentation model directly into segmentation/; it is used from
+/// there as long as it exists.
+pub(crate) fn model_path(diarization_dir: &Path, spec: &DiarizationModelSpec) -> PathBuf {
+    match spec.role {
+        ModelRole::Segmentation => {
+            let legacy = diarization_dir.join("segmentation").join("model.onnx");
+            if spec.id == DEFAULT_SEGMENTATION_MODEL && legacy.exists() {
+                legacy
+            } else {
+                diarization_dir.join("segmentation").join(spec.id).join("model.onnx")
+            }
+        }
+        ModelRole::Embedding => diarization_dir.join("embedding").join(format!("{}.onnx", spec.id)),
+    }
+}
+
+pub(crate) fn is_downloaded(diarization_dir: &Path, spec: &DiarizationModelSpec) -> bool {
+    std::fs::metadata(model_path(diarization_dir, spec))
+        .map(|m| m.len() > 0)
+        .unwrap_or(false)
+}
+
+/// Hex SHA-256 of a file. Blocking.
+#[cfg(feature = "app")]
+pub(crate) fn file_sha256(path: &Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 1 << 16];
+    loop {
+        let n = file.read(&mut buffer).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+    }
+    Ok(hasher
+        .finalize()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect())
+}
+
+/// Check a downloaded file against the pinned hash of `spec`.
+#[cfg(feature = "app")]
+pub(crate) fn verify_download(path: &Path, spec: &DiarizationModelSpec) -> Result<(), String> {
+    let actual = file_sha256(path)?;
+    match spec.sha256 {
+        Some(expected) if !expected.eq_ignore_ascii_case(&actual) => Err(format!(
+            "Prüfsumme von {} stimmt nicht (erwartet {}, erhalten {})",
+            spec.name, expected, actual
+        )),
+        Some(_) => Ok(()),
+        None => {
+            eprintln!(
+                "[diarization] WARNING: {} has no pinned sha256, downloaded unverified (got {})",
+                spec.id, actual
+            );
+            Ok(())
+        }
+    }
+}
+
+/// All registered diarization models with their download and selection state.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn list_diarization_models(app: tauri::AppHandle) -> Result<Vec<DiarizationModelInfo>, String> {
+    let paths = BinkyPaths::from_app(&app)?;
+    let diarization_dir = paths.diarization_models_dir();
+    let (segmentation, embedding) = selected_models(&paths.db_path);
+    Ok(DIARIZATION_MODELS
+        .iter()
+        .map(|spec| DiarizationModelInfo {
+            id: spec.id.to_string(),
+            role: spec.role.as_str().to_string(),
+            name: spec.name.to_string(),
+            url: spec.url.to_string(),
+            sha256: spec.sha256.map(str::to_string),
+            language: spec.language.to_string(),
+            size_mb: spec.size_mb,
+            downloaded: is_downloaded(&diarization_dir, spec),
+            selected: spec.id == segmentation.id || spec.id == embedding.id,
+        })
+        .collect())
+}
+
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn get_diarization_settings(app: tauri::AppHandle) -> Result<DiarizationSettings, String> {
+    let paths = BinkyPaths::from_app(&app)?;
+    let (segmentation, embedding) = selected_models(&paths.db_path);
+    Ok(DiarizationSettings {
+        segmentation_model: segmentation.id.to_string(),
+        embedding_model: embedding.id.to_string(),
+        provider: read_provider(&paths.db_path),
+        num_threads: crate::commands::pipeline::read_cpu_budget(&paths.db_path).sherpa_threads,
+    })
+}
+
+/// Select models and the execution provider. The thread count is the
+/// cpu_sherpa_threads share of the CPU budget (set_cpu_budget). Changing the
+/// embedding model makes enrolled voiceprints unusable until hosts are
+/// enrolled again, since embeddings of different models are not comparable.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn set_diarization_settings(
+    segmentation_model: Option<String>,
+    embedding_model: Option<String>,
+    provider: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let mut updates: Vec<(&str, String)> = Vec::new();
+    for (key, value, role) in [
+        ("diarization_segmentation_model", segmentation_model, ModelRole::Segmentation),
+        ("diarization_embedding_model", embedding_model, ModelRole::Embedding),
+    ] {
+        if let Some(id) = value {
+            match model_spec(&id) {
+                Some(spec) if spec.role == role => updates.push((key, id)),
+                _ => return Err(format!("Unbekanntes Diarisierungsmodell: {}", id)),
+            }
+        }
+    }
+    if let Some(provider) = provider {
+        if !PROVIDERS.contains(&provider.as_str()) {
+            return Err(format!("Unbekannter Provider: {}", provider));
+        }
+        updates.push(("diarization_provider", provider));
+    }
+
+    let paths = BinkyPaths::from_app(&app)?;
+    let conn = rusqlite::Connection::open(&paths.db_path).map_err(|e| e.to_string())?;
+    for (key, value) in updates {
+        conn.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+            rusqlite::params![key, value],
+        )
+        .map_err(|e| format!("Einstellung konnte nicht gespeichert werden: {}", e))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registry_ids_are_unique_and_defaults_registered() {
+        let mut ids: Vec<&str> = DIARIZATION_MODELS.iter().map(|m| m.id).collect();
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), DIARIZATION_MODELS.len());
+        assert_eq!(model_spec(DEFAULT_SEGMENTATION_MODEL).map(|m| m.role), Some(ModelRole::Segmentation));
+        assert_eq!(model_spec(DEFAULT_EMBEDDING_MODEL).map(|m| m.role), Some(ModelRole::Embedding));
+    }
+
+    #[test]
+    fn pinned_hashes_are_sha256_hex() {
+        for spec in DIARIZATION_MODELS {
+            if let Some(hash) = spec.sha256 {
+                assert_eq!(hash.len(), 64, "{}", spec.id);
+                assert!(hash.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f')), "{}", spec.id);
+            }
+        }
+    }
+
+    #[test]
+    #[ignore = "the release hashes still have to be pinned from a verified download"]
+    fn every_model_has_a_pinned_hash() {
+        let unpinned: Vec<&str> = DIARIZATION_MODELS.iter().filter(|m| m.sha256.is_none()).map(|m| m.id).collect();
+        assert!(unpinned.is_empty(), "no sha256 pinned for {:?}", unpinned);
+    }
+
+    #[test]
+    fn models_install_per_id() {
+        let dir = Path::new("/models/diarization");
+        assert_eq!(
+            model_path(dir, model_spec("reverb-diarization-v1").unwrap()),
+            dir.join("segmentation/reverb-diarization-v1/model.onnx")
+        );
+        assert_eq!(
+            model_path(dir, model_spec(DEFAULT_EMBEDDING_MODEL).unwrap()),
+            dir.join("embedding/wespeaker_en_voxceleb_resnet34_LM.onnx")
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use sherpa_rs::sherpa_rs_sys;
+use std::ffi::CString;
+use std::path::Path;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Offline speaker diarization via the sherpa-onnx C API
+//
+// sherpa-rs 0.6's Diarize hard-codes num_threads = 1 for both the segmentation
+// and the embedding model and consumes the samples. This wrapper builds the
+// same SherpaOnnxOfflineSpeakerDiarizationConfig with the thread count and
+// execution provider from the diarization settings, and borrows the samples,
+// so the stage can embed segments from the same buffer afterwards.
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Clustering and runtime options of one diarizer.
+pub(crate) struct DiarizerOptions<'a> {
+    /// Number of speakers; a value <= 0 clusters by `threshold`.
+    pub num_clusters: i32,
+    pub threshold: f32,
+    /// Shortest speech and pause (seconds) kept as a turn.
+    pub min_duration_on: f32,
+    pub min_duration_off: f32,
+    pub num_threads: i32,
+    /// ONNX Runtime execution provider ("cpu", "cuda", "coreml").
+    pub provider: &'a str,
+}
+
+/// One speaker turn: start and end in seconds, cluster index.
+pub(crate) struct DiarizerSegment {
+    pub start: f32,
+    pub end: f32,
+    pub speaker: i32,
+}
+
+pub(crate) struct Diarizer {
+    sd: *const sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarization,
+}
+
+fn c_path(path: &Path) -> Result<CString, String> {
+    CString::new(path.to_string_lossy().as_bytes()).map_err(|e| e.to_string())
+}
+
+impl Diarizer {
+    pub(crate) fn new(seg_path: &Path, emb_path: &Path, options: &DiarizerOptions) -> Result<Self, String> {
+        let segmentation_model = c_path(seg_path)?;
+        let embedding_model = c_path(emb_path)?;
+        let provider = CString::new(options.provider).map_err(|e| e.to_string())?;
+        let num_threads = options.num_threads.max(1);
+
+        // The C side copies the strings; they only need to outlive this call
+        let config = sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarizationConfig {
+            segmentation: sherpa_rs_sys::SherpaOnnxOfflineSpeakerSegmentationModelConfig {
+                pyannote: sherpa_rs_sys::SherpaOnnxOfflineSpeakerSegmentationPyannoteModelConfig {
+                    model: segmentation_model.as_ptr(),
+                },
+                num_threads,
+                debug: 0,
+                provider: provider.as_ptr(),
+            },
+            embedding: sherpa_rs_sys::SherpaOnnxSpeakerEmbeddingExtractorConfig {
+                model: embedding_model.as_ptr(),
+                num_threads,
+                debug: 0,
+                provider: provider.as_ptr(),
+            },
+            clustering: sherpa_rs_sys::SherpaOnnxFastClusteringConfig {
+                num_clusters: options.num_clusters,
+                threshold: options.threshold,
+            },
+            min_duration_on: options.min_duration_on,
+            min_duration_off: options.min_duration_off,
+        };
+
+        let sd = unsafe { sherpa_rs_sys::SherpaOnnxCreateOfflineSpeakerDiarization(&config) };
+        if sd.is_null() {
+            return Err("Failed to initialize offline speaker diarization".to_string());
+        }
+        Ok(Self { sd })
+    }
+
+    /// Diarize 16 kHz mono samples. Segments are sorted by start time.
+    pub(crate) fn compute(&mut self, samples: &[f32]) -> Result<Vec<DiarizerSegment>, String> {
+        unsafe {
+            let result = sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarizationProcess(
+                self.sd,
+                samples.as_ptr(),
+                samples.len() as i32,
+            );
+            if result.is_null() {
+                return Err("Diarization returned no result".to_string());
+            }
+
+            let num_segments = sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarizationResultGetNumSegments(result);
+            let segments_ptr = sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarizationResultSortByStartTime(result);
+            let segments = if segments_ptr.is_null() || num_segments <= 0 {
+                Vec::new()
+            } else {
+                std::slice::from_raw_parts(segments_ptr, num_segments as usize)
+                    .iter()
+                    .map(|s| DiarizerSegment {
+                        start: s.start,
+                        end: s.end,
+                        speaker: s.speaker,
+                    })
+                    .collect()
+            };
+
+            if !segments_ptr.is_null() {
+                sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarizationDestroySegment(segments_ptr);
+            }
+            sherpa_rs_sys::SherpaOnnxOfflineSpeakerDiarizationDestroyResult(result);
+
+            if segments.is_empty() {
+                return Err("No segments found".to_string());
+            }
+            Ok(segments)
+        }
+    }
+}
+
+impl Drop for Diarizer {
+    fn drop(&mut self) {
+        unsafe {
+            sherpa_rs_sys::SherpaOnnxDestroyOfflineSpeakerDiarization(self.sd);
+        }
+    }
+}
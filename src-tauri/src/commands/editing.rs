@@ -0,0 +1,833 @@
+#[cfg(feature = "app")]
+use crate::models::diarization::{SegmentSnapshot, TranscriptRevision};
+use rusqlite::Connection;
+#[cfg(feature = "app")]
+use rusqlite::Transaction;
+#[cfg(feature = "app")]
+use tauri::Manager;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Edit protection helpers (used by re-transcription / re-diarization paths)
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Time ranges of manually edited segments for an episode.
+pub(crate) fn load_edited_ranges(conn: &Connection, episode_id: i64) -> Vec<(i64, i64)> {
+    let mut stmt = match conn.prepare(
+        "SELECT start_ms, end_ms FROM diarization_segments \
+         WHERE episode_id = ?1 AND edited = 1 ORDER BY start_ms",
+    ) {
+        Ok(s) => s,
+        Err(_) => return vec![],
+    };
+    let collected: Vec<(i64, i64)> = match stmt.query_map(rusqlite::params![episode_id], |row| {
+        Ok((row.get(0)?, row.get(1)?))
+    }) {
+        Ok(rows) => rows.filter_map(|r| r.ok()).collect(),
+        Err(_) => vec![],
+    };
+    collected
+}
+
+/// True if more than half of [start_ms, end_ms) is covered by an edited range.
+/// Automatic segments that mostly overlap a manual edit are dropped on re-runs.
+pub(crate) fn overlaps_edited(edited: &[(i64, i64)], start_ms: i64, end_ms: i64) -> bool {
+    let duration = end_ms - start_ms;
+    if duration <= 0 {
+        return false;
+    }
+    let covered: i64 = edited
+        .iter()
+        .map(|(es, ee)| (end_ms.min(*ee) - start_ms.max(*es)).max(0))
+        .sum();
+    covered * 2 > duration
+}
+
+/// (start_ms, end_ms, corrected speaker) of the automatic segments of an
+/// episode that were relabelled by hand. Read before a re-run deletes them, so
+/// the corrections can be carried over to the new segments.
+pub(crate) fn load_corrected_ranges(conn: &Connection, episode_id: i64) -> Vec<(i64, i64, String)> {
+    let mut stmt = match conn.prepare(
+        "SELECT start_ms, end_ms, corrected_speaker FROM diarization_segments \
+         WHERE episode_id = ?1 AND edited = 0 AND corrected_speaker IS NOT NULL ORDER BY start_ms",
+    ) {
+        Ok(s) => s,
+        Err(_) => return vec![],
+    };
+    let collected: Vec<(i64, i64, String)> = match stmt.query_map(rusqlite::params![episode_id], |row| {
+        Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+    }) {
+        Ok(rows) => rows.filter_map(|r| r.ok()).collect(),
+        Err(_) => vec![],
+    };
+    collected
+}
+
+/// The corrected speaker covering more than half of [start_ms, end_ms), if any.
+/// New segments of a re-run take it over, so relabels are not lost.
+pub(crate) fn carried_correction(corrected: &[(i64, i64, String)], start_ms: i64, end_ms: i64) -> Option<String> {
+    let duration = end_ms - start_ms;
+    if duration <= 0 {
+        return None;
+    }
+    let mut covered: Vec<(&str, i64)> = Vec::new();
+    for (cs, ce, speaker) in corrected {
+        let overlap = (end_ms.min(*ce) - start_ms.max(*cs)).max(0);
+        if overlap == 0 {
+            continue;
+        }
+        match covered.iter_mut().find(|(s, _)| s == speaker) {
+            Some((_, ms)) => *ms += overlap,
+            None => covered.push((speaker, overlap)),
+        }
+    }
+    covered
+        .into_iter()
+        .find(|(_, ms)| ms * 2 > duration)
+        .map(|(speaker, _)| speaker.to_string())
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Snapshot + revision plumbing
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[cfg(feature = "app")]
+pub(crate) fn load_snapshot(conn: &Connection, segment_id: i64) -> Result<SegmentSnapshot, String> {
+    conn.query_row(
+        "SELECT id, episode_id, start_ms, end_ms, speaker_label, corrected_speaker, \
+                confidence, text, COALESCE(edited, 0), embedding \
+         FROM diarization_segments WHERE id = ?1",
+        rusqlite::params![segment_id],
+        |row| {
+            Ok(SegmentSnapshot {
+                id: row.get(0)?,
+                episode_id: row.get(1)?,
+                start_ms: row.get(2)?,
+                end_ms: row.get(3)?,
+                speaker_label: row.get(4)?,
+                corrected_speaker: row.get(5)?,
+                confidence: row.get(6)?,
+                text: row.get(7)?,
+                edited: row.get::<_, i64>(8)? != 0,
+                embedding: row.get(9)?,
+            })
+        },
+    )
+    .map_err(|e| format!("Segment {} nicht gefunden: {}", segment_id, e))
+}
+
+/// Write a snapshot back: UPDATE if the row exists, otherwise INSERT with its id.
+/// Never uses INSERT OR REPLACE — REPLACE does not fire delete triggers and
+/// would leave a duplicate rowid in search_index.
+#[cfg(feature = "app")]
+pub(crate) fn write_snapshot(tx: &Transaction, snap: &SegmentSnapshot) -> Result<(), String> {
+    let updated = tx
+        .execute(
+            "UPDATE diarization_segments SET start_ms = ?2, end_ms = ?3, speaker_label = ?4, \
+             corrected_speaker = ?5, confidence = ?6, text = ?7, edited = ?8, embedding = ?9 \
+             WHERE id = ?1",
+            rusqlite::params![
+                snap.id,
+                snap.start_ms,
+                snap.end_ms,
+                snap.speaker_label,
+                snap.corrected_speaker,
+                snap.confidence,
+                snap.text,
+                snap.edited as i64,
+                snap.embedding
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+
+    if updated == 0 {
+        tx.execute(
+            "INSERT INTO diarization_segments \
+             (id, episode_id, start_ms, end_ms, speaker_label, corrected_speaker, confidence, text, \
+              edited, embedding) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+            rusqlite::params![
+                snap.id,
+                snap.episode_id,
+                snap.start_ms,
+                snap.end_ms,
+                snap.speaker_label,
+                snap.corrected_speaker,
+                snap.confidence,
+                snap.text,
+                snap.edited as i64,
+                snap.embedding
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "app")]
+pub(crate) fn record_revision(
+    tx: &Transaction,
+    episode_id: i64,
+    action: &str,
+    before: &[SegmentSnapshot],
+    after: &[SegmentSnapshot],
+) -> Result<i64, String> {
+    let before_json = serde_json::to_string(before).map_err(|e| e.to_string())?;
+    let after_json = serde_json::to_string(after).map_err(|e| e.to_string())?;
+    tx.execute(
+        "INSERT INTO transcript_revisions (episode_id, action, before_json, after_json) \
+         VALUES (?1, ?2, ?3, ?4)",
+        rusqlite::params![episode_id, action, before_json, after_json],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(tx.last_insert_rowid())
+}
+
+/// Split `text` into two parts at the word closest to `ratio` (0.0–1.0).
+/// Used when a segment is split by time and no explicit texts are given.
+#[cfg(feature = "app")]
+pub(crate) fn split_text_at_ratio(text: &str, ratio: f64) -> (String, String) {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let cut = ((words.len() as f64) * ratio.clamp(0.0, 1.0)).round() as usize;
+    (words[..cut].join(" "), words[cut..].join(" "))
+}
+
+#[cfg(feature = "app")]
+fn open_db(app: &tauri::AppHandle) -> Result<Connection, String> {
+    let db_path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("binky.db");
+    Connection::open(&db_path).map_err(|e| e.to_string())
+}
+
+#[cfg(feature = "app")]
+fn non_empty(text: String) -> Option<String> {
+    let trimmed = text.trim();
+    if trimmed.is_empty() {
+        None
+    } else {
+        Some(trimmed.to_string())
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Commands
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Replace the text of one segment. Returns the updated segment.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub fn edit_segment_text(
+    segment_id: i64,
+    text: String,
+    app: tauri::AppHandle,
+) -> Result<SegmentSnapshot, String> {
+    edit_text(&mut open_db(&app)?, segment_id, text)
+}
+
+#[cfg(feature = "app")]
+fn edit_text(conn: &mut Connection, segment_id: i64, text: String) -> Result<SegmentSnapshot, String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let before = load_snapshot(&tx, segment_id)?;
+    let after = SegmentSnapshot {
+        text: non_empty(text),
+        edited: true,
+        ..before.clone()
+    };
+    write_snapshot(&tx, &after)?;
+    record_revision(
+        &tx,
+        before.episode_id,
+        "edit",
+        &[before],
+        std::slice::from_ref(&after),
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(after)
+}
+
+/// Split one segment at `at_ms` into two segments with the same speaker.
+/// Without explicit texts the words are divided proportionally to time.
+/// Neither half keeps the embedding: it described the whole segment.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub fn split_segment(
+    segment_id: i64,
+    at_ms: i64,
+    text_before: Option<String>,
+    text_after: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<Vec<SegmentSnapshot>, String> {
+    split(&mut open_db(&app)?, segment_id, at_ms, text_before, text_after)
+}
+
+#[cfg(feature = "app")]
+fn split(
+    conn: &mut Connection,
+    segment_id: i64,
+    at_ms: i64,
+    text_before: Option<String>,
+    text_after: Option<String>,
+) -> Result<Vec<SegmentSnapshot>, String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let before = load_snapshot(&tx, segment_id)?;
+    if at_ms <= before.start_ms || at_ms >= before.end_ms {
+        return Err("Trennpunkt liegt außerhalb des Segments".to_string());
+    }
+
+    let ratio = (at_ms - before.start_ms) as f64 / (before.end_ms - before.start_ms) as f64;
+    let (auto_first, auto_second) =
+        split_text_at_ratio(before.text.as_deref().unwrap_or(""), ratio);
+
+    let first = SegmentSnapshot {
+        end_ms: at_ms,
+        text: non_empty(text_before.unwrap_or(auto_first)),
+        edited: true,
+        embedding: None,
+        ..before.clone()
+    };
+    write_snapshot(&tx, &first)?;
+
+    tx.execute(
+        "INSERT INTO diarization_segments \
+         (episode_id, start_ms, end_ms, speaker_label, corrected_speaker, confidence, text, edited) \
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, 1)",
+        rusqlite::params![
+            before.episode_id,
+            at_ms,
+            before.end_ms,
+            before.speaker_label,
+            before.corrected_speaker,
+            before.confidence,
+            non_empty(text_after.unwrap_or(auto_second))
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    let second = load_snapshot(&tx, tx.last_insert_rowid())?;
+
+    let after = vec![first, second];
+    record_revision(&tx, before.episode_id, "split", &[before], &after)?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(after)
+}
+
+/// Merge two or more adjacent segments of one episode into the earliest one.
+/// The merged segment keeps the first segment's speaker; texts are joined.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub fn merge_segments(
+    segment_ids: Vec<i64>,
+    app: tauri::AppHandle,
+) -> Result<SegmentSnapshot, String> {
+    merge(&mut open_db(&app)?, &segment_ids)
+}
+
+#[cfg(feature = "app")]
+fn merge(conn: &mut Connection, segment_ids: &[i64]) -> Result<SegmentSnapshot, String> {
+    if segment_ids.len() < 2 {
+        return Err("Mindestens zwei Segmente zum Zusammenführen nötig".to_string());
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let mut before: Vec<SegmentSnapshot> = segment_ids
+        .iter()
+        .map(|id| load_snapshot(&tx, *id))
+        .collect::<Result<_, _>>()?;
+    before.sort_by_key(|s| s.start_ms);
+
+    let episode_id = before[0].episode_id;
+    if before.iter().any(|s| s.episode_id != episode_id) {
+        return Err("Segmente gehören zu verschiedenen Episoden".to_string());
+    }
+
+    // Adjacent = no other segment of the episode starts between the first and last one.
+    let (range_start, range_end) = (before[0].start_ms, before[before.len() - 1].start_ms);
+    let in_between: i64 = tx
+        .query_row(
+            "SELECT COUNT(*) FROM diarization_segments \
+             WHERE episode_id = ?1 AND start_ms >= ?2 AND start_ms <= ?3",
+            rusqlite::params![episode_id, range_start, range_end],
+            |row| row.get(0),
+        )
+        .map_err(|e| e.to_string())?;
+    if in_between as usize != before.len() {
+        return Err("Nur benachbarte Segmente können zusammengeführt werden".to_string());
+    }
+
+    let text = before
+        .iter()
+        .filter_map(|s| s.text.as_deref())
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let merged = SegmentSnapshot {
+        end_ms: before
+            .iter()
+            .map(|s| s.end_ms)
+            .max()
+            .unwrap_or(before[0].end_ms),
+        text: non_empty(text),
+        edited: true,
+        ..before[0].clone()
+    };
+
+    for seg in &before[1..] {
+        tx.execute(
+            "DELETE FROM diarization_segments WHERE id = ?1",
+            rusqlite::params![seg.id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    write_snapshot(&tx, &merged)?;
+    record_revision(
+        &tx,
+        episode_id,
+        "merge",
+        &before,
+        std::slice::from_ref(&merged),
+    )?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(merged)
+}
+
+/// Set (Some) or clear (None) corrected_speaker per (segment id, speaker) as
+/// one revision. Segments already carrying that speaker are skipped.
+#[cfg(feature = "app")]
+pub(crate) fn relabel_segments(
+    tx: &Transaction,
+    episode_id: i64,
+    action: &str,
+    assignments: &[(i64, Option<String>)],
+) -> Result<Vec<SegmentSnapshot>, String> {
+    let mut before = Vec::new();
+    let mut after = Vec::new();
+    for (id, speaker) in assignments {
+        let current = load_snapshot(tx, *id)?;
+        if current.episode_id != episode_id {
+            return Err("Segmente gehören zu verschiedenen Episoden".to_string());
+        }
+        if current.corrected_speaker == *speaker {
+            continue;
+        }
+        let updated = SegmentSnapshot {
+            corrected_speaker: speaker.clone(),
+            ..current.clone()
+        };
+        write_snapshot(tx, &updated)?;
+        before.push(current);
+        after.push(updated);
+    }
+    if !after.is_empty() {
+        record_revision(tx, episode_id, action, &before, &after)?;
+    }
+    Ok(after)
+}
+
+#[cfg(feature = "app")]
+fn segment_ids(tx: &Transaction, sql: &str, params: impl rusqlite::Params) -> Result<Vec<i64>, String> {
+    let mut stmt = tx.prepare(sql).map_err(|e| e.to_string())?;
+    let ids = stmt
+        .query_map(params, |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<i64>, _>>()
+        .map_err(|e| e.to_string())?;
+    Ok(ids)
+}
+
+/// Assign `speaker` to every segment of an episode whose midpoint lies in
+/// [start_ms, end_ms); None or "" resets them to their diarization label.
+/// Returns the changed segments.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub fn relabel_segment_range(
+    episode_id: i64,
+    start_ms: i64,
+    end_ms: i64,
+    speaker: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<Vec<SegmentSnapshot>, String> {
+    relabel_range(&mut open_db(&app)?, episode_id, start_ms, end_ms, speaker)
+}
+
+#[cfg(feature = "app")]
+fn relabel_range(
+    conn: &mut Connection,
+    episode_id: i64,
+    start_ms: i64,
+    end_ms: i64,
+    speaker: Option<String>,
+) -> Result<Vec<SegmentSnapshot>, String> {
+    if end_ms <= start_ms {
+        return Err("Ungültiger Zeitbereich".to_string());
+    }
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let ids = segment_ids(
+        &tx,
+        "SELECT id FROM diarization_segments WHERE episode_id = ?1 \
+         AND (start_ms + end_ms) / 2 >= ?2 AND (start_ms + end_ms) / 2 < ?3 ORDER BY start_ms",
+        rusqlite::params![episode_id, start_ms, end_ms],
+    )?;
+    let speaker = speaker.and_then(non_empty);
+    let assignments: Vec<(i64, Option<String>)> = ids.into_iter().map(|id| (id, speaker.clone())).collect();
+    let after = relabel_segments(&tx, episode_id, "relabel", &assignments)?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(after)
+}
+
+/// Assign `speaker` to every segment of one diarization cluster (segments with
+/// `speaker_label`), e.g. when a guest was taken for a host. None or "" resets
+/// them. Returns the changed segments.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub fn relabel_cluster(
+    episode_id: i64,
+    speaker_label: String,
+    speaker: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<Vec<SegmentSnapshot>, String> {
+    relabel_by_cluster(&mut open_db(&app)?, episode_id, &speaker_label, speaker)
+}
+
+#[cfg(feature = "app")]
+fn relabel_by_cluster(
+    conn: &mut Connection,
+    episode_id: i64,
+    speaker_label: &str,
+    speaker: Option<String>,
+) -> Result<Vec<SegmentSnapshot>, String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let ids = segment_ids(
+        &tx,
+        "SELECT id FROM diarization_segments WHERE episode_id = ?1 AND speaker_label = ?2 \
+         ORDER BY start_ms",
+        rusqlite::params![episode_id, speaker_label],
+    )?;
+    if ids.is_empty() {
+        return Err(format!("Keine Segmente von {} in dieser Episode", speaker_label));
+    }
+    let speaker = speaker.and_then(non_empty);
+    let assignments: Vec<(i64, Option<String>)> = ids.into_iter().map(|id| (id, speaker.clone())).collect();
+    let after = relabel_segments(&tx, episode_id, "relabel", &assignments)?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(after)
+}
+
+/// List all revisions of an episode, newest first.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub fn list_transcript_revisions(
+    episode_id: i64,
+    app: tauri::AppHandle,
+) -> Result<Vec<TranscriptRevision>, String> {
+    let conn = open_db(&app)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, episode_id, action, before_json, after_json, reverted, created_at \
+             FROM transcript_revisions WHERE episode_id = ?1 ORDER BY id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let revisions = stmt
+        .query_map(rusqlite::params![episode_id], |row| {
+            let before_json: String = row.get(3)?;
+            let after_json: String = row.get(4)?;
+            Ok(TranscriptRevision {
+                id: row.get(0)?,
+                episode_id: row.get(1)?,
+                action: row.get(2)?,
+                before: serde_json::from_str(&before_json).unwrap_or_default(),
+                after: serde_json::from_str(&after_json).unwrap_or_default(),
+                reverted: row.get::<_, i64>(5)? != 0,
+                created_at: row.get(6)?,
+            })
+        })
+        .map_err(|e| e.to_string())?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?;
+
+    Ok(revisions)
+}
+
+/// Undo the most recent non-reverted revision of an episode by restoring its
+/// "before" snapshots. Segments created by the revision are deleted.
+/// Refused when a segment the revision wrote has since changed or is gone
+/// (e.g. re-diarized), since restoring it would duplicate or clobber newer
+/// segments. Returns the undone revision id, or None if there is nothing to undo.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub fn undo_last_revision(episode_id: i64, app: tauri::AppHandle) -> Result<Option<i64>, String> {
+    undo_last(&mut open_db(&app)?, episode_id)
+}
+
+#[cfg(feature = "app")]
+fn undo_last(conn: &mut Connection, episode_id: i64) -> Result<Option<i64>, String> {
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let latest: Option<(i64, String, String)> = tx
+        .query_row(
+            "SELECT id, before_json, after_json FROM transcript_revisions \
+             WHERE episode_id = ?1 AND reverted = 0 ORDER BY id DESC LIMIT 1",
+            rusqlite::params![episode_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .ok();
+
+    let (revision_id, before_json, after_json) = match latest {
+        Some(v) => v,
+        None => return Ok(None),
+    };
+
+    let before: Vec<SegmentSnapshot> =
+        serde_json::from_str(&before_json).map_err(|e| e.to_string())?;
+    let after: Vec<SegmentSnapshot> =
+        serde_json::from_str(&after_json).map_err(|e| e.to_string())?;
+
+    for snap in &after {
+        if load_snapshot(&tx, snap.id).ok().as_ref() != Some(snap) {
+            return Err(format!(
+                "Rückgängig nicht möglich: Segment {} wurde seitdem verändert oder neu analysiert",
+                snap.id
+            ));
+        }
+    }
+
+    for created in after
+        .iter()
+        .filter(|a| !before.iter().any(|b| b.id == a.id))
+    {
+        tx.execute(
+            "DELETE FROM diarization_segments WHERE id = ?1",
+            rusqlite::params![created.id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    for snap in &before {
+        write_snapshot(&tx, snap)?;
+    }
+
+    tx.execute(
+        "UPDATE transcript_revisions SET reverted = 1 WHERE id = ?1",
+        rusqlite::params![revision_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(Some(revision_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "app")]
+    #[test]
+    fn split_text_proportionally() {
+        let (a, b) = split_text_at_ratio("eins zwei drei vier", 0.5);
+        assert_eq!(a, "eins zwei");
+        assert_eq!(b, "drei vier");
+    }
+
+    #[cfg(feature = "app")]
+    #[test]
+    fn split_text_at_edges() {
+        assert_eq!(
+            split_text_at_ratio("a b", 0.0),
+            (String::new(), "a b".to_string())
+        );
+        assert_eq!(
+            split_text_at_ratio("a b", 1.0),
+            ("a b".to_string(), String::new())
+        );
+    }
+
+    #[test]
+    fn overlap_requires_majority() {
+        let edited = vec![(1000, 2000)];
+        assert!(overlaps_edited(&edited, 1000, 1800));
+        assert!(!overlaps_edited(&edited, 1500, 3000));
+        assert!(!overlaps_edited(&edited, 2000, 3000));
+    }
+
+    /// In-memory database with all migrations and two segments of episode 1.
+    #[cfg(feature = "app")]
+    fn test_db() -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        crate::migrations::apply_pending(&mut conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO episodes (id, title) VALUES (1, 'Folge 1');
+             INSERT INTO diarization_segments (id, episode_id, start_ms, end_ms, speaker_label, text)
+             VALUES (1, 1, 0, 4000, 'SPEAKER_0', 'hallo zusammen und willkommen'),
+                    (2, 1, 4000, 8000, 'SPEAKER_1', 'danke schön');",
+        )
+        .unwrap();
+        conn
+    }
+
+    /// (rowid, start_ms, end_ms, text) of the search index rows matching `term`.
+    #[cfg(feature = "app")]
+    fn search(conn: &Connection, term: &str) -> Vec<(i64, i64, i64, String)> {
+        let mut stmt = conn
+            .prepare(
+                "SELECT rowid, start_ms, end_ms, segment_text FROM search_index \
+                 WHERE search_index MATCH ?1 ORDER BY rowid",
+            )
+            .unwrap();
+        let rows = stmt
+            .query_map([term], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)))
+            .unwrap();
+        rows.map(|r| r.unwrap()).collect()
+    }
+
+    #[cfg(feature = "app")]
+    fn index_size(conn: &Connection) -> i64 {
+        conn.query_row("SELECT COUNT(*) FROM search_index", [], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[cfg(feature = "app")]
+    #[test]
+    fn edit_and_undo_keep_search_index_in_sync() {
+        let mut conn = test_db();
+        edit_text(&mut conn, 1, "servus miteinander".to_string()).unwrap();
+        assert!(search(&conn, "hallo").is_empty());
+        assert_eq!(search(&conn, "servus"), vec![(1, 0, 4000, "servus miteinander".to_string())]);
+
+        undo_last(&mut conn, 1).unwrap();
+        assert!(search(&conn, "servus").is_empty());
+        assert_eq!(search(&conn, "hallo").len(), 1);
+        assert_eq!(index_size(&conn), 2);
+    }
+
+    #[cfg(feature = "app")]
+    #[test]
+    fn split_and_undo_keep_search_index_in_sync() {
+        let mut conn = test_db();
+        let parts = split(&mut conn, 1, 2000, None, None).unwrap();
+        assert_eq!(search(&conn, "hallo"), vec![(1, 0, 2000, "hallo zusammen".to_string())]);
+        assert_eq!(
+            search(&conn, "willkommen"),
+            vec![(parts[1].id, 2000, 4000, "und willkommen".to_string())]
+        );
+        assert_eq!(index_size(&conn), 3);
+
+        undo_last(&mut conn, 1).unwrap();
+        assert_eq!(
+            search(&conn, "willkommen"),
+            vec![(1, 0, 4000, "hallo zusammen und willkommen".to_string())]
+        );
+        assert_eq!(index_size(&conn), 2);
+    }
+
+    #[cfg(feature = "app")]
+    fn embedding(conn: &Connection, segment_id: i64) -> Option<Vec<u8>> {
+        load_snapshot(conn, segment_id).unwrap().embedding
+    }
+
+    #[cfg(feature = "app")]
+    #[test]
+    fn embeddings_survive_undo_and_are_dropped_on_split() {
+        let mut conn = test_db();
+        conn.execute_batch(
+            "UPDATE diarization_segments SET embedding = X'0000803F' WHERE id = 1;
+             UPDATE diarization_segments SET embedding = X'00000040' WHERE id = 2;",
+        )
+        .unwrap();
+
+        merge(&mut conn, &[1, 2]).unwrap();
+        undo_last(&mut conn, 1).unwrap();
+        assert_eq!(embedding(&conn, 1), Some(vec![0x00, 0x00, 0x80, 0x3f]));
+        assert_eq!(embedding(&conn, 2), Some(vec![0x00, 0x00, 0x00, 0x40]));
+
+        let parts = split(&mut conn, 1, 2000, None, None).unwrap();
+        assert_eq!((embedding(&conn, 1), embedding(&conn, parts[1].id)), (None, None));
+        undo_last(&mut conn, 1).unwrap();
+        assert_eq!(embedding(&conn, 1), Some(vec![0x00, 0x00, 0x80, 0x3f]));
+    }
+
+    #[cfg(feature = "app")]
+    #[test]
+    fn merge_and_undo_keep_search_index_in_sync() {
+        let mut conn = test_db();
+        merge(&mut conn, &[1, 2]).unwrap();
+        assert_eq!(
+            search(&conn, "danke"),
+            vec![(1, 0, 8000, "hallo zusammen und willkommen danke schön".to_string())]
+        );
+        assert_eq!(index_size(&conn), 1);
+
+        undo_last(&mut conn, 1).unwrap();
+        assert_eq!(search(&conn, "danke"), vec![(2, 4000, 8000, "danke schön".to_string())]);
+        assert_eq!(search(&conn, "hallo"), vec![(1, 0, 4000, "hallo zusammen und willkommen".to_string())]);
+        assert_eq!(index_size(&conn), 2);
+    }
+
+    #[cfg(feature = "app")]
+    fn corrected(conn: &Connection, segment_id: i64) -> Option<String> {
+        load_snapshot(conn, segment_id).unwrap().corrected_speaker
+    }
+
+    #[cfg(feature = "app")]
+    #[test]
+    fn relabel_range_and_cluster_record_undoable_revisions() {
+        let mut conn = test_db();
+        let changed = relabel_range(&mut conn, 1, 0, 3000, Some("SPEAKER_2".to_string())).unwrap();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(corrected(&conn, 1).as_deref(), Some("SPEAKER_2"));
+        assert_eq!(corrected(&conn, 2), None);
+
+        relabel_by_cluster(&mut conn, 1, "SPEAKER_1", Some("SPEAKER_0".to_string())).unwrap();
+        assert_eq!(corrected(&conn, 2).as_deref(), Some("SPEAKER_0"));
+        assert!(relabel_by_cluster(&mut conn, 1, "SPEAKER_5", None).is_err());
+
+        // "" resets to the diarization label
+        relabel_range(&mut conn, 1, 0, 8000, Some(String::new())).unwrap();
+        assert_eq!((corrected(&conn, 1), corrected(&conn, 2)), (None, None));
+
+        undo_last(&mut conn, 1).unwrap();
+        assert_eq!(corrected(&conn, 2).as_deref(), Some("SPEAKER_0"));
+        undo_last(&mut conn, 1).unwrap();
+        undo_last(&mut conn, 1).unwrap();
+        assert_eq!((corrected(&conn, 1), corrected(&conn, 2)), (None, None));
+    }
+
+    #[test]
+    fn corrections_carry_over_by_majority_overlap() {
+        let corrected = vec![(0, 4000, "SPEAKER_2".to_string()), (4000, 5000, "SPEAKER_0".to_string())];
+        assert_eq!(carried_correction(&corrected, 500, 3500).as_deref(), Some("SPEAKER_2"));
+        assert_eq!(carried_correction(&corrected, 3000, 9000), None);
+        assert_eq!(carried_correction(&corrected, 8000, 9000), None);
+    }
+
+    #[cfg(feature = "app")]
+    #[test]
+    fn undo_is_refused_after_the_segments_were_rediarized() {
+        let mut conn = test_db();
+        relabel_range(&mut conn, 1, 0, 8000, Some("SPEAKER_2".to_string())).unwrap();
+
+        // Re-diarization replaces every segment that was not edited by hand
+        conn.execute_batch(
+            "DELETE FROM diarization_segments WHERE episode_id = 1 AND edited = 0;
+             INSERT INTO diarization_segments (id, episode_id, start_ms, end_ms, speaker_label)
+             VALUES (3, 1, 0, 8000, 'SPEAKER_0');",
+        )
+        .unwrap();
+
+        assert!(undo_last(&mut conn, 1).is_err());
+        let count: i64 = conn
+            .query_row("SELECT COUNT(*) FROM diarization_segments", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 1);
+    }
+}
@@ -1,7 +1,6 @@
 use crate::models::episode::EpisodeMetadata;
 use chrono::{DateTime, NaiveDate};
 use rss::Channel;
-use tauri_plugin_http::reqwest;
 
 const RSS_URL: &str =
     "https://cdn.julephosting.de/podcasts/1188-nettgefluster-der-podcast-eines-ehepaars/feed.rss";
@@ -40,11 +39,16 @@ fn parse_duration_minutes(duration: &str) -> Option<f64> {
 }
 
 /// Fetch and parse the Nettgefluster RSS feed, returning episode metadata for 2024+.
-///
-/// Uses `tauri_plugin_http::reqwest` so the request goes through the macOS sandbox entitlements
-/// declared in the capabilities JSON (network: outbound). Raw `reqwest` would bypass this.
+#[cfg(feature = "app")]
 #[tauri::command]
 pub async fn sync_rss() -> Result<Vec<EpisodeMetadata>, String> {
+    fetch_feed().await
+}
+
+/// The feed behind `sync_rss`, shared with the CLI. Plain `reqwest` is the same
+/// crate `tauri_plugin_http` re-exports; outbound network access is granted to
+/// the whole process by the macOS sandbox entitlement, not per client.
+pub(crate) async fn fetch_feed() -> Result<Vec<EpisodeMetadata>, String> {
     let bytes = reqwest::get(RSS_URL)
         .await
         .map_err(|e| format!("HTTP request failed: {}", e))?
@@ -112,3 +116,38 @@ pub async fn sync_rss() -> Result<Vec<EpisodeMetadata>, String> {
 
     Ok(episodes)
 }
+
+/// Insert feed episodes that are not in the database yet, matched by title and
+/// publish date like the frontend sync (episodes has no UNIQUE constraint).
+/// Returns the number of episodes added.
+pub(crate) fn store_new_episodes(
+    db_path: &std::path::Path,
+    episodes: &[EpisodeMetadata],
+) -> Result<usize, String> {
+    let mut conn = rusqlite::Connection::open(db_path).map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    let mut added = 0;
+    for ep in episodes {
+        added += tx
+            .execute(
+                "INSERT INTO episodes \
+                   (title, description, audio_url, publish_date, duration_minutes, \
+                    episode_number, podcast_name, transcription_status) \
+                 SELECT ?1, ?2, ?3, ?4, ?5, ?6, 'Nettgefluster', 'not_started' \
+                 WHERE NOT EXISTS ( \
+                   SELECT 1 FROM episodes WHERE title = ?1 AND publish_date IS ?4 \
+                 )",
+                rusqlite::params![
+                    ep.title,
+                    ep.description,
+                    ep.audio_url,
+                    ep.pub_date,
+                    ep.duration_minutes,
+                    ep.episode_number
+                ],
+            )
+            .map_err(|e| format!("Episode konnte nicht gespeichert werden: {}", e))?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(added)
+}
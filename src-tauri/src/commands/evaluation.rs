@@ -0,0 +1,366 @@
+use crate::commands::diarization::{
+    diarize_samples, find_diarization_models, label_speakers, read_speaker_count, SpeakerCount,
+    AUTO_CLUSTER_THRESHOLD,
+};
+use crate::commands::overlap::Turn;
+use crate::models::diarization::{DiarizationEvaluation, DiarizationEvent};
+use crate::paths::BinkyPaths;
+use std::collections::{BTreeSet, HashMap};
+use tauri::ipc::Channel;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Diarization evaluation
+//
+// Segments a person reviewed (speaker corrected or text edited) serve as ground
+// truth: the current diarization config is re-run on their episodes' audio and
+// scored against them with the diarization error rate (DER), split into missed
+// speech, false alarm and speaker confusion. Only the reviewed time ranges are
+// scored; the rest of an episode still carries automatic labels, which would
+// score the diarizer against itself. Results are stored per config in
+// diarization_evaluations (migrations 028, 032) to compare setups over time.
+// ─────────────────────────────────────────────────────────────────────────────
+
+const EVALUATION_COLUMNS: &str = "id, segmentation_model, embedding_model, threshold, episode_ids, \
+     reference_ms, missed_ms, false_alarm_ms, confusion_ms, der, created_at, speaker_count";
+
+/// Error times of a hypothesis against a reference, in ms.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ErrorTimes {
+    pub reference_ms: i64,
+    pub missed_ms: i64,
+    pub false_alarm_ms: i64,
+    pub confusion_ms: i64,
+}
+
+impl ErrorTimes {
+    fn add(&mut self, other: ErrorTimes) {
+        self.reference_ms += other.reference_ms;
+        self.missed_ms += other.missed_ms;
+        self.false_alarm_ms += other.false_alarm_ms;
+        self.confusion_ms += other.confusion_ms;
+    }
+
+    fn der(&self) -> f64 {
+        if self.reference_ms == 0 {
+            return 0.0;
+        }
+        (self.missed_ms + self.false_alarm_ms + self.confusion_ms) as f64 / self.reference_ms as f64
+    }
+}
+
+/// Speakers of `turns` talking throughout [from, to).
+fn speakers_in(turns: &[Turn], from: i64, to: i64) -> BTreeSet<&str> {
+    turns
+        .iter()
+        .filter(|(start, end, _)| *start <= from && *end >= to)
+        .map(|(_, _, speaker)| speaker.as_str())
+        .collect()
+}
+
+/// Reviewed segments of an episode as (start, end, effective speaker).
+fn reviewed_turns(conn: &rusqlite::Connection, episode_id: i64) -> Result<Vec<Turn>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT start_ms, end_ms, COALESCE(corrected_speaker, speaker_label) \
+             FROM diarization_segments \
+             WHERE episode_id = ?1 AND (corrected_speaker IS NOT NULL OR edited = 1) \
+             ORDER BY start_ms",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params![episode_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))
+        .map_err(|e| e.to_string())?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+/// The parts of `turns` inside the time covered by `reviewed`.
+pub(crate) fn clip_to_reviewed(turns: &[Turn], reviewed: &[Turn]) -> Vec<Turn> {
+    let mut ranges: Vec<(i64, i64)> = reviewed.iter().map(|(start, end, _)| (*start, *end)).collect();
+    ranges.sort_unstable();
+    let mut merged: Vec<(i64, i64)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    turns
+        .iter()
+        .flat_map(|(start, end, speaker)| {
+            merged.iter().filter_map(move |(from, to)| {
+                let (s, e) = ((*start).max(*from), (*end).min(*to));
+                (e > s).then(|| (s, e, speaker.clone()))
+            })
+        })
+        .collect()
+}
+
+/// Score hypothesis turns against reference turns, NIST style without collar.
+///
+/// The timeline is cut at every boundary of either side. Hypothesis speakers
+/// are mapped one-to-one onto reference speakers by time talked together,
+/// greedily (exact for the handful of speakers in a podcast unless two
+/// mappings tie). Per stretch with r reference and h hypothesis speakers:
+/// missed max(0, r - h), false alarm max(0, h - r), confusion min(r, h) minus
+/// the correctly mapped speakers, each times the stretch length.
+pub(crate) fn error_times(reference: &[Turn], hypothesis: &[Turn]) -> ErrorTimes {
+    let mut boundaries: Vec<i64> = reference
+        .iter()
+        .chain(hypothesis)
+        .flat_map(|(start, end, _)| [*start, *end])
+        .collect();
+    boundaries.sort_unstable();
+    boundaries.dedup();
+
+    let stretches: Vec<(i64, BTreeSet<&str>, BTreeSet<&str>)> = boundaries
+        .windows(2)
+        .map(|w| (w[1] - w[0], speakers_in(reference, w[0], w[1]), speakers_in(hypothesis, w[0], w[1])))
+        .filter(|(_, r, h)| !r.is_empty() || !h.is_empty())
+        .collect();
+
+    // Time each (reference, hypothesis) pair talks together
+    let mut together: HashMap<(&str, &str), i64> = HashMap::new();
+    for (ms, r, h) in &stretches {
+        for (ref_speaker, hyp_speaker) in r.iter().flat_map(|r| h.iter().map(move |h| (*r, *h))) {
+            *together.entry((ref_speaker, hyp_speaker)).or_insert(0) += ms;
+        }
+    }
+    let mut pairs: Vec<((&str, &str), i64)> = together.into_iter().collect();
+    pairs.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let mut mapping: HashMap<&str, &str> = HashMap::new();
+    for ((ref_speaker, hyp_speaker), _) in pairs {
+        if !mapping.contains_key(hyp_speaker) && !mapping.values().any(|r| *r == ref_speaker) {
+            mapping.insert(hyp_speaker, ref_speaker);
+        }
+    }
+
+    let mut times = ErrorTimes::default();
+    for (ms, r, h) in &stretches {
+        let (r_count, h_count) = (r.len() as i64, h.len() as i64);
+        let correct = h.iter().filter(|s| mapping.get(*s).is_some_and(|m| r.contains(m))).count() as i64;
+        times.reference_ms += r_count * ms;
+        times.missed_ms += (r_count - h_count).max(0) * ms;
+        times.false_alarm_ms += (h_count - r_count).max(0) * ms;
+        times.confusion_ms += (r_count.min(h_count) - correct) * ms;
+    }
+    times
+}
+
+fn evaluation_from_row(row: &rusqlite::Row) -> rusqlite::Result<DiarizationEvaluation> {
+    let episode_ids: String = row.get(4)?;
+    Ok(DiarizationEvaluation {
+        id: row.get(0)?,
+        segmentation_model: row.get(1)?,
+        embedding_model: row.get(2)?,
+        threshold: row.get(3)?,
+        speaker_count: row.get(11)?,
+        episode_ids: episode_ids.split(',').filter_map(|id| id.parse().ok()).collect(),
+        reference_ms: row.get(5)?,
+        missed_ms: row.get(6)?,
+        false_alarm_ms: row.get(7)?,
+        confusion_ms: row.get(8)?,
+        der: row.get(9)?,
+        created_at: row.get(10)?,
+    })
+}
+
+/// Re-run diarization with the current config (and an optional clustering
+/// threshold to try) on episodes with reviewed segments — all, or the given
+/// ones — and score it against the reviewed speakers. The result is stored.
+///
+/// The threshold only steers clustering when the speaker count is left open,
+/// so trying one clusters every episode by it, ignoring their speaker count.
+/// Without one, each episode uses its own speaker count setting.
+#[tauri::command]
+pub async fn evaluate_diarization(
+    episode_ids: Option<Vec<i64>>,
+    threshold: Option<f32>,
+    on_event: Channel<DiarizationEvent>,
+    app: tauri::AppHandle,
+) -> Result<DiarizationEvaluation, String> {
+    let forced_count = threshold.map(|_| SpeakerCount::Auto);
+    let threshold = threshold.unwrap_or(AUTO_CLUSTER_THRESHOLD);
+    if !(threshold > 0.0 && threshold < 1.0) {
+        return Err("Schwellwert muss zwischen 0 und 1 liegen".to_string());
+    }
+
+    let paths = BinkyPaths::from_app(&app)?;
+    let db_path = paths.db_path.clone();
+    let setup = find_diarization_models(&paths).await?;
+
+    // (episode id, audio URL, reviewed turns)
+    let episodes: Vec<(i64, String, Vec<Turn>)> = {
+        let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT DISTINCT e.id, e.audio_url FROM episodes e \
+                 JOIN diarization_segments d ON d.episode_id = e.id \
+                 WHERE (d.corrected_speaker IS NOT NULL OR d.edited = 1) \
+                   AND e.audio_url IS NOT NULL ORDER BY e.id",
+            )
+            .map_err(|e| e.to_string())?;
+        let corrected: Vec<(i64, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .filter(|(id, _)| episode_ids.as_ref().is_none_or(|ids| ids.contains(id)))
+            .collect();
+        let mut episodes = Vec::new();
+        for (episode_id, audio_url) in corrected {
+            episodes.push((episode_id, audio_url, reviewed_turns(&conn, episode_id)?));
+        }
+        episodes
+    };
+    if episodes.is_empty() {
+        return Err("Keine korrigierten Episoden zum Auswerten gefunden".to_string());
+    }
+
+    let total = episodes.len() as i32;
+    let mut times = ErrorTimes::default();
+    let mut evaluated = Vec::new();
+    for (index, (episode_id, audio_url, reference)) in episodes.into_iter().enumerate() {
+        let index = index as i32;
+        let samples = crate::commands::pipeline::decode_episode_audio(&paths, episode_id, &audio_url, |percent| {
+            let _ = on_event.send(DiarizationEvent::Progress {
+                percent: (index * 100 + percent / 2) / total,
+            });
+        })
+        .await?;
+
+        let episode_setup = setup.clone();
+        let speaker_count = forced_count.unwrap_or_else(|| read_speaker_count(&db_path, episode_id));
+        let hypothesis = tauri::async_runtime::spawn_blocking(move || {
+            let segments = diarize_samples(&episode_setup, &samples, speaker_count, threshold)?;
+            // Same noise merging as the diarize stage; labels don't matter
+            // since speakers are mapped by time
+            let (segments, _) = label_speakers(segments, &HashMap::new(), &[], speaker_count);
+            Ok::<Vec<Turn>, String>(
+                segments
+                    .into_iter()
+                    .map(|s| (s.start_ms, s.end_ms, s.speaker_label))
+                    .collect(),
+            )
+        })
+        .await
+        .map_err(|e| format!("Diarization task panicked: {}", e))??;
+
+        times.add(error_times(&reference, &clip_to_reviewed(&hypothesis, &reference)));
+        evaluated.push(episode_id);
+        let _ = on_event.send(DiarizationEvent::Progress {
+            percent: ((index + 1) * 100) / total,
+        });
+    }
+
+    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
+    let episode_ids = evaluated.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(",");
+    conn.execute(
+        "INSERT INTO diarization_evaluations \
+         (segmentation_model, embedding_model, threshold, speaker_count, episode_ids, reference_ms, \
+          missed_ms, false_alarm_ms, confusion_ms, der) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        rusqlite::params![
+            setup.segmentation.id,
+            setup.embedding.id,
+            threshold as f64,
+            if forced_count.is_some() { "auto" } else { "episode" },
+            episode_ids,
+            times.reference_ms,
+            times.missed_ms,
+            times.false_alarm_ms,
+            times.confusion_ms,
+            times.der(),
+        ],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.query_row(
+        &format!("SELECT {} FROM diarization_evaluations WHERE id = ?1", EVALUATION_COLUMNS),
+        rusqlite::params![conn.last_insert_rowid()],
+        evaluation_from_row,
+    )
+    .map_err(|e| e.to_string())
+}
+
+/// All stored evaluation runs, newest first.
+#[tauri::command]
+pub async fn list_diarization_evaluations(app: tauri::AppHandle) -> Result<Vec<DiarizationEvaluation>, String> {
+    let paths = BinkyPaths::from_app(&app)?;
+    let conn = rusqlite::Connection::open(&paths.db_path).map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(&format!(
+            "SELECT {} FROM diarization_evaluations ORDER BY created_at DESC, id DESC",
+            EVALUATION_COLUMNS
+        ))
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], evaluation_from_row)
+        .map_err(|e| e.to_string())?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(start_s: i64, end_s: i64, speaker: &str) -> Turn {
+        (start_s * 1000, end_s * 1000, speaker.to_string())
+    }
+
+    #[test]
+    fn perfect_hypothesis_with_other_labels_scores_zero() {
+        let reference = vec![turn(0, 10, "SPEAKER_0"), turn(10, 20, "SPEAKER_1")];
+        let hypothesis = vec![turn(0, 10, "SPEAKER_1"), turn(10, 20, "SPEAKER_0")];
+        let times = error_times(&reference, &hypothesis);
+        assert_eq!(times.reference_ms, 20_000);
+        assert_eq!(times.der(), 0.0);
+    }
+
+    #[test]
+    fn splits_errors_into_missed_false_alarm_and_confusion() {
+        let reference = vec![
+            turn(0, 10, "SPEAKER_0"),
+            turn(10, 20, "SPEAKER_1"),
+            turn(20, 30, "SPEAKER_0"),
+        ];
+        let hypothesis = vec![
+            // 2 s of SPEAKER_1 missed
+            turn(0, 10, "A"),
+            turn(12, 20, "B"),
+            // 3 s attributed to the wrong speaker
+            turn(20, 27, "A"),
+            turn(27, 30, "B"),
+            // 5 s of speech where the reference has a pause
+            turn(30, 35, "A"),
+        ];
+        let times = error_times(&reference, &hypothesis);
+        assert_eq!(
+            times,
+            ErrorTimes {
+                reference_ms: 30_000,
+                missed_ms: 2_000,
+                false_alarm_ms: 5_000,
+                confusion_ms: 3_000,
+            }
+        );
+        assert!((times.der() - 1.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn overlapping_reference_speech_counts_per_speaker() {
+        // Both talk from 5 to 10 s; the hypothesis only has one of them there
+        let reference = vec![turn(0, 10, "SPEAKER_0"), turn(5, 15, "SPEAKER_1")];
+        let hypothesis = vec![turn(0, 10, "A"), turn(10, 15, "B")];
+        let times = error_times(&reference, &hypothesis);
+        assert_eq!(times.reference_ms, 20_000);
+        assert_eq!(times.missed_ms, 5_000);
+        assert_eq!(times.confusion_ms, 0);
+    }
+
+    #[test]
+    fn only_reviewed_time_is_scored() {
+        // Reviewed: 0–10 s and 20–30 s; the hypothesis talks through the gap
+        let reviewed = vec![turn(0, 10, "SPEAKER_0"), turn(20, 30, "SPEAKER_1")];
+        let hypothesis = vec![turn(0, 15, "A"), turn(15, 30, "B")];
+        let clipped = clip_to_reviewed(&hypothesis, &reviewed);
+        assert_eq!(clipped, vec![turn(0, 10, "A"), turn(20, 30, "B")]);
+        assert_eq!(error_times(&reviewed, &clipped).der(), 0.0);
+    }
+}
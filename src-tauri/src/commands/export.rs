@@ -0,0 +1,588 @@
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+#[cfg(feature = "app")]
+use tauri::Manager;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Srt,
+    Vtt,
+    Txt,
+    Md,
+    Json,
+}
+
+impl ExportFormat {
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Srt => "srt",
+            ExportFormat::Vtt => "vtt",
+            ExportFormat::Txt => "txt",
+            ExportFormat::Md => "md",
+            ExportFormat::Json => "json",
+        }
+    }
+}
+
+impl std::str::FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "srt" => Ok(ExportFormat::Srt),
+            "vtt" => Ok(ExportFormat::Vtt),
+            "txt" => Ok(ExportFormat::Txt),
+            "md" => Ok(ExportFormat::Md),
+            "json" => Ok(ExportFormat::Json),
+            other => Err(format!("Unbekanntes Exportformat: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ExportOptions {
+    /// Prefix lines with timestamps (TXT / Markdown). SRT and WebVTT always carry cue times.
+    #[serde(default = "default_true")]
+    pub include_timestamps: bool,
+    /// Join consecutive segments of the same speaker into one turn.
+    #[serde(default)]
+    pub merge_speaker_turns: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            include_timestamps: true,
+            merge_speaker_turns: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExportSegment {
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub speaker: Option<String>,
+    pub text: String,
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Loading
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Map effective speaker labels (SPEAKER_N) to display names for one episode.
+///
+/// Priority: episode-specific host_profiles row → global host_profiles row
+/// (episode_id IS NULL) → host_N_name setting. Labels without a name are absent
+/// from the map so callers can fall back to the raw label.
+pub(crate) fn resolve_speaker_names(conn: &Connection, episode_id: i64) -> HashMap<String, String> {
+    let mut names: HashMap<String, String> = HashMap::new();
+
+    for key in ["host_0_name", "host_1_name"] {
+        let name: Option<String> = conn
+            .query_row(
+                "SELECT value FROM settings WHERE key = ?1",
+                rusqlite::params![key],
+                |row| row.get(0),
+            )
+            .ok();
+        if let Some(name) = name.filter(|n| !n.trim().is_empty()) {
+            let label = key.replace("host_", "SPEAKER_").replace("_name", "");
+            names.insert(label, name);
+        }
+    }
+
+    // ORDER BY puts global rows (NULL episode_id) first so episode rows overwrite them.
+    if let Ok(mut stmt) = conn.prepare(
+        "SELECT speaker_label, host_name FROM host_profiles \
+         WHERE episode_id IS NULL OR episode_id = ?1 \
+         ORDER BY episode_id IS NOT NULL, id",
+    ) {
+        let rows: Vec<(String, String)> = match stmt.query_map(rusqlite::params![episode_id], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        }) {
+            Ok(mapped) => mapped.filter_map(|r| r.ok()).collect(),
+            Err(_) => vec![],
+        };
+        for (label, name) in rows {
+            if !name.trim().is_empty() {
+                names.insert(label, name);
+            }
+        }
+    }
+
+    names
+}
+
+/// Load the segments to export for one episode.
+///
+/// Uses diarization_segments text when any is present (speaker-attributed),
+/// otherwise falls back to the Whisper segments_json without speakers.
+pub(crate) fn load_export_segments(
+    conn: &Connection,
+    episode_id: i64,
+) -> Result<Vec<ExportSegment>, String> {
+    let names = resolve_speaker_names(conn, episode_id);
+
+    let diarized: Vec<ExportSegment> = {
+        let mut stmt = conn
+            .prepare(
+                "SELECT start_ms, end_ms, COALESCE(corrected_speaker, speaker_label), text \
+                 FROM diarization_segments \
+                 WHERE episode_id = ?1 AND text IS NOT NULL AND text != '' \
+                 ORDER BY start_ms",
+            )
+            .map_err(|e| e.to_string())?;
+        let collected: Vec<ExportSegment> = stmt
+            .query_map(rusqlite::params![episode_id], |row| {
+                let label: String = row.get(2)?;
+                Ok(ExportSegment {
+                    start_ms: row.get(0)?,
+                    end_ms: row.get(1)?,
+                    speaker: Some(names.get(&label).cloned().unwrap_or(label)),
+                    text: row.get::<_, String>(3)?.trim().to_string(),
+                })
+            })
+            .map_err(|e| e.to_string())?
+            .filter_map(|r| r.ok())
+            .collect();
+        collected
+    };
+
+    if !diarized.is_empty() {
+        return Ok(diarized);
+    }
+
+    let transcript: Option<(String, Option<String>)> = conn
+        .query_row(
+            "SELECT full_text, segments_json FROM transcripts WHERE episode_id = ?1",
+            rusqlite::params![episode_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .ok();
+
+    let (full_text, segments_json) = match transcript {
+        Some(t) => t,
+        None => return Err("Kein Transkript für diese Episode gefunden".to_string()),
+    };
+
+    let from_json: Vec<ExportSegment> = segments_json
+        .and_then(|json| serde_json::from_str::<Vec<serde_json::Value>>(&json).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|v| {
+            let text = v["text"].as_str()?.trim().to_string();
+            // Skip empty text and segments flagged by the hallucination filter
+            if text.is_empty() || v["hallucination"].is_string() {
+                return None;
+            }
+            Some(ExportSegment {
+                start_ms: v["start_ms"].as_i64()?,
+                end_ms: v["end_ms"].as_i64()?,
+                speaker: None,
+                text,
+            })
+        })
+        .collect();
+
+    if !from_json.is_empty() {
+        return Ok(from_json);
+    }
+
+    if full_text.trim().is_empty() {
+        return Err("Kein Transkript für diese Episode gefunden".to_string());
+    }
+
+    // No timing information at all — export the plain text as one block.
+    Ok(vec![ExportSegment {
+        start_ms: 0,
+        end_ms: 0,
+        speaker: None,
+        text: full_text.trim().to_string(),
+    }])
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Rendering
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Join consecutive segments with the same speaker into one turn.
+/// Segments without a speaker are never merged.
+pub(crate) fn merge_speaker_turns(segments: &[ExportSegment]) -> Vec<ExportSegment> {
+    let mut merged: Vec<ExportSegment> = Vec::with_capacity(segments.len());
+    for seg in segments {
+        match merged.last_mut() {
+            Some(prev) if prev.speaker.is_some() && prev.speaker == seg.speaker => {
+                prev.end_ms = prev.end_ms.max(seg.end_ms);
+                prev.text.push(' ');
+                prev.text.push_str(&seg.text);
+            }
+            _ => merged.push(seg.clone()),
+        }
+    }
+    merged
+}
+
+/// Format milliseconds as HH:MM:SS<sep>mmm (SRT uses ',', WebVTT uses '.').
+fn format_cue_time(ms: i64, sep: char) -> String {
+    let ms = ms.max(0);
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        (ms / 60_000) % 60,
+        (ms / 1000) % 60,
+        sep,
+        ms % 1000
+    )
+}
+
+/// Format milliseconds as HH:MM:SS for TXT / Markdown.
+fn format_clock(ms: i64) -> String {
+    let secs = ms.max(0) / 1000;
+    format!("{:02}:{:02}:{:02}", secs / 3600, (secs / 60) % 60, secs % 60)
+}
+
+pub(crate) fn render_export(
+    format: ExportFormat,
+    episode_id: i64,
+    title: &str,
+    segments: &[ExportSegment],
+    options: &ExportOptions,
+) -> String {
+    let merged;
+    let segments = if options.merge_speaker_turns {
+        merged = merge_speaker_turns(segments);
+        &merged[..]
+    } else {
+        segments
+    };
+
+    let mut out = String::new();
+    match format {
+        ExportFormat::Srt => {
+            for (i, seg) in segments.iter().enumerate() {
+                out.push_str(&format!(
+                    "{}\n{} --> {}\n",
+                    i + 1,
+                    format_cue_time(seg.start_ms, ','),
+                    format_cue_time(seg.end_ms, ',')
+                ));
+                match &seg.speaker {
+                    Some(speaker) => out.push_str(&format!("{}: {}\n\n", speaker, seg.text)),
+                    None => out.push_str(&format!("{}\n\n", seg.text)),
+                }
+            }
+        }
+        ExportFormat::Vtt => {
+            out.push_str("WEBVTT\n\n");
+            for seg in segments {
+                out.push_str(&format!(
+                    "{} --> {}\n",
+                    format_cue_time(seg.start_ms, '.'),
+                    format_cue_time(seg.end_ms, '.')
+                ));
+                match &seg.speaker {
+                    Some(speaker) => out.push_str(&format!("<v {}>{}\n\n", speaker, seg.text)),
+                    None => out.push_str(&format!("{}\n\n", seg.text)),
+                }
+            }
+        }
+        ExportFormat::Txt => {
+            for seg in segments {
+                if options.include_timestamps {
+                    out.push_str(&format!("[{}] ", format_clock(seg.start_ms)));
+                }
+                if let Some(speaker) = &seg.speaker {
+                    out.push_str(&format!("{}: ", speaker));
+                }
+                out.push_str(&seg.text);
+                out.push('\n');
+            }
+        }
+        ExportFormat::Md => {
+            out.push_str(&format!("# {}\n\n", title));
+            for seg in segments {
+                let mut header: Vec<String> = Vec::new();
+                if let Some(speaker) = &seg.speaker {
+                    header.push(format!("**{}**", speaker));
+                }
+                if options.include_timestamps {
+                    header.push(format!("_{}_", format_clock(seg.start_ms)));
+                }
+                if !header.is_empty() {
+                    out.push_str(&header.join(" "));
+                    out.push_str("\n\n");
+                }
+                out.push_str(&seg.text);
+                out.push_str("\n\n");
+            }
+        }
+        ExportFormat::Json => {
+            let doc = serde_json::json!({
+                "episode_id": episode_id,
+                "title": title,
+                "segments": segments,
+            });
+            out = serde_json::to_string_pretty(&doc).unwrap_or_default();
+        }
+    }
+    out
+}
+
+/// Build a filesystem-safe file name such as "042 - Folgentitel.srt". Control
+/// characters and line breaks become spaces; an empty title falls back to the
+/// episode id.
+pub(crate) fn export_file_name(
+    episode_id: i64,
+    episode_number: Option<i64>,
+    title: &str,
+    format: ExportFormat,
+) -> String {
+    let safe_title: String = title
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => ' ',
+            c => c,
+        })
+        .collect();
+    let mut safe_title = safe_title.split_whitespace().collect::<Vec<_>>().join(" ");
+    if safe_title.is_empty() {
+        safe_title = format!("Folge {}", episode_id);
+    }
+    match episode_number {
+        Some(n) => format!("{:03} - {}.{}", n, safe_title, format.extension()),
+        None => format!("{}.{}", safe_title, format.extension()),
+    }
+}
+
+pub(crate) fn export_episode(
+    conn: &Connection,
+    episode_id: i64,
+    format: ExportFormat,
+    options: &ExportOptions,
+) -> Result<(Option<i64>, String, String), String> {
+    let (episode_number, title): (Option<i64>, String) = conn
+        .query_row(
+            "SELECT episode_number, title FROM episodes WHERE id = ?1",
+            rusqlite::params![episode_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| format!("Episode {} nicht gefunden: {}", episode_id, e))?;
+
+    let segments = load_export_segments(conn, episode_id)?;
+    let rendered = render_export(format, episode_id, &title, &segments, options);
+    Ok((episode_number, title, rendered))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Commands
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Render one episode's transcript in the requested format and return it as a string.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub fn export_transcript(
+    episode_id: i64,
+    format: ExportFormat,
+    options: Option<ExportOptions>,
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    let db_path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("binky.db");
+    let conn = Connection::open(&db_path).map_err(|e| e.to_string())?;
+
+    let options = options.unwrap_or_default();
+    let (_, _, rendered) = export_episode(&conn, episode_id, format, &options)?;
+    Ok(rendered)
+}
+
+/// Export every transcribed episode into `folder`, one file per episode.
+/// Episodes without any transcript text are skipped. Returns the number of files written.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub fn export_all_transcripts(
+    folder: String,
+    format: ExportFormat,
+    options: Option<ExportOptions>,
+    app: tauri::AppHandle,
+) -> Result<u32, String> {
+    let db_path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| e.to_string())?
+        .join("binky.db");
+    export_to_folder(&db_path, None, Path::new(&folder), format, &options.unwrap_or_default())
+}
+
+/// Export the given episodes, or every episode with text when None, into
+/// `folder`. Episodes that fail to render are skipped. Returns the number of
+/// files written.
+pub(crate) fn export_to_folder(
+    db_path: &Path,
+    episode_ids: Option<&[i64]>,
+    folder: &Path,
+    format: ExportFormat,
+    options: &ExportOptions,
+) -> Result<u32, String> {
+    std::fs::create_dir_all(folder)
+        .map_err(|e| format!("Exportordner konnte nicht erstellt werden: {}", e))?;
+
+    let conn = Connection::open(db_path).map_err(|e| e.to_string())?;
+
+    let episode_ids: Vec<i64> = match episode_ids {
+        Some(ids) => ids.to_vec(),
+        None => exportable_episodes(&conn)?,
+    };
+
+    // Lower-cased, since the file systems of macOS and Windows ignore case
+    let mut taken: HashSet<String> = HashSet::new();
+    let mut written: u32 = 0;
+    for episode_id in episode_ids {
+        let (episode_number, title, rendered) = match export_episode(&conn, episode_id, format, options) {
+            Ok(v) => v,
+            Err(e) => {
+                eprintln!("[export] Skipping episode {}: {}", episode_id, e);
+                continue;
+            }
+        };
+        let mut file_name = export_file_name(episode_id, episode_number, &title, format);
+        if !taken.insert(file_name.to_lowercase()) {
+            // Same number and title as an episode already written: keep both
+            let stem = file_name.strip_suffix(&format!(".{}", format.extension())).unwrap_or(&file_name);
+            file_name = format!("{} ({}).{}", stem, episode_id, format.extension());
+            taken.insert(file_name.to_lowercase());
+        }
+        let path = folder.join(file_name);
+        std::fs::write(&path, rendered)
+            .map_err(|e| format!("Datei {} konnte nicht geschrieben werden: {}", path.display(), e))?;
+        written += 1;
+    }
+
+    Ok(written)
+}
+
+/// Episodes with a transcript or segment text, oldest first.
+fn exportable_episodes(conn: &Connection) -> Result<Vec<i64>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT e.id FROM episodes e \
+             WHERE EXISTS (SELECT 1 FROM transcripts t WHERE t.episode_id = e.id) \
+                OR EXISTS (SELECT 1 FROM diarization_segments ds \
+                           WHERE ds.episode_id = e.id AND ds.text IS NOT NULL AND ds.text != '') \
+             ORDER BY e.publish_date ASC",
+        )
+        .map_err(|e| e.to_string())?;
+    let episode_ids = stmt
+        .query_map([], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    Ok(episode_ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seg(start_ms: i64, end_ms: i64, speaker: Option<&str>, text: &str) -> ExportSegment {
+        ExportSegment {
+            start_ms,
+            end_ms,
+            speaker: speaker.map(|s| s.to_string()),
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn cue_time_formats() {
+        assert_eq!(format_cue_time(3_723_456, ','), "01:02:03,456");
+        assert_eq!(format_cue_time(1_500, '.'), "00:00:01.500");
+    }
+
+    #[test]
+    fn merges_consecutive_same_speaker() {
+        let segments = vec![
+            seg(0, 1000, Some("Anna"), "Hallo"),
+            seg(1000, 2000, Some("Anna"), "zusammen"),
+            seg(2000, 3000, Some("Ben"), "Hi"),
+        ];
+        let merged = merge_speaker_turns(&segments);
+        assert_eq!(merged.len(), 2);
+        assert_eq!(merged[0], seg(0, 2000, Some("Anna"), "Hallo zusammen"));
+    }
+
+    #[test]
+    fn unattributed_segments_not_merged() {
+        let segments = vec![seg(0, 1000, None, "Eins"), seg(1000, 2000, None, "Zwei")];
+        assert_eq!(merge_speaker_turns(&segments).len(), 2);
+    }
+
+    #[test]
+    fn srt_numbers_cues_and_prefixes_speaker() {
+        let out = render_export(
+            ExportFormat::Srt,
+            1,
+            "Folge",
+            &[seg(0, 1500, Some("Anna"), "Hallo")],
+            &ExportOptions::default(),
+        );
+        assert_eq!(out, "1\n00:00:00,000 --> 00:00:01,500\nAnna: Hallo\n\n");
+    }
+
+    #[test]
+    fn txt_without_timestamps() {
+        let options = ExportOptions {
+            include_timestamps: false,
+            merge_speaker_turns: false,
+        };
+        let out = render_export(ExportFormat::Txt, 1, "Folge", &[seg(0, 1000, Some("Ben"), "Hi")], &options);
+        assert_eq!(out, "Ben: Hi\n");
+    }
+
+    #[test]
+    fn file_name_is_sanitized() {
+        assert_eq!(export_file_name(1, Some(7), "A/B: C?", ExportFormat::Md), "007 - A_B_ C_.md");
+        assert_eq!(export_file_name(1, None, "Eins\nZwei\tDrei", ExportFormat::Txt), "Eins Zwei Drei.txt");
+        assert_eq!(export_file_name(42, None, " \r\n ", ExportFormat::Txt), "Folge 42.txt");
+    }
+
+    #[test]
+    fn episodes_with_the_same_name_get_separate_files() {
+        let dir = std::env::temp_dir().join(format!("binky-export-test-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join("binky.db");
+        let mut conn = Connection::open(&db_path).unwrap();
+        crate::migrations::apply_pending(&mut conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO episodes (id, title, episode_number) VALUES (1, 'Folge', 3), (2, 'folge', 3);
+             INSERT INTO diarization_segments (episode_id, start_ms, end_ms, speaker_label, text)
+             VALUES (1, 0, 1000, 'SPEAKER_0', 'eins'), (2, 0, 1000, 'SPEAKER_0', 'zwei');",
+        )
+        .unwrap();
+
+        let out = dir.join("out");
+        let written =
+            export_to_folder(&db_path, None, &out, ExportFormat::Txt, &ExportOptions::default()).unwrap();
+        let mut files: Vec<String> = std::fs::read_dir(&out)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(written, 2);
+        assert_eq!(files, vec!["003 - Folge.txt", "003 - folge (2).txt"]);
+    }
+}
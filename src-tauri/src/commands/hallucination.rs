@@ -0,0 +1,339 @@
+use crate::commands::transcription::{is_duplicate_segment, read_setting};
+use crate::models::transcript::HallucinationFilter;
+use std::path::Path;
+#[cfg(feature = "app")]
+use tauri::Manager;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Whisper hallucination filter
+//
+// Whisper invents text on silence and music: stock subtitle credits, phrases
+// looping within one segment, or segments it itself rates as non-speech.
+// Each segment is classified once after decoding; flagged segments are kept in
+// segments_json (with a `hallucination` reason) but excluded from full_text,
+// the speaker backfill and exports.
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Longest phrase (in words) checked for back-to-back repetition.
+const MAX_NGRAM_WORDS: usize = 6;
+
+/// Words a segment may contain besides a stock phrase and still count as one.
+const STOCK_PHRASE_SLACK_WORDS: usize = 2;
+
+/// no_speech_prob a low-confidence segment needs before it is flagged. Mumbled,
+/// fast or dialect speech decodes with a very low logprob too, but Whisper
+/// still rates it as speech.
+const LOW_CONFIDENCE_MIN_NO_SPEECH: f32 = 0.3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HallucinationKind {
+    /// Exact repeat of one of the last 20 segments.
+    Duplicate,
+    /// A phrase looping within the segment.
+    Repetition,
+    /// Known filler Whisper emits on silence.
+    StockPhrase,
+    /// Whisper's own silence rule: high no_speech_prob and low logprob.
+    NoSpeech,
+    /// Very low logprob on a segment Whisper doubts is speech at all.
+    LowConfidence,
+}
+
+impl HallucinationKind {
+    pub(crate) fn as_str(&self) -> &'static str {
+        match self {
+            HallucinationKind::Duplicate => "duplicate",
+            HallucinationKind::Repetition => "repetition",
+            HallucinationKind::StockPhrase => "stock_phrase",
+            HallucinationKind::NoSpeech => "no_speech",
+            HallucinationKind::LowConfidence => "low_confidence",
+        }
+    }
+}
+
+/// Defaults tuned for German podcasts. The silence thresholds are stricter than
+/// whisper.cpp's decoding fallback (0.6 / -1.0) so real speech is rarely lost.
+pub(crate) fn default_hallucination_filter() -> HallucinationFilter {
+    HallucinationFilter {
+        enabled: true,
+        max_ngram_repeats: 4,
+        no_speech_thold: 0.8,
+        logprob_thold: -1.0,
+        min_avg_logprob: -2.0,
+        stock_phrases: [
+            "Untertitel im Auftrag des ZDF",
+            "Untertitel der Amara.org-Community",
+            "Untertitelung im Auftrag des ZDF",
+            "Untertitelung aufgrund der Amara.org-Community",
+            "Vielen Dank fürs Zuschauen",
+            "Copyright WDR",
+            "Subtitles by the Amara.org community",
+            "Thanks for watching",
+        ]
+        .iter()
+        .map(|p| p.to_string())
+        .collect(),
+    }
+}
+
+/// Clamp user-supplied values into meaningful ranges.
+fn sanitize_hallucination_filter(mut filter: HallucinationFilter) -> HallucinationFilter {
+    filter.max_ngram_repeats = filter.max_ngram_repeats.max(1);
+    filter.no_speech_thold = filter.no_speech_thold.clamp(0.0, 1.0);
+    filter.logprob_thold = filter.logprob_thold.min(0.0);
+    filter.min_avg_logprob = filter.min_avg_logprob.min(0.0);
+    filter.stock_phrases.retain(|p| !normalize_words(p).is_empty());
+    filter
+}
+
+/// Active filter from `whisper_hallucination_filter`; defaults if unset or invalid.
+pub(crate) fn read_hallucination_filter(db_path: &Path) -> HallucinationFilter {
+    read_setting(db_path, "whisper_hallucination_filter")
+        .and_then(|json| serde_json::from_str::<HallucinationFilter>(&json).ok())
+        .map(sanitize_hallucination_filter)
+        .unwrap_or_else(default_hallucination_filter)
+}
+
+/// Lowercased words without punctuation ("Amara.org-Community" → amara, org, community).
+fn normalize_words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_string())
+        .collect()
+}
+
+/// True if some phrase of 1–6 words occurs more than `max_repeats` times back to back.
+fn has_looping_ngram(words: &[String], max_repeats: usize) -> bool {
+    for n in 1..=MAX_NGRAM_WORDS {
+        if words.len() < n * (max_repeats + 1) {
+            break;
+        }
+        for start in 0..=(words.len() - n) {
+            let ngram = &words[start..start + n];
+            let mut repeats = 1;
+            let mut next = start + n;
+            while next + n <= words.len() && &words[next..next + n] == ngram {
+                repeats += 1;
+                next += n;
+            }
+            if repeats > max_repeats {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+/// True if the segment is (almost) nothing but one of the stock phrases.
+fn is_stock_phrase(words: &[String], phrases: &[String]) -> bool {
+    phrases.iter().any(|phrase| {
+        let phrase = normalize_words(phrase);
+        !phrase.is_empty()
+            && words.len() <= phrase.len() + STOCK_PHRASE_SLACK_WORDS
+            && words.windows(phrase.len()).any(|w| w == phrase.as_slice())
+    })
+}
+
+/// Mean log-probability of a segment's text tokens (0.0 without tokens).
+pub(crate) fn average_logprob(token_logprobs: &[f32]) -> f32 {
+    if token_logprobs.is_empty() {
+        return 0.0;
+    }
+    token_logprobs.iter().sum::<f32>() / token_logprobs.len() as f32
+}
+
+/// Classify one decoded segment. `previous` are the segments collected so far
+/// (flagged ones included). Exact duplicates are flagged even with the filter
+/// disabled, as they were always dropped before the filter existed.
+pub(crate) fn classify_segment(
+    text: &str,
+    avg_logprob: f32,
+    no_speech_prob: f32,
+    previous: &[serde_json::Value],
+    filter: &HallucinationFilter,
+) -> Option<HallucinationKind> {
+    if is_duplicate_segment(text, previous) {
+        return Some(HallucinationKind::Duplicate);
+    }
+    if !filter.enabled {
+        return None;
+    }
+    let words = normalize_words(text);
+    if words.is_empty() {
+        return None;
+    }
+    if is_stock_phrase(&words, &filter.stock_phrases) {
+        return Some(HallucinationKind::StockPhrase);
+    }
+    if has_looping_ngram(&words, filter.max_ngram_repeats) {
+        return Some(HallucinationKind::Repetition);
+    }
+    if no_speech_prob >= filter.no_speech_thold && avg_logprob < filter.logprob_thold {
+        return Some(HallucinationKind::NoSpeech);
+    }
+    if avg_logprob < filter.min_avg_logprob && no_speech_prob >= LOW_CONFIDENCE_MIN_NO_SPEECH {
+        return Some(HallucinationKind::LowConfidence);
+    }
+    None
+}
+
+/// Current hallucination filter settings.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn get_hallucination_filter(app: tauri::AppHandle) -> Result<HallucinationFilter, String> {
+    let db_path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Cannot resolve app data dir: {}", e))?
+        .join("binky.db");
+    Ok(read_hallucination_filter(&db_path))
+}
+
+/// Save the hallucination filter. Applies to transcriptions started afterwards.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn set_hallucination_filter(
+    filter: HallucinationFilter,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let db_path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Cannot resolve app data dir: {}", e))?
+        .join("binky.db");
+
+    let json = serde_json::to_string(&sanitize_hallucination_filter(filter))
+        .map_err(|e| e.to_string())?;
+    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
+    conn.execute(
+        "INSERT OR REPLACE INTO settings (key, value) VALUES ('whisper_hallucination_filter', ?1)",
+        rusqlite::params![json],
+    )
+    .map_err(|e| format!("Filtereinstellungen konnten nicht gespeichert werden: {}", e))?;
+    Ok(())
+}
+
+/// Segments of an episode's Whisper transcript that the filter flagged, as
+/// stored in segments_json (`text`, `start_ms`, `end_ms`, `hallucination`).
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn list_flagged_segments(
+    episode_id: i64,
+    app: tauri::AppHandle,
+) -> Result<Vec<serde_json::Value>, String> {
+    let db_path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Cannot resolve app data dir: {}", e))?
+        .join("binky.db");
+
+    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
+    let segments_json: Option<String> = conn
+        .query_row(
+            "SELECT segments_json FROM transcripts WHERE episode_id = ?1",
+            rusqlite::params![episode_id],
+            |row| row.get(0),
+        )
+        .map_err(|_| "Kein Transkript für diese Episode gefunden".to_string())?;
+
+    Ok(segments_json
+        .and_then(|json| serde_json::from_str::<Vec<serde_json::Value>>(&json).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|seg| seg["hallucination"].is_string())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(text: &str) -> Option<HallucinationKind> {
+        classify_segment(text, -0.3, 0.05, &[], &default_hallucination_filter())
+    }
+
+    #[test]
+    fn normal_speech_passes() {
+        assert_eq!(classify("Heute geht es um den Eisvogel und seine Brutzeit."), None);
+    }
+
+    #[test]
+    fn exact_duplicate_flagged_even_when_disabled() {
+        let previous = vec![serde_json::json!({ "text": "Hallo Welt", "start_ms": 0, "end_ms": 1000 })];
+        let mut filter = default_hallucination_filter();
+        filter.enabled = false;
+        assert_eq!(
+            classify_segment("hallo welt", -0.3, 0.0, &previous, &filter),
+            Some(HallucinationKind::Duplicate)
+        );
+        assert_eq!(classify_segment("Etwas anderes", -5.0, 0.99, &previous, &filter), None);
+    }
+
+    #[test]
+    fn stock_phrase_flagged_with_punctuation_and_case() {
+        assert_eq!(
+            classify(" Untertitel im Auftrag des ZDF, 2021"),
+            Some(HallucinationKind::StockPhrase)
+        );
+        assert_eq!(
+            classify("Untertitel der Amara.org-Community"),
+            Some(HallucinationKind::StockPhrase)
+        );
+    }
+
+    #[test]
+    fn stock_phrase_inside_longer_speech_not_flagged() {
+        assert_eq!(
+            classify("Wir haben gestern darüber gesprochen, dass Untertitel im Auftrag des ZDF oft falsch erkannt werden."),
+            None
+        );
+    }
+
+    #[test]
+    fn looping_single_word_and_phrase_flagged() {
+        assert_eq!(classify("ja ja ja ja ja ja"), Some(HallucinationKind::Repetition));
+        assert_eq!(
+            classify("Das ist gut. Das ist gut. Das ist gut. Das ist gut. Das ist gut."),
+            Some(HallucinationKind::Repetition)
+        );
+    }
+
+    #[test]
+    fn short_natural_repetition_not_flagged() {
+        assert_eq!(classify("Ja, ja, ja, genau so war das."), None);
+    }
+
+    #[test]
+    fn silence_rule_needs_both_signals() {
+        let filter = default_hallucination_filter();
+        assert_eq!(
+            classify_segment("Tschüss.", -1.4, 0.9, &[], &filter),
+            Some(HallucinationKind::NoSpeech)
+        );
+        assert_eq!(classify_segment("Tschüss.", -0.2, 0.9, &[], &filter), None);
+        assert_eq!(classify_segment("Tschüss.", -1.4, 0.1, &[], &filter), None);
+    }
+
+    #[test]
+    fn very_low_logprob_flagged() {
+        assert_eq!(
+            classify_segment("Blub blob", -2.5, 0.5, &[], &default_hallucination_filter()),
+            Some(HallucinationKind::LowConfidence)
+        );
+    }
+
+    #[test]
+    fn low_logprob_speech_is_kept() {
+        assert_eq!(
+            classify_segment("Des hob i ned gwusst", -2.5, 0.05, &[], &default_hallucination_filter()),
+            None
+        );
+    }
+
+    #[test]
+    fn average_logprob_of_empty_tokens_is_neutral() {
+        assert_eq!(average_logprob(&[]), 0.0);
+        assert!((average_logprob(&[-1.0, -2.0]) + 1.5).abs() < 1e-6);
+    }
+}
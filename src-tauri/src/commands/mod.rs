@@ -2,6 +2,33 @@ pub mod episodes;
 pub mod transcription;
 pub mod diarization;
 pub mod topics;
+#[cfg(feature = "app")]
 pub mod birds;
+#[cfg(feature = "app")]
 pub mod assemblyai;
 pub mod search;
+pub mod export;
+pub mod editing;
+pub mod pipeline;
+pub mod hallucination;
+pub mod chunking;
+pub mod whisper_callbacks;
+#[cfg(feature = "app")]
+pub mod retranscribe;
+#[cfg(feature = "app")]
+pub mod comparison;
+#[cfg(feature = "app")]
+pub mod benchmark;
+pub mod voiceprint;
+#[cfg(feature = "app")]
+pub mod clustering;
+pub mod overlap;
+pub mod attribution;
+#[cfg(feature = "app")]
+pub mod propagation;
+#[cfg(feature = "app")]
+pub mod evaluation;
+pub mod diarization_models;
+pub mod diarizer;
+#[cfg(feature = "app")]
+pub mod turn_taking;
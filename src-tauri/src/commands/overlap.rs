@@ -0,0 +1,344 @@
+use crate::models::diarization::{EpisodeSpeakingTime, OverlapSegment, SpeakerTime};
+#[cfg(feature = "app")]
+use crate::paths::BinkyPaths;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Overlapping speech
+//
+// sherpa-onnx's offline diarization C API (see diarizer.rs) returns only the
+// final segments, not pyannote's per-frame segmentation output. Overlap is
+// therefore taken from where segments of different speakers overlap in time;
+// overlapped speech that ended up inside one speaker's segment is missed.
+// The stretches are stored as overlap segments (migration 027), and speaking
+// time splits them evenly among the speakers talking at once instead of
+// crediting whichever segment happens to be summed. Overlaps shorter than
+// MIN_OVERLAP_MS count as neither, in both places.
+//
+// Only the stretches are stored; who talks in them is read from the current
+// (corrected) segment speakers, so relabels and corrections never leave a
+// stored overlap pointing at the wrong people.
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// (start_ms, end_ms, speaker) of one diarization segment.
+pub(crate) type Turn = (i64, i64, String);
+
+/// Overlaps shorter than this are boundary jitter between two turns, not
+/// people talking over each other.
+const MIN_OVERLAP_MS: i64 = 250;
+
+/// Cut the timeline at every segment boundary into (start, end, speakers
+/// talking), speakers sorted. Silence is left out.
+fn active_speakers(turns: &[Turn]) -> Vec<(i64, i64, Vec<&str>)> {
+    let mut events: Vec<(i64, i32, &str)> = Vec::with_capacity(turns.len() * 2);
+    for (start, end, speaker) in turns.iter().filter(|(start, end, _)| end > start) {
+        events.push((*start, 1, speaker));
+        events.push((*end, -1, speaker));
+    }
+    events.sort_by_key(|(at, _, _)| *at);
+
+    let mut active: BTreeMap<&str, i32> = BTreeMap::new();
+    let mut stretches = Vec::new();
+    let mut previous: Option<i64> = None;
+    for (at, delta, speaker) in events {
+        if let Some(from) = previous.filter(|from| at > *from) {
+            let speakers: Vec<&str> = active.iter().filter(|(_, n)| **n > 0).map(|(s, _)| *s).collect();
+            if !speakers.is_empty() {
+                stretches.push((from, at, speakers));
+            }
+        }
+        *active.entry(speaker).or_insert(0) += delta;
+        previous = Some(at);
+    }
+    stretches
+}
+
+/// active_speakers with boundary jitter resolved: in a run of multi-speaker
+/// stretches shorter than MIN_OVERLAP_MS, only the speaker who already talked
+/// before the run keeps the time (the first one when nobody did).
+fn credited_speakers(turns: &[Turn]) -> Vec<(i64, i64, Vec<&str>)> {
+    let mut stretches = active_speakers(turns);
+    let mut i = 0;
+    while i < stretches.len() {
+        if stretches[i].2.len() < 2 {
+            i += 1;
+            continue;
+        }
+        let mut j = i;
+        while j + 1 < stretches.len() && stretches[j + 1].2.len() > 1 && stretches[j + 1].0 == stretches[j].1 {
+            j += 1;
+        }
+        if stretches[j].1 - stretches[i].0 < MIN_OVERLAP_MS {
+            let before: Vec<&str> = match i.checked_sub(1).map(|p| &stretches[p]) {
+                Some((_, end, speakers)) if *end == stretches[i].0 => speakers.clone(),
+                _ => Vec::new(),
+            };
+            for stretch in &mut stretches[i..=j] {
+                let kept: Vec<&str> = stretch.2.iter().copied().filter(|s| before.contains(s)).collect();
+                if kept.is_empty() {
+                    stretch.2.truncate(1);
+                } else {
+                    stretch.2 = kept;
+                }
+            }
+        }
+        i = j + 1;
+    }
+    stretches
+}
+
+/// Stretches of at least MIN_OVERLAP_MS in which two or more speakers talk.
+pub(crate) fn overlap_regions(turns: &[Turn]) -> Vec<OverlapSegment> {
+    let mut regions: Vec<OverlapSegment> = Vec::new();
+    for (start, end, speakers) in active_speakers(turns).into_iter().filter(|(_, _, s)| s.len() > 1) {
+        match regions.last_mut() {
+            Some(last) if last.end_ms == start => {
+                last.end_ms = end;
+                for speaker in speakers {
+                    if !last.speakers.iter().any(|s| s == speaker) {
+                        last.speakers.push(speaker.to_string());
+                    }
+                }
+                last.speakers.sort();
+            }
+            _ => regions.push(OverlapSegment {
+                start_ms: start,
+                end_ms: end,
+                speakers: speakers.into_iter().map(str::to_string).collect(),
+            }),
+        }
+    }
+    regions.retain(|r| r.end_ms - r.start_ms >= MIN_OVERLAP_MS);
+    regions
+}
+
+/// Speaking time per speaker, most first. Time in which several speakers
+/// talk is split evenly among them, so the shares add up to the speech time.
+/// Boundary jitter is credited like in credited_speakers, not split.
+pub(crate) fn speaking_time(episode_id: i64, turns: &[Turn]) -> EpisodeSpeakingTime {
+    let mut per_speaker: HashMap<&str, f64> = HashMap::new();
+    let mut speech_ms = 0;
+    for (start, end, speakers) in credited_speakers(turns) {
+        speech_ms += end - start;
+        let share = (end - start) as f64 / speakers.len() as f64;
+        for speaker in speakers {
+            *per_speaker.entry(speaker).or_insert(0.0) += share;
+        }
+    }
+
+    let mut speakers: Vec<SpeakerTime> = per_speaker
+        .into_iter()
+        .map(|(speaker, ms)| SpeakerTime {
+            speaker: speaker.to_string(),
+            ms: ms.round() as i64,
+            percent: if speech_ms > 0 { ms * 100.0 / speech_ms as f64 } else { 0.0 },
+        })
+        .collect();
+    speakers.sort_by(|a, b| b.ms.cmp(&a.ms).then(a.speaker.cmp(&b.speaker)));
+
+    EpisodeSpeakingTime {
+        episode_id,
+        speech_ms,
+        overlap_ms: overlap_regions(turns).iter().map(|r| r.end_ms - r.start_ms).sum(),
+        speakers,
+    }
+}
+
+/// (episode id, start, end, effective speaker) of all segments, or of one episode.
+pub(crate) fn load_turns(
+    conn: &rusqlite::Connection,
+    episode_id: Option<i64>,
+) -> Result<Vec<(i64, Turn)>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT episode_id, start_ms, end_ms, COALESCE(corrected_speaker, speaker_label) \
+             FROM diarization_segments WHERE ?1 IS NULL OR episode_id = ?1 \
+             ORDER BY episode_id, start_ms",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params![episode_id], |row| {
+            Ok((row.get(0)?, (row.get(1)?, row.get(2)?, row.get(3)?)))
+        })
+        .map_err(|e| e.to_string())?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+/// Speaking time of one episode, or of every episode with diarization segments.
+pub(crate) fn episode_speaking_times(
+    conn: &rusqlite::Connection,
+    episode_id: Option<i64>,
+) -> Result<Vec<EpisodeSpeakingTime>, String> {
+    let mut by_episode: BTreeMap<i64, Vec<Turn>> = BTreeMap::new();
+    for (episode_id, turn) in load_turns(conn, episode_id)? {
+        by_episode.entry(episode_id).or_default().push(turn);
+    }
+    Ok(by_episode
+        .into_iter()
+        .map(|(episode_id, turns)| speaking_time(episode_id, &turns))
+        .collect())
+}
+
+/// Speakers talking in each stored overlap, taken from the current segment
+/// speakers. Overlaps left with a single speaker (both sides relabelled to the
+/// same person) are dropped.
+#[cfg(feature = "app")]
+pub(crate) fn with_current_speakers(regions: Vec<(i64, i64)>, turns: &[Turn]) -> Vec<OverlapSegment> {
+    regions
+        .into_iter()
+        .filter_map(|(start_ms, end_ms)| {
+            let mut speakers: Vec<String> = turns
+                .iter()
+                .filter(|(start, end, _)| *start < end_ms && *end > start_ms)
+                .map(|(_, _, speaker)| speaker.clone())
+                .collect();
+            speakers.sort();
+            speakers.dedup();
+            (speakers.len() > 1).then_some(OverlapSegment { start_ms, end_ms, speakers })
+        })
+        .collect()
+}
+
+/// Recompute the overlap segments of an episode from its diarization segments.
+/// Called after diarization and re-clustering. The stored speakers are only a
+/// snapshot; list_overlap_segments reads the current ones.
+pub(crate) fn refresh_overlap_segments(db_path: &Path, episode_id: i64) -> Result<(), String> {
+    let mut conn = rusqlite::Connection::open(db_path).map_err(|e| e.to_string())?;
+    let turns: Vec<Turn> = load_turns(&conn, Some(episode_id))?
+        .into_iter()
+        .map(|(_, turn)| turn)
+        .collect();
+
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+    tx.execute(
+        "DELETE FROM overlap_segments WHERE episode_id = ?1",
+        rusqlite::params![episode_id],
+    )
+    .map_err(|e| e.to_string())?;
+    for region in overlap_regions(&turns) {
+        tx.execute(
+            "INSERT INTO overlap_segments (episode_id, start_ms, end_ms, speakers) VALUES (?1, ?2, ?3, ?4)",
+            rusqlite::params![episode_id, region.start_ms, region.end_ms, region.speakers.join(",")],
+        )
+        .map_err(|e| format!("Failed to insert overlap segment: {}", e))?;
+    }
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Overlapping speech of an episode, in time order, with its current speakers.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn list_overlap_segments(
+    episode_id: i64,
+    app: tauri::AppHandle,
+) -> Result<Vec<OverlapSegment>, String> {
+    let paths = BinkyPaths::from_app(&app)?;
+    let conn = rusqlite::Connection::open(&paths.db_path).map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT start_ms, end_ms FROM overlap_segments \
+             WHERE episode_id = ?1 ORDER BY start_ms",
+        )
+        .map_err(|e| e.to_string())?;
+    let regions: Vec<(i64, i64)> = stmt
+        .query_map(rusqlite::params![episode_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+    let turns: Vec<Turn> = load_turns(&conn, Some(episode_id))?
+        .into_iter()
+        .map(|(_, turn)| turn)
+        .collect();
+    Ok(with_current_speakers(regions, &turns))
+}
+
+/// Speaking time and overlap time of one episode, or of every diarized
+/// episode when `episode_id` is None.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn get_speaking_time(
+    episode_id: Option<i64>,
+    app: tauri::AppHandle,
+) -> Result<Vec<EpisodeSpeakingTime>, String> {
+    let paths = BinkyPaths::from_app(&app)?;
+    let conn = rusqlite::Connection::open(&paths.db_path).map_err(|e| e.to_string())?;
+    episode_speaking_times(&conn, episode_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(start_ms: i64, end_ms: i64, speaker: &str) -> Turn {
+        (start_ms, end_ms, speaker.to_string())
+    }
+
+    #[test]
+    fn finds_stretches_where_speakers_talk_at_once() {
+        let turns = vec![
+            turn(0, 10_000, "SPEAKER_0"),
+            turn(8_000, 20_000, "SPEAKER_1"),
+            turn(15_000, 16_000, "SPEAKER_2"),
+            // Boundary jitter, too short to count
+            turn(19_900, 30_000, "SPEAKER_0"),
+        ];
+        assert_eq!(
+            overlap_regions(&turns),
+            vec![
+                OverlapSegment {
+                    start_ms: 8_000,
+                    end_ms: 10_000,
+                    speakers: vec!["SPEAKER_0".to_string(), "SPEAKER_1".to_string()],
+                },
+                OverlapSegment {
+                    start_ms: 15_000,
+                    end_ms: 16_000,
+                    speakers: vec!["SPEAKER_1".to_string(), "SPEAKER_2".to_string()],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_overlapping_time_evenly() {
+        let turns = vec![turn(0, 6_000, "SPEAKER_0"), turn(4_000, 8_000, "SPEAKER_1")];
+        let time = speaking_time(1, &turns);
+        assert_eq!(time.speech_ms, 8_000);
+        assert_eq!(time.overlap_ms, 2_000);
+        assert_eq!(time.speakers[0].ms, 5_000);
+        assert_eq!(time.speakers[1].ms, 3_000);
+        assert_eq!(time.speakers.iter().map(|s| s.percent).sum::<f64>(), 100.0);
+    }
+
+    #[test]
+    fn boundary_jitter_stays_with_the_speaker_already_talking() {
+        let turns = vec![turn(0, 10_100, "SPEAKER_0"), turn(10_000, 20_000, "SPEAKER_1")];
+        let time = speaking_time(1, &turns);
+        assert_eq!(time.overlap_ms, 0);
+        assert_eq!(time.speakers[0].speaker, "SPEAKER_0");
+        assert_eq!(time.speakers[0].ms, 10_100);
+        assert_eq!(time.speakers[1].ms, 9_900);
+    }
+
+    #[cfg(feature = "app")]
+    #[test]
+    fn overlap_speakers_follow_relabels() {
+        let regions = vec![(8_000, 10_000), (15_000, 16_000)];
+        let turns = vec![
+            turn(0, 10_000, "SPEAKER_0"),
+            turn(8_000, 20_000, "SPEAKER_2"),
+            // Relabelled to the speaker it overlapped with
+            turn(15_000, 16_000, "SPEAKER_2"),
+        ];
+        assert_eq!(
+            with_current_speakers(regions, &turns),
+            vec![OverlapSegment {
+                start_ms: 8_000,
+                end_ms: 10_000,
+                speakers: vec!["SPEAKER_0".to_string(), "SPEAKER_2".to_string()],
+            }]
+        );
+    }
+}
@@ -0,0 +1,1048 @@
+use crate::models::diarization::DiarizationEvent;
+use crate::models::pipeline::{CpuBudget, Stage, StageInfo, StageStatus};
+#[cfg(feature = "app")]
+use crate::models::pipeline::{CpuBudgetInfo, JobQueueSnapshot, QueuedPipeline};
+use crate::models::transcript::TranscriptionEvent;
+use crate::paths::BinkyPaths;
+use crate::progress::Progress;
+use crate::state::job_engine::{EngineState, PipelineJob, StageResult};
+use futures_util::StreamExt;
+#[cfg(feature = "app")]
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+#[cfg(feature = "app")]
+use std::path::PathBuf;
+use std::sync::Arc;
+#[cfg(feature = "app")]
+use tauri::Manager;
+use tokio::io::AsyncWriteExt;
+use tokio_util::sync::CancellationToken;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Job engine
+//
+// One queue of per-episode pipelines (see state::job_engine). Up to
+// `cpu_worker_slots` worker loops run one stage each; stage statuses are mirrored into job_stages
+// and the legacy episode status columns (transcription_status,
+// diarization_status) so existing UI keeps working (migration 020).
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Stages queued by start_diarization.
+pub(crate) const DIARIZATION_STAGES: &[Stage] =
+    &[Stage::Download, Stage::Diarize, Stage::BackfillText, Stage::Index];
+
+/// Stages queued by start_transcription. Topic analysis is opt-in via the
+/// `pipeline_analyze_topics` setting because it calls the OpenAI API.
+pub(crate) fn transcription_stages(db_path: &Path) -> Vec<Stage> {
+    let mut stages = vec![
+        Stage::Download,
+        Stage::Transcribe,
+        Stage::Diarize,
+        Stage::BackfillText,
+    ];
+    if read_setting(db_path, "pipeline_analyze_topics").as_deref() == Some("true") {
+        stages.push(Stage::AnalyzeTopics);
+    }
+    stages.push(Stage::Index);
+    stages
+}
+
+fn read_setting(db_path: &Path, key: &str) -> Option<String> {
+    crate::commands::transcription::read_setting(db_path, key)
+}
+
+/// Stages that read the downloaded audio file.
+fn needs_audio(stage: Stage) -> bool {
+    matches!(stage, Stage::Transcribe | Stage::Diarize)
+}
+
+#[cfg(feature = "app")]
+fn db_path(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    Ok(BinkyPaths::from_app(app)?.db_path)
+}
+
+/// Stored audio URL of an episode.
+pub(crate) fn episode_audio_url(db_path: &Path, episode_id: i64) -> Result<String, String> {
+    let conn = rusqlite::Connection::open(db_path).map_err(|e| e.to_string())?;
+    conn.query_row(
+        "SELECT audio_url FROM episodes WHERE id = ?1",
+        rusqlite::params![episode_id],
+        |row| row.get::<_, Option<String>>(0),
+    )
+    .ok()
+    .flatten()
+    .ok_or_else(|| "Episode hat keine Audio-URL".to_string())
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Persistence (pipeline_jobs + job_stages)
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn persist_job(db_path: &Path, job: &PipelineJob) {
+    if let Ok(conn) = rusqlite::Connection::open(db_path) {
+        let _ = conn.execute(
+            "INSERT INTO pipeline_jobs (episode_id, audio_url, priority) VALUES (?1, ?2, ?3) \
+             ON CONFLICT(episode_id) DO UPDATE SET audio_url = excluded.audio_url, priority = excluded.priority",
+            rusqlite::params![job.episode_id, job.audio_url, job.priority],
+        );
+    }
+    save_stages(db_path, job.episode_id, &job.stages);
+}
+
+fn save_stages(db_path: &Path, episode_id: i64, stages: &[StageInfo]) {
+    if let Ok(mut conn) = rusqlite::Connection::open(db_path) {
+        if let Ok(tx) = conn.transaction() {
+            for info in stages {
+                let _ = tx.execute(
+                    "INSERT INTO job_stages (episode_id, stage, status, attempts, error, updated_at) \
+                     VALUES (?1, ?2, ?3, ?4, ?5, datetime('now')) \
+                     ON CONFLICT(episode_id, stage) DO UPDATE SET status = excluded.status, \
+                     attempts = excluded.attempts, error = excluded.error, updated_at = excluded.updated_at",
+                    rusqlite::params![
+                        episode_id,
+                        info.stage.as_str(),
+                        info.status.as_str(),
+                        info.attempts,
+                        info.error
+                    ],
+                );
+            }
+            let _ = tx.commit();
+        }
+    }
+}
+
+fn delete_job_row(db_path: &Path, episode_id: i64) {
+    if let Ok(conn) = rusqlite::Connection::open(db_path) {
+        let _ = conn.execute(
+            "DELETE FROM pipeline_jobs WHERE episode_id = ?1",
+            rusqlite::params![episode_id],
+        );
+    }
+}
+
+/// Rewrite position and priority of all jobs after the queue order changed.
+fn sync_queue_order(db_path: &Path, order: &[(i64, i32)]) {
+    if let Ok(mut conn) = rusqlite::Connection::open(db_path) {
+        if let Ok(tx) = conn.transaction() {
+            for (position, (episode_id, priority)) in order.iter().enumerate() {
+                let _ = tx.execute(
+                    "UPDATE pipeline_jobs SET position = ?1, priority = ?2 WHERE episode_id = ?3",
+                    rusqlite::params![position as i64, priority, episode_id],
+                );
+            }
+            let _ = tx.commit();
+        }
+    }
+}
+
+#[cfg(feature = "app")]
+fn load_stage_rows(conn: &rusqlite::Connection, episode_id: i64) -> Vec<StageInfo> {
+    let mut stmt = match conn.prepare(
+        "SELECT stage, status, attempts, error FROM job_stages WHERE episode_id = ?1",
+    ) {
+        Ok(s) => s,
+        Err(_) => return vec![],
+    };
+    let rows: Vec<(String, String, u32, Option<String>)> = match stmt
+        .query_map(rusqlite::params![episode_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        }) {
+        Ok(rows) => rows.filter_map(|r| r.ok()).collect(),
+        Err(_) => vec![],
+    };
+    let mut stages: Vec<StageInfo> = rows
+        .into_iter()
+        .filter_map(|(stage, status, attempts, error)| {
+            Some(StageInfo {
+                stage: Stage::parse(&stage)?,
+                status: StageStatus::parse(&status)?,
+                attempts,
+                error,
+            })
+        })
+        .collect();
+    stages.sort_by_key(|s| s.stage);
+    stages
+}
+
+/// Pipelines left over from the previous session, in queue order.
+#[cfg(feature = "app")]
+fn load_persisted_jobs(db_path: &Path) -> Vec<PipelineJob> {
+    let conn = match rusqlite::Connection::open(db_path) {
+        Ok(c) => c,
+        Err(_) => return vec![],
+    };
+    let rows: Vec<(i64, String, i32)> = {
+        let mut stmt = match conn.prepare(
+            "SELECT episode_id, audio_url, priority FROM pipeline_jobs \
+             ORDER BY position IS NULL, position, id",
+        ) {
+            Ok(s) => s,
+            Err(_) => return vec![],
+        };
+        let collected = match stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?))) {
+            Ok(rows) => rows.filter_map(|r| r.ok()).collect(),
+            Err(_) => vec![],
+        };
+        collected
+    };
+
+    rows.into_iter()
+        .map(|(episode_id, audio_url, priority)| {
+            let mut job = PipelineJob::new(episode_id, audio_url, &[]);
+            job.priority = priority;
+            job.stages = load_stage_rows(&conn, episode_id);
+            job
+        })
+        .collect()
+}
+
+#[cfg(feature = "app")]
+fn read_paused(db_path: &Path) -> bool {
+    read_setting(db_path, "queue_paused").as_deref() == Some("true")
+}
+
+/// Cores available to this process (at least 1).
+pub(crate) fn available_cores() -> u32 {
+    std::thread::available_parallelism()
+        .map(|n| n.get() as u32)
+        .unwrap_or(1)
+}
+
+/// CPU budget from settings; missing or invalid values fall back to defaults.
+pub(crate) fn read_cpu_budget(db_path: &Path) -> CpuBudget {
+    let defaults = CpuBudget::default_for(available_cores());
+    let value = |key: &str, default: u32| -> u32 {
+        read_setting(db_path, key)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default)
+    };
+    CpuBudget {
+        worker_slots: value("cpu_worker_slots", defaults.worker_slots).max(1),
+        whisper_threads: value("cpu_whisper_threads", defaults.whisper_threads),
+        sherpa_threads: value("cpu_sherpa_threads", defaults.sherpa_threads).max(1),
+    }
+}
+
+#[cfg(feature = "app")]
+fn write_paused(db_path: &Path, paused: bool) {
+    if let Ok(conn) = rusqlite::Connection::open(db_path) {
+        let _ = conn.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES ('queue_paused', ?1)",
+            rusqlite::params![if paused { "true" } else { "false" }],
+        );
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Stage status → episode columns + frontend events
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// What the status/event mirroring needs to know about a job.
+#[derive(Clone)]
+struct JobView {
+    episode_id: i64,
+    transcribe_pending: bool,
+    transcription_events: Option<Progress<TranscriptionEvent>>,
+    diarization_events: Option<Progress<DiarizationEvent>>,
+}
+
+impl JobView {
+    fn of(job: &PipelineJob) -> Self {
+        Self {
+            episode_id: job.episode_id,
+            transcribe_pending: job.has_pending(Stage::Transcribe),
+            transcription_events: job.transcription_events.clone(),
+            diarization_events: job.diarization_events.clone(),
+        }
+    }
+}
+
+/// Status for an episode whose stage did not run: keep 'done' if earlier
+/// results exist, otherwise back to 'not_started'.
+fn settle_status(db_path: &Path, episode_id: i64, stage: Stage) {
+    let conn = match rusqlite::Connection::open(db_path) {
+        Ok(c) => c,
+        Err(_) => return,
+    };
+    let has_result = |sql: &str| -> bool {
+        conn.query_row(sql, rusqlite::params![episode_id], |_| Ok(()))
+            .is_ok()
+    };
+    if stage == Stage::Transcribe {
+        let status = if has_result("SELECT 1 FROM transcripts WHERE episode_id = ?1") {
+            "done"
+        } else {
+            "not_started"
+        };
+        crate::commands::transcription::update_episode_status(db_path, episode_id, status, None);
+    } else {
+        let status = if has_result(
+            "SELECT 1 FROM diarization_segments WHERE episode_id = ?1 LIMIT 1",
+        ) {
+            "done"
+        } else {
+            "not_started"
+        };
+        crate::commands::diarization::update_diarization_status(db_path, episode_id, status, None);
+    }
+}
+
+fn reflect_episode_status(db_path: &Path, view: &JobView, info: &StageInfo) {
+    use crate::commands::diarization::update_diarization_status as set_diarization;
+    use crate::commands::transcription::update_episode_status as set_transcription;
+
+    let id = view.episode_id;
+    let error = info.error.as_deref();
+    match (info.stage, info.status) {
+        (Stage::Download, StageStatus::Running) if view.transcribe_pending => {
+            set_transcription(db_path, id, "downloading", None)
+        }
+        (Stage::Transcribe, StageStatus::Pending) => set_transcription(db_path, id, "queued", None),
+        (Stage::Transcribe, StageStatus::Running) => {
+            set_transcription(db_path, id, "transcribing", None)
+        }
+        (Stage::Transcribe, StageStatus::Done) => set_transcription(db_path, id, "done", None),
+        (Stage::Transcribe, StageStatus::Failed | StageStatus::Blocked) => {
+            set_transcription(db_path, id, "error", error)
+        }
+        (Stage::Transcribe, StageStatus::Cancelled) => settle_status(db_path, id, Stage::Transcribe),
+        (Stage::Diarize, StageStatus::Pending) => set_diarization(db_path, id, "queued", None),
+        (Stage::Diarize, StageStatus::Running) => set_diarization(db_path, id, "processing", None),
+        // Done: the diarize stage itself stores 'done' or 'solo'
+        (Stage::Diarize, StageStatus::Failed | StageStatus::Blocked) => {
+            set_diarization(db_path, id, "error", error)
+        }
+        (Stage::Diarize, StageStatus::Cancelled | StageStatus::Skipped) => {
+            settle_status(db_path, id, Stage::Diarize)
+        }
+        _ => {}
+    }
+}
+
+fn notify_frontend(view: &JobView, info: &StageInfo) {
+    let message = info.error.clone().unwrap_or_default();
+    match info.stage {
+        Stage::Transcribe => {
+            if let Some(ch) = &view.transcription_events {
+                let event = match info.status {
+                    StageStatus::Done => TranscriptionEvent::Done {
+                        episode_id: view.episode_id,
+                    },
+                    StageStatus::Failed | StageStatus::Blocked => TranscriptionEvent::Error { message },
+                    StageStatus::Cancelled => TranscriptionEvent::Cancelled,
+                    _ => return,
+                };
+                ch.send(event);
+            }
+        }
+        Stage::Diarize => {
+            if let Some(ch) = &view.diarization_events {
+                let event = match info.status {
+                    StageStatus::Done => DiarizationEvent::Done {
+                        episode_id: view.episode_id,
+                    },
+                    StageStatus::Failed | StageStatus::Blocked | StageStatus::Skipped => {
+                        DiarizationEvent::Error { message }
+                    }
+                    StageStatus::Cancelled => DiarizationEvent::Cancelled,
+                    _ => return,
+                };
+                ch.send(event);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn record_changes(db_path: &Path, view: &JobView, changed: &[StageInfo]) {
+    if changed.is_empty() {
+        return;
+    }
+    save_stages(db_path, view.episode_id, changed);
+    for info in changed {
+        reflect_episode_status(db_path, view, info);
+        notify_frontend(view, info);
+    }
+}
+
+/// Drop finished pipelines from the DB and delete their cached audio.
+async fn finalize_jobs(paths: &BinkyPaths, finished: Vec<PipelineJob>, order: &[(i64, i32)]) {
+    for job in &finished {
+        delete_job_row(&paths.db_path, job.episode_id);
+        let _ = tokio::fs::remove_file(paths.audio_cache_path(job.episode_id)).await;
+    }
+    sync_queue_order(&paths.db_path, order);
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Stage execution
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Download stage: stream the episode audio into the cache (progress 0–50%).
+async fn run_download_stage(
+    audio_url: &str,
+    audio_path: &Path,
+    cancel_token: &CancellationToken,
+    view: &JobView,
+) -> StageResult {
+    download_audio(audio_url, audio_path, cancel_token, |percent| {
+        if let Some(ch) = &view.transcription_events {
+            ch.send(TranscriptionEvent::Downloading { percent });
+        }
+        if let Some(ch) = &view.diarization_events {
+            ch.send(DiarizationEvent::Progress { percent });
+        }
+    })
+    .await
+}
+
+/// Stream episode audio to `audio_path` via a `.part` file, reporting progress
+/// as 0–50%. An existing file is complete (written via rename) and reused.
+pub(crate) async fn download_audio(
+    audio_url: &str,
+    audio_path: &Path,
+    cancel_token: &CancellationToken,
+    on_percent: impl Fn(i32),
+) -> StageResult {
+    if audio_path.exists() {
+        return StageResult::Done;
+    }
+    if let Some(dir) = audio_path.parent() {
+        if let Err(e) = tokio::fs::create_dir_all(dir).await {
+            return StageResult::Failed(format!("Cannot create cache dir: {}", e));
+        }
+    }
+
+    let part_path = audio_path.with_extension("mp3.part");
+    let response = match reqwest::get(audio_url).await {
+        Ok(r) if r.status().is_success() => r,
+        Ok(r) => return StageResult::Failed(format!("Audio download failed: HTTP {}", r.status())),
+        Err(e) => return StageResult::Failed(format!("Audio download failed: {}", e)),
+    };
+
+    let total_bytes = response.content_length().unwrap_or(0);
+    let mut stream = response.bytes_stream();
+    let mut downloaded_bytes: u64 = 0;
+
+    let mut file = match tokio::fs::File::create(&part_path).await {
+        Ok(f) => f,
+        Err(e) => return StageResult::Failed(format!("Cannot create temp audio file: {}", e)),
+    };
+
+    while let Some(chunk_result) = stream.next().await {
+        // Check for cancellation between chunks
+        if cancel_token.is_cancelled() {
+            drop(file);
+            let _ = tokio::fs::remove_file(&part_path).await;
+            return StageResult::Cancelled;
+        }
+
+        let chunk = match chunk_result {
+            Ok(c) => c,
+            Err(e) => {
+                drop(file);
+                let _ = tokio::fs::remove_file(&part_path).await;
+                return StageResult::Failed(format!("Download stream error: {}", e));
+            }
+        };
+
+        if let Err(e) = file.write_all(&chunk).await {
+            drop(file);
+            let _ = tokio::fs::remove_file(&part_path).await;
+            return StageResult::Failed(format!("Failed to write audio chunk: {}", e));
+        }
+
+        downloaded_bytes += chunk.len() as u64;
+        // Map download progress to 0-50 range (unknown total: show midpoint)
+        let percent = (downloaded_bytes * 50)
+            .checked_div(total_bytes)
+            .map_or(25, |p| p as i32);
+        on_percent(percent);
+    }
+
+    if let Err(e) = file.flush().await {
+        drop(file);
+        let _ = tokio::fs::remove_file(&part_path).await;
+        return StageResult::Failed(format!("Failed to flush audio file: {}", e));
+    }
+    drop(file);
+
+    match tokio::fs::rename(&part_path, audio_path).await {
+        Ok(()) => StageResult::Done,
+        Err(e) => StageResult::Failed(format!("Failed to move audio file: {}", e)),
+    }
+}
+
+/// Decode the cached episode audio to 16 kHz mono PCM, or download it to a
+/// private temp file first. The shared cache file is only ever read here: a
+/// pipeline's Download stage may be writing or renaming it at the same time.
+#[cfg(feature = "app")]
+pub(crate) async fn decode_episode_audio(
+    paths: &BinkyPaths,
+    episode_id: i64,
+    audio_url: &str,
+    on_percent: impl Fn(i32),
+) -> Result<Vec<f32>, String> {
+    let cached = paths.audio_cache_path(episode_id);
+    if let Ok(samples) = crate::commands::transcription::decode_mp3_to_pcm(&cached) {
+        return Ok(samples);
+    }
+
+    let temp_path = std::env::temp_dir().join(format!(
+        "binky-{}-{}-{}.mp3",
+        episode_id,
+        std::process::id(),
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos())
+    ));
+    let download = download_audio(audio_url, &temp_path, &CancellationToken::new(), on_percent).await;
+    if let StageResult::Failed(e) = download {
+        return Err(e);
+    }
+    let decoded = crate::commands::transcription::decode_mp3_to_pcm(&temp_path);
+    let _ = tokio::fs::remove_file(&temp_path).await;
+    decoded.map_err(|e| format!("Audio decode failed: {}", e))
+}
+
+async fn run_stage(
+    paths: &BinkyPaths,
+    stage: Stage,
+    audio_url: &str,
+    cancel_token: &CancellationToken,
+    view: &JobView,
+) -> StageResult {
+    let db_path = paths.db_path.as_path();
+    let episode_id = view.episode_id;
+    let audio_path = paths.audio_cache_path(episode_id);
+    if needs_audio(stage) && !audio_path.exists() {
+        return StageResult::Failed("Audiodatei fehlt im Cache".to_string());
+    }
+
+    match stage {
+        Stage::Download => run_download_stage(audio_url, &audio_path, cancel_token, view).await,
+        Stage::Transcribe => {
+            crate::commands::transcription::run_transcription_stage(
+                paths,
+                episode_id,
+                &audio_path,
+                cancel_token,
+                view.transcription_events.as_ref(),
+            )
+            .await
+        }
+        Stage::Diarize => {
+            crate::commands::diarization::run_diarization_stage(
+                paths,
+                episode_id,
+                &audio_path,
+                cancel_token,
+                view.diarization_events.as_ref(),
+            )
+            .await
+        }
+        Stage::BackfillText => {
+            // No-op for episodes without Whisper segments or diarization segments
+            crate::commands::diarization::backfill_segment_text_from_whisper(db_path, episode_id);
+            StageResult::Done
+        }
+        Stage::AnalyzeTopics => {
+            if read_setting(db_path, "openai_api_key").unwrap_or_default().is_empty() {
+                return StageResult::Skipped("Kein OpenAI API-Schlüssel konfiguriert".to_string());
+            }
+            match crate::commands::topics::analyze_topics_for_episode(db_path, episode_id).await {
+                Ok(_) => StageResult::Done,
+                Err(e) => StageResult::Failed(e),
+            }
+        }
+        Stage::Index => match crate::commands::search::reindex_episode(db_path, episode_id) {
+            Ok(()) => StageResult::Done,
+            Err(e) => StageResult::Failed(e),
+        },
+    }
+}
+
+/// Run a future on the app's async runtime, or on the CLI's tokio runtime.
+fn spawn(future: impl Future<Output = ()> + Send + 'static) {
+    #[cfg(feature = "app")]
+    tauri::async_runtime::spawn(future);
+    #[cfg(not(feature = "app"))]
+    tokio::spawn(future);
+}
+
+/// Start worker loops for runnable jobs, up to the CPU budget's worker slots.
+pub(crate) fn spawn_workers(paths: &BinkyPaths, state: &Arc<EngineState>) {
+    let wanted = {
+        let mut engine = state.engine.lock().unwrap();
+        let wanted = engine.workers_wanted();
+        engine.workers += wanted;
+        wanted
+    };
+    for _ in 0..wanted {
+        let paths = paths.clone();
+        let state = state.clone();
+        spawn(async move {
+            worker_loop(paths, state).await;
+        });
+    }
+}
+
+async fn worker_loop(paths: BinkyPaths, state: Arc<EngineState>) {
+    let db_path = paths.db_path.clone();
+
+    loop {
+        // Pick the next stage and — if there is none, the engine is paused or
+        // the slot budget shrank — retire this worker under the same lock, so
+        // a concurrent enqueue sees the freed slot and spawns a new worker.
+        let next = {
+            let mut engine = state.engine.lock().unwrap();
+            let next = if engine.paused || engine.workers > engine.worker_slots {
+                None
+            } else {
+                engine.start_next_stage()
+            };
+            match next {
+                Some((episode_id, stage)) => {
+                    let job = engine.job(episode_id).expect("job of started stage");
+                    let running = job.stage(stage).cloned().expect("started stage");
+                    Some((
+                        stage,
+                        job.audio_url.clone(),
+                        job.cancel_token.clone(),
+                        JobView::of(job),
+                        running,
+                    ))
+                }
+                None => {
+                    engine.workers -= 1;
+                    None
+                }
+            }
+        };
+
+        let (stage, audio_url, cancel_token, view, running) = match next {
+            Some(n) => n,
+            None => break,
+        };
+
+        record_changes(&db_path, &view, &[running]);
+
+        let result = run_stage(&paths, stage, &audio_url, &cancel_token, &view).await;
+        let result = if cancel_token.is_cancelled() {
+            StageResult::Cancelled
+        } else {
+            result
+        };
+
+        let (changed, finished, order) = {
+            let mut engine = state.engine.lock().unwrap();
+            let changed = engine.finish_stage(view.episode_id, stage, result);
+            (changed, engine.take_finished(), engine.order())
+        };
+        record_changes(&db_path, &view, &changed);
+        if !finished.is_empty() {
+            finalize_jobs(&paths, finished, &order).await;
+        }
+        spawn_workers(&paths, &state);
+    }
+}
+
+/// Queue a pipeline (or merge stages into an already queued one), persist it
+/// and make sure workers run. Refused while a range re-transcription of the
+/// episode runs; the check and the enqueue happen under the engine lock, the
+/// same lock retranscribe_range holds while registering its run.
+pub(crate) fn enqueue_job(paths: &BinkyPaths, state: &Arc<EngineState>, mut job: PipelineJob) -> Result<(), String> {
+    // Audio-reading stages always need the download stage in the same job
+    if job.stages.iter().any(|s| needs_audio(s.stage)) {
+        job.request(&[Stage::Download]);
+    }
+    let episode_id = job.episode_id;
+
+    let (view, stages, audio_url, priority, order) = {
+        let mut engine = state.engine.lock().unwrap();
+        if state.range_runs.lock().unwrap().contains_key(&episode_id) {
+            return Err("Für diese Episode läuft gerade eine Neutranskription".to_string());
+        }
+        engine.enqueue(job);
+        let queued = engine.job(episode_id).expect("job just enqueued");
+        (
+            JobView::of(queued),
+            queued.stages.clone(),
+            queued.audio_url.clone(),
+            queued.priority,
+            engine.order(),
+        )
+    };
+
+    let db_path = paths.db_path.as_path();
+    let mut snapshot = PipelineJob::new(episode_id, audio_url, &[]);
+    snapshot.priority = priority;
+    snapshot.stages = stages.clone();
+    persist_job(db_path, &snapshot);
+    sync_queue_order(db_path, &order);
+    for info in stages.iter().filter(|s| s.status == StageStatus::Pending) {
+        reflect_episode_status(db_path, &view, info);
+    }
+
+    spawn_workers(paths, state);
+    Ok(())
+}
+
+/// Cancel an episode's pipeline: the running stage stops at its next
+/// cancellation check, pending stages are cancelled right away.
+#[cfg(feature = "app")]
+pub(crate) fn cancel_job(paths: &BinkyPaths, state: &Arc<EngineState>, episode_id: i64) {
+    let (view, changed, finished, order) = {
+        let mut engine = state.engine.lock().unwrap();
+        let view = match engine.job(episode_id) {
+            Some(job) => JobView::of(job),
+            None => return,
+        };
+        let changed = engine.cancel(episode_id);
+        (view, changed, engine.take_finished(), engine.order())
+    };
+
+    record_changes(&paths.db_path, &view, &changed);
+    if !finished.is_empty() {
+        let paths = paths.clone();
+        spawn(async move {
+            finalize_jobs(&paths, finished, &order).await;
+        });
+    }
+}
+
+/// Re-queue the pipelines of the previous session. Called once from the setup
+/// hook, after stale in-flight statuses have been reset.
+#[cfg(feature = "app")]
+pub(crate) async fn resume_persisted_jobs(app: tauri::AppHandle) {
+    let state = match app.try_state::<Arc<EngineState>>() {
+        Some(s) => s.inner().clone(),
+        None => return,
+    };
+    let paths = match BinkyPaths::from_app(&app) {
+        Ok(p) => p,
+        Err(_) => return,
+    };
+    let db_path = paths.db_path.as_path();
+
+    {
+        let mut engine = state.engine.lock().unwrap();
+        engine.paused = read_paused(db_path);
+        engine.worker_slots = read_cpu_budget(db_path).worker_slots as usize;
+    }
+
+    let mut resumed = false;
+    for mut job in load_persisted_jobs(db_path) {
+        let audio_cached = paths.audio_cache_path(job.episode_id).exists();
+        for info in job.stages.iter_mut() {
+            // A stage that was running when the app quit starts over; a finished
+            // download whose cache file vanished is repeated.
+            let redo_download = info.stage == Stage::Download
+                && info.status == StageStatus::Done
+                && !audio_cached;
+            if info.status == StageStatus::Running || redo_download {
+                info.status = StageStatus::Pending;
+            }
+        }
+
+        if job.is_finished() {
+            delete_job_row(db_path, job.episode_id);
+            continue;
+        }
+
+        let view = JobView::of(&job);
+        save_stages(db_path, job.episode_id, &job.stages);
+        for info in job.stages.iter().filter(|s| s.status == StageStatus::Pending) {
+            reflect_episode_status(db_path, &view, info);
+        }
+        // Persisted order already reflects priorities and manual moves — keep it.
+        state.engine.lock().unwrap().jobs.push_back(job);
+        resumed = true;
+    }
+
+    if resumed {
+        spawn_workers(&paths, &state);
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Commands
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Queue a pipeline for an episode. Without `stages` the transcription
+/// pipeline is used; without `audio_url` the episode's stored URL.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn start_pipeline(
+    episode_id: i64,
+    audio_url: Option<String>,
+    stages: Option<Vec<Stage>>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<EngineState>>,
+) -> Result<(), String> {
+    let paths = BinkyPaths::from_app(&app)?;
+    let audio_url = match audio_url {
+        Some(url) => url,
+        None => episode_audio_url(&paths.db_path, episode_id)?,
+    };
+    let stages = stages.unwrap_or_else(|| transcription_stages(&paths.db_path));
+    if stages.is_empty() {
+        return Err("Keine Verarbeitungsstufen angegeben".to_string());
+    }
+
+    enqueue_job(&paths, state.inner(), PipelineJob::new(episode_id, audio_url, &stages))
+}
+
+/// All queued pipelines with per-stage status, plus pause state.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn get_job_queue(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<EngineState>>,
+) -> Result<JobQueueSnapshot, String> {
+    let (jobs, is_processing, paused) = {
+        let engine = state.engine.lock().unwrap();
+        let jobs: Vec<(i64, i32, Vec<StageInfo>)> = engine
+            .jobs
+            .iter()
+            .map(|j| (j.episode_id, j.priority, j.stages.clone()))
+            .collect();
+        (jobs, engine.is_processing(), engine.paused)
+    };
+
+    let mut titles: HashMap<i64, String> = HashMap::new();
+    if let Ok(conn) = rusqlite::Connection::open(db_path(&app)?) {
+        for (episode_id, _, _) in &jobs {
+            if let Ok(title) = conn.query_row(
+                "SELECT title FROM episodes WHERE id = ?1",
+                rusqlite::params![episode_id],
+                |row| row.get::<_, String>(0),
+            ) {
+                titles.insert(*episode_id, title);
+            }
+        }
+    }
+
+    Ok(JobQueueSnapshot {
+        is_processing,
+        paused,
+        jobs: jobs
+            .into_iter()
+            .enumerate()
+            .map(|(position, (episode_id, priority, stages))| QueuedPipeline {
+                episode_id,
+                title: titles.get(&episode_id).cloned(),
+                position,
+                priority,
+                stages,
+            })
+            .collect(),
+    })
+}
+
+/// Stage statuses of one episode — live if queued, otherwise the last run.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn get_pipeline_status(
+    episode_id: i64,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<EngineState>>,
+) -> Result<Vec<StageInfo>, String> {
+    if let Some(job) = state.engine.lock().unwrap().job(episode_id) {
+        return Ok(job.stages.clone());
+    }
+    let conn = rusqlite::Connection::open(db_path(&app)?).map_err(|e| e.to_string())?;
+    Ok(load_stage_rows(&conn, episode_id))
+}
+
+/// Move a queued pipeline to `position` (0 = next).
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn move_queued_job(
+    episode_id: i64,
+    position: usize,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<EngineState>>,
+) -> Result<(), String> {
+    let order = {
+        let mut engine = state.engine.lock().unwrap();
+        if !engine.move_to(episode_id, position) {
+            return Err(format!("Episode {} ist nicht in der Warteschlange", episode_id));
+        }
+        engine.order()
+    };
+    sync_queue_order(&db_path(&app)?, &order);
+    Ok(())
+}
+
+/// Set the priority of a queued pipeline; higher priorities run first.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn set_queued_job_priority(
+    episode_id: i64,
+    priority: i32,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<EngineState>>,
+) -> Result<(), String> {
+    let order = {
+        let mut engine = state.engine.lock().unwrap();
+        if !engine.set_priority(episode_id, priority) {
+            return Err(format!("Episode {} ist nicht in der Warteschlange", episode_id));
+        }
+        engine.order()
+    };
+    sync_queue_order(&db_path(&app)?, &order);
+    Ok(())
+}
+
+/// Remove a queued pipeline that is not running. Use cancel_pipeline for the
+/// running one.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn remove_queued_job(
+    episode_id: i64,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<EngineState>>,
+) -> Result<(), String> {
+    let (job, order) = {
+        let mut engine = state.engine.lock().unwrap();
+        let job = engine.remove(episode_id)?;
+        (job, engine.order())
+    };
+
+    let paths = BinkyPaths::from_app(&app)?;
+    let view = JobView::of(&job);
+    let cancelled: Vec<StageInfo> = job
+        .stages
+        .iter()
+        .filter(|s| s.status == StageStatus::Pending)
+        .map(|s| StageInfo {
+            status: StageStatus::Cancelled,
+            ..s.clone()
+        })
+        .collect();
+    record_changes(&paths.db_path, &view, &cancelled);
+    finalize_jobs(&paths, vec![job], &order).await;
+    Ok(())
+}
+
+/// Cancel an episode's pipeline, including its running stage.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn cancel_pipeline(
+    episode_id: i64,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<EngineState>>,
+) -> Result<(), String> {
+    cancel_job(&BinkyPaths::from_app(&app)?, state.inner(), episode_id);
+    Ok(())
+}
+
+/// Retry a failed, blocked or cancelled stage and everything after it. Works
+/// for queued pipelines and for finished ones (re-queued from job_stages).
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn retry_pipeline_stage(
+    episode_id: i64,
+    stage: Stage,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<EngineState>>,
+) -> Result<(), String> {
+    let paths = BinkyPaths::from_app(&app)?;
+
+    let queued = {
+        let mut engine = state.engine.lock().unwrap();
+        engine
+            .job(episode_id)
+            .map(JobView::of)
+            .map(|view| (view, engine.retry(episode_id, stage)))
+    };
+    if let Some((view, changed)) = queued {
+        record_changes(&paths.db_path, &view, &changed);
+        spawn_workers(&paths, state.inner());
+        return Ok(());
+    }
+
+    // Not queued any more: re-queue the stage plus the previously requested
+    // stages that depend on it.
+    let audio_url = episode_audio_url(&paths.db_path, episode_id)?;
+    let conn = rusqlite::Connection::open(&paths.db_path).map_err(|e| e.to_string())?;
+    let mut stages: Vec<Stage> = load_stage_rows(&conn, episode_id)
+        .into_iter()
+        .map(|s| s.stage)
+        .filter(|s| crate::state::job_engine::depends_on(*s, stage))
+        .collect();
+    stages.push(stage);
+
+    enqueue_job(&paths, state.inner(), PipelineJob::new(episode_id, audio_url, &stages))
+}
+
+/// Pause the workers after their running stages. Persists across restarts.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn pause_job_queue(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<EngineState>>,
+) -> Result<(), String> {
+    state.engine.lock().unwrap().paused = true;
+    write_paused(&db_path(&app)?, true);
+    Ok(())
+}
+
+/// Resume a paused queue.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn resume_job_queue(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<EngineState>>,
+) -> Result<(), String> {
+    let paths = BinkyPaths::from_app(&app)?;
+    state.engine.lock().unwrap().paused = false;
+    write_paused(&paths.db_path, false);
+    spawn_workers(&paths, state.inner());
+    Ok(())
+}
+
+/// Current CPU budget and the number of cores it is checked against.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn get_cpu_budget(app: tauri::AppHandle) -> Result<CpuBudgetInfo, String> {
+    Ok(CpuBudgetInfo {
+        budget: read_cpu_budget(&db_path(&app)?),
+        available_cores: available_cores(),
+    })
+}
+
+/// Save a new CPU budget. More slots take effect immediately; with fewer
+/// slots, surplus workers stop after their running stage. Thread counts apply
+/// to stages started afterwards.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn set_cpu_budget(
+    budget: CpuBudget,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<EngineState>>,
+) -> Result<(), String> {
+    budget.validate(available_cores())?;
+
+    let paths = BinkyPaths::from_app(&app)?;
+    let conn = rusqlite::Connection::open(&paths.db_path).map_err(|e| e.to_string())?;
+    for (key, value) in [
+        ("cpu_worker_slots", budget.worker_slots),
+        ("cpu_whisper_threads", budget.whisper_threads),
+        ("cpu_sherpa_threads", budget.sherpa_threads),
+    ] {
+        conn.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?1, ?2)",
+            rusqlite::params![key, value.to_string()],
+        )
+        .map_err(|e| format!("CPU-Budget konnte nicht gespeichert werden: {}", e))?;
+    }
+
+    state.engine.lock().unwrap().worker_slots = budget.worker_slots as usize;
+    spawn_workers(&paths, state.inner());
+    Ok(())
+}
@@ -0,0 +1,223 @@
+use crate::commands::editing::relabel_segments;
+use crate::commands::voiceprint::{cosine_similarity, decode_embedding, mean_embedding, normalized};
+use crate::models::diarization::{PropagationSuggestion, SegmentSnapshot, SpeakerAssignment};
+use crate::paths::BinkyPaths;
+use std::collections::HashMap;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Propagating speaker corrections
+//
+// Segments relabelled by hand (corrected_speaker differs from the diarization
+// label) are examples: their stored embeddings (migration 026) average into
+// one centroid per corrected speaker. An untouched segment is suggested for the
+// relabel when it is at least as similar as the threshold to a corrected
+// centroid AND clearly closer to it than to the centroid of the other untouched
+// segments of its current speaker; the accepted ones are applied as one
+// revision, so a whole batch can be undone.
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Default minimum cosine similarity to a corrected speaker's centroid.
+/// Stricter than MIN_HOST_SIMILARITY: one episode offers few examples.
+const DEFAULT_PROPAGATION_SIMILARITY: f32 = 0.6;
+
+/// How much closer a segment must be to the corrected centroid than to its
+/// current speaker's, so voices between both stay as they are.
+const MIN_PROPAGATION_MARGIN: f32 = 0.05;
+
+/// A diarization segment with a stored embedding.
+pub(crate) struct EmbeddedSegment {
+    pub id: i64,
+    /// Effective speaker (corrected_speaker or speaker_label).
+    pub speaker: String,
+    /// Relabelled by hand to another speaker than diarization found.
+    pub relabelled: bool,
+    pub embedding: Vec<f32>,
+}
+
+/// (segment id, suggested speaker, similarity) for every segment that is not
+/// relabelled itself and is closest to the centroid of another speaker's
+/// relabelled segments, by at least `min_similarity`, and closer to it by
+/// MIN_PROPAGATION_MARGIN than to the centroid of its current speaker's other
+/// untouched segments. Most similar first.
+pub(crate) fn suggest_relabels(segments: &[EmbeddedSegment], min_similarity: f32) -> Vec<(i64, String, f32)> {
+    let mut examples: HashMap<&str, Vec<Vec<f32>>> = HashMap::new();
+    for s in segments.iter().filter(|s| s.relabelled) {
+        examples.entry(s.speaker.as_str()).or_default().push(s.embedding.clone());
+    }
+    let centroids: Vec<(&str, Vec<f32>)> = examples
+        .into_iter()
+        .filter_map(|(speaker, embeddings)| mean_embedding(&embeddings).map(|c| (speaker, c)))
+        .collect();
+
+    // Sum of the normalized untouched embeddings per speaker; a segment's own
+    // is taken out again, so it is compared with the rest of its speaker only
+    let mut untouched: HashMap<&str, (Vec<f32>, usize)> = HashMap::new();
+    for s in segments.iter().filter(|s| !s.relabelled) {
+        let (sum, count) = untouched
+            .entry(s.speaker.as_str())
+            .or_insert_with(|| (vec![0.0; s.embedding.len()], 0));
+        if sum.len() == s.embedding.len() {
+            sum.iter_mut().zip(normalized(&s.embedding)).for_each(|(a, v)| *a += v);
+            *count += 1;
+        }
+    }
+
+    let mut suggestions: Vec<(i64, String, f32)> = segments
+        .iter()
+        .filter(|s| !s.relabelled)
+        .filter_map(|s| {
+            let (speaker, similarity) = centroids
+                .iter()
+                .map(|(speaker, centroid)| (*speaker, cosine_similarity(&s.embedding, centroid)))
+                .max_by(|a, b| a.1.total_cmp(&b.1))?;
+            if speaker == s.speaker || similarity < min_similarity {
+                return None;
+            }
+            let own = match untouched.get(s.speaker.as_str()) {
+                Some((sum, count)) if *count > 1 && sum.len() == s.embedding.len() => {
+                    let rest: Vec<f32> = sum.iter().zip(normalized(&s.embedding)).map(|(a, v)| a - v).collect();
+                    cosine_similarity(&s.embedding, &rest)
+                }
+                _ => 0.0,
+            };
+            if similarity - own < MIN_PROPAGATION_MARGIN {
+                return None;
+            }
+            Some((s.id, speaker.to_string(), similarity))
+        })
+        .collect();
+    suggestions.sort_by(|a, b| b.2.total_cmp(&a.2).then(a.0.cmp(&b.0)));
+    suggestions
+}
+
+/// Segments of an episode that look like the ones relabelled by hand, with the
+/// speaker they were relabelled to. Nothing is changed; confirm the wanted ones
+/// with apply_speaker_propagation.
+#[tauri::command]
+pub async fn suggest_speaker_propagation(
+    episode_id: i64,
+    min_similarity: Option<f32>,
+    app: tauri::AppHandle,
+) -> Result<Vec<PropagationSuggestion>, String> {
+    let min_similarity = min_similarity.unwrap_or(DEFAULT_PROPAGATION_SIMILARITY);
+    if !(0.0..=1.0).contains(&min_similarity) {
+        return Err("Ähnlichkeit muss zwischen 0 und 1 liegen".to_string());
+    }
+
+    let paths = BinkyPaths::from_app(&app)?;
+    let conn = rusqlite::Connection::open(&paths.db_path).map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, start_ms, end_ms, text, speaker_label, corrected_speaker, embedding \
+             FROM diarization_segments WHERE episode_id = ?1 AND embedding IS NOT NULL \
+             ORDER BY start_ms",
+        )
+        .map_err(|e| e.to_string())?;
+    // (segment id → start, end, text) for the preview, plus the embeddings
+    let mut details: HashMap<i64, (i64, i64, Option<String>)> = HashMap::new();
+    let mut segments: Vec<EmbeddedSegment> = Vec::new();
+    let rows = stmt
+        .query_map(rusqlite::params![episode_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, String>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, Vec<u8>>(6)?,
+            ))
+        })
+        .map_err(|e| e.to_string())?;
+    for (id, start_ms, end_ms, text, label, corrected, blob) in rows.filter_map(|r| r.ok()) {
+        details.insert(id, (start_ms, end_ms, text));
+        segments.push(EmbeddedSegment {
+            id,
+            relabelled: corrected.as_ref().is_some_and(|c| *c != label),
+            speaker: corrected.unwrap_or(label),
+            embedding: decode_embedding(&blob),
+        });
+    }
+
+    if !segments.iter().any(|s| s.relabelled) {
+        return Err("Keine von Hand umbenannten Segmente mit Sprecher-Embedding gefunden".to_string());
+    }
+
+    let current: HashMap<i64, String> = segments.iter().map(|s| (s.id, s.speaker.clone())).collect();
+    Ok(suggest_relabels(&segments, min_similarity)
+        .into_iter()
+        .map(|(segment_id, suggested_speaker, similarity)| {
+            let (start_ms, end_ms, text) = details.remove(&segment_id).unwrap_or((0, 0, None));
+            PropagationSuggestion {
+                segment_id,
+                start_ms,
+                end_ms,
+                text,
+                current_speaker: current.get(&segment_id).cloned().unwrap_or_default(),
+                suggested_speaker,
+                similarity,
+            }
+        })
+        .collect())
+}
+
+/// Apply confirmed suggestions as one "propagate" revision (undo restores them
+/// all). Returns the changed segments.
+#[tauri::command]
+pub async fn apply_speaker_propagation(
+    episode_id: i64,
+    assignments: Vec<SpeakerAssignment>,
+    app: tauri::AppHandle,
+) -> Result<Vec<SegmentSnapshot>, String> {
+    let paths = BinkyPaths::from_app(&app)?;
+    let mut conn = rusqlite::Connection::open(&paths.db_path).map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    let assignments: Vec<(i64, Option<String>)> = assignments
+        .into_iter()
+        .map(|a| (a.segment_id, Some(a.speaker)))
+        .collect();
+    let after = relabel_segments(&tx, episode_id, "propagate", &assignments)?;
+
+    tx.commit().map_err(|e| e.to_string())?;
+    Ok(after)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn segment(id: i64, speaker: &str, relabelled: bool, embedding: [f32; 2]) -> EmbeddedSegment {
+        EmbeddedSegment {
+            id,
+            speaker: speaker.to_string(),
+            relabelled,
+            embedding: embedding.to_vec(),
+        }
+    }
+
+    #[test]
+    fn suggests_the_relabel_for_similar_segments_only() {
+        let segments = vec![
+            // Two segments moved from SPEAKER_0 to SPEAKER_1 by hand
+            segment(1, "SPEAKER_1", true, [0.0, 1.0]),
+            segment(2, "SPEAKER_1", true, [0.1, 1.0]),
+            // Same voice, still labelled SPEAKER_0
+            segment(3, "SPEAKER_0", false, [0.05, 0.9]),
+            // Similar enough, but closer to the rest of SPEAKER_0
+            segment(4, "SPEAKER_0", false, [0.5, 0.8]),
+            // Really SPEAKER_0
+            segment(5, "SPEAKER_0", false, [1.0, 0.0]),
+            // Already SPEAKER_1
+            segment(6, "SPEAKER_1", false, [0.0, 1.0]),
+        ];
+        let suggestions = suggest_relabels(&segments, 0.6);
+        let ids: Vec<i64> = suggestions.iter().map(|s| s.0).collect();
+        assert_eq!(ids, vec![3]);
+        assert_eq!(suggestions[0].1, "SPEAKER_1");
+        assert!(suggestions[0].2 > 0.95);
+
+        // A threshold above every similarity suggests nothing
+        assert!(suggest_relabels(&segments, 1.0).is_empty());
+    }
+}
@@ -0,0 +1,351 @@
+use crate::commands::attribution::words_from_tokens;
+use crate::commands::chunking::{ChunkSegment, CHUNK_SAMPLES};
+use crate::commands::hallucination::{average_logprob, classify_segment, read_hallucination_filter};
+use crate::commands::transcription::{build_full_params, read_quality_preset, resolve_model};
+use crate::commands::whisper_callbacks::{full_with_callbacks, WhisperRunError};
+use crate::models::transcript::{RetranscribeOptions, TranscriptionEvent};
+use crate::paths::BinkyPaths;
+use crate::state::job_engine::EngineState;
+use std::sync::Arc;
+use tauri::ipc::Channel;
+use tokio_util::sync::CancellationToken;
+use whisper_rs::{WhisperContext, WhisperContextParameters};
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Re-transcribe a time range
+//
+// Decodes only [start_ms, end_ms) (plus some context on both sides) and splices
+// the result into transcripts.segments_json; each new segment records the model
+// and prompt it was decoded with. Segments outside the range, and diarization
+// segments edited by hand, are left untouched. Runs outside the job queue; its
+// cancellation token lives in EngineState::range_runs.
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Audio decoded around the range so words at its edges are heard in full.
+const CONTEXT_MS: i64 = 2_000;
+
+fn segment_center(segment: &serde_json::Value) -> Option<i64> {
+    Some((segment["start_ms"].as_i64()? + segment["end_ms"].as_i64()?) / 2)
+}
+
+/// Replace the segments whose center lies in `[start_ms, end_ms)` with the new
+/// ones (new segments outside the range are dropped). Keeps time order.
+pub(crate) fn splice_segments(
+    existing: Vec<serde_json::Value>,
+    new_segments: Vec<serde_json::Value>,
+    start_ms: i64,
+    end_ms: i64,
+) -> Vec<serde_json::Value> {
+    let in_range = |segment: &serde_json::Value| {
+        segment_center(segment)
+            .map(|c| c >= start_ms && c < end_ms)
+            .unwrap_or(false)
+    };
+    let mut spliced: Vec<serde_json::Value> =
+        existing.into_iter().filter(|s| !in_range(s)).collect();
+    let insert_at = spliced
+        .iter()
+        .position(|s| segment_center(s).map(|c| c >= end_ms).unwrap_or(false))
+        .unwrap_or(spliced.len());
+    spliced.splice(
+        insert_at..insert_at,
+        new_segments.into_iter().filter(|s| in_range(s)),
+    );
+    spliced
+}
+
+/// Transcript text from segments, skipping hallucination-flagged ones.
+fn full_text_of(segments: &[serde_json::Value]) -> String {
+    segments
+        .iter()
+        .filter(|s| !s["hallucination"].is_string())
+        .filter_map(|s| s["text"].as_str())
+        .collect()
+}
+
+/// Re-transcribe `[start_ms, end_ms)` of a Whisper-transcribed episode,
+/// optionally with another downloaded model or an initial prompt. At most 20
+/// minutes at a time; cancel with `cancel_retranscribe_range`.
+#[tauri::command]
+pub async fn retranscribe_range(
+    episode_id: i64,
+    start_ms: i64,
+    end_ms: i64,
+    options: Option<RetranscribeOptions>,
+    on_event: Channel<TranscriptionEvent>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<EngineState>>,
+) -> Result<(), String> {
+    if start_ms < 0 || end_ms <= start_ms {
+        return Err("Ungültiger Zeitbereich".to_string());
+    }
+    if (end_ms - start_ms) * 16 > CHUNK_SAMPLES as i64 {
+        return Err("Der Bereich darf höchstens 20 Minuten lang sein".to_string());
+    }
+    let paths = BinkyPaths::from_app(&app)?;
+
+    let cancel_token = CancellationToken::new();
+    {
+        // Same lock order as enqueue_job (engine, then range_runs), so a
+        // pipeline can't be queued between the check and the registration
+        let engine = state.engine.lock().unwrap();
+        if engine.job(episode_id).is_some() {
+            return Err("Episode wird gerade verarbeitet".to_string());
+        }
+        let mut runs = state.range_runs.lock().unwrap();
+        if runs.contains_key(&episode_id) {
+            return Err("Für diese Episode läuft bereits eine Neutranskription".to_string());
+        }
+        runs.insert(episode_id, cancel_token.clone());
+    }
+    let result = run_retranscription(
+        &paths,
+        episode_id,
+        start_ms,
+        end_ms,
+        options.unwrap_or_default(),
+        &on_event,
+        cancel_token,
+    )
+    .await;
+    state.range_runs.lock().unwrap().remove(&episode_id);
+    result
+}
+
+/// Cancel a running `retranscribe_range` of an episode; the transcript stays
+/// as it was.
+#[tauri::command]
+pub async fn cancel_retranscribe_range(
+    episode_id: i64,
+    state: tauri::State<'_, Arc<EngineState>>,
+) -> Result<(), String> {
+    if let Some(token) = state.range_runs.lock().unwrap().get(&episode_id) {
+        token.cancel();
+    }
+    Ok(())
+}
+
+async fn run_retranscription(
+    paths: &BinkyPaths,
+    episode_id: i64,
+    start_ms: i64,
+    end_ms: i64,
+    options: RetranscribeOptions,
+    on_event: &Channel<TranscriptionEvent>,
+    cancel_token: CancellationToken,
+) -> Result<(), String> {
+    let db_path = paths.db_path.as_path();
+
+    let (audio_url, segments_json, language): (Option<String>, Option<String>, Option<String>) = {
+        let conn = rusqlite::Connection::open(db_path).map_err(|e| e.to_string())?;
+        conn.query_row(
+            "SELECT e.audio_url, t.segments_json, t.language \
+             FROM episodes e JOIN transcripts t ON t.episode_id = e.id WHERE e.id = ?1",
+            rusqlite::params![episode_id],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .map_err(|_| "Kein Transkript für diese Episode gefunden".to_string())?
+    };
+    let existing: Vec<serde_json::Value> = segments_json
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .ok_or_else(|| "Nur Whisper-Transkripte können teilweise neu transkribiert werden".to_string())?;
+    let audio_url = audio_url.ok_or_else(|| "Episode hat keine Audio-URL".to_string())?;
+    let language = language.unwrap_or_else(|| "de".to_string());
+
+    let (model_name, model_path) = resolve_model(&paths.models_dir, options.model.as_deref()).await?;
+
+    let audio = crate::commands::pipeline::decode_episode_audio(paths, episode_id, &audio_url, |percent| {
+        let _ = on_event.send(TranscriptionEvent::Downloading { percent });
+    })
+    .await?;
+    if cancel_token.is_cancelled() {
+        return Err("Abgebrochen".to_string());
+    }
+
+    let from_sample = ((start_ms - CONTEXT_MS).max(0) * 16) as usize;
+    let to_sample = (((end_ms + CONTEXT_MS) * 16) as usize).min(audio.len());
+    if from_sample >= to_sample {
+        return Err("Der Zeitbereich liegt hinter dem Ende der Episode".to_string());
+    }
+    let window = audio[from_sample..to_sample].to_vec();
+    drop(audio);
+    let offset_ms = (from_sample / 16) as i64;
+
+    let mut preset = read_quality_preset(db_path);
+    let budget = crate::commands::pipeline::read_cpu_budget(db_path);
+    preset.n_threads = Some(
+        budget.whisper_threads_for(preset.n_threads, crate::commands::pipeline::available_cores()),
+    );
+    let prompt = options
+        .prompt
+        .map(|p| p.replace('\0', ""))
+        .filter(|p| !p.trim().is_empty());
+    let prompt_for_whisper = prompt.clone();
+    let on_event_for_whisper = on_event.clone();
+
+    let new_segments = tauri::async_runtime::spawn_blocking(move || {
+        let ctx = WhisperContext::new_with_params(
+            &model_path.to_string_lossy(),
+            WhisperContextParameters::default(),
+        )
+        .map_err(|e| format!("Failed to load Whisper model: {}", e))?;
+        let mut whisper_state = ctx
+            .create_state()
+            .map_err(|e| format!("Failed to create Whisper state: {}", e))?;
+
+        let mut params = build_full_params(&preset, &language);
+        if let Some(prompt) = &prompt_for_whisper {
+            params.set_initial_prompt(prompt);
+        }
+        let mut on_progress = |percent: i32| {
+            let _ = on_event_for_whisper.send(TranscriptionEvent::Progress {
+                percent: 50 + percent / 2,
+            });
+        };
+        full_with_callbacks(&mut whisper_state, params, &window, &cancel_token, &mut on_progress)
+            .map_err(|e| match e {
+                WhisperRunError::Cancelled => "Abgebrochen".to_string(),
+                WhisperRunError::Failed(e) => format!("Whisper failed: {}", e),
+            })?;
+
+        let eot = ctx.token_eot();
+        let segments: Vec<ChunkSegment> = whisper_state
+            .as_iter()
+            .map(|segment| {
+                let token_logprobs: Vec<f32> = (0..segment.n_tokens())
+                    .filter_map(|i| segment.get_token(i))
+                    .map(|token| token.token_data())
+                    .filter(|data| data.id < eot)
+                    .map(|data| data.plog)
+                    .collect();
+                let words = words_from_tokens(
+                    (0..segment.n_tokens())
+                        .filter_map(|i| segment.get_token(i))
+                        .filter(|token| token.token_data().id < eot)
+                        .map(|token| {
+                            let data = token.token_data();
+                            (
+                                token.to_bytes().map(|b| b.to_vec()).unwrap_or_default(),
+                                data.t0 * 10 + offset_ms,
+                                data.t1 * 10 + offset_ms,
+                            )
+                        }),
+                );
+                ChunkSegment {
+                    text: segment.to_string(),
+                    start_ms: segment.start_timestamp() * 10 + offset_ms,
+                    end_ms: segment.end_timestamp() * 10 + offset_ms,
+                    avg_logprob: average_logprob(&token_logprobs),
+                    no_speech_prob: segment.no_speech_probability(),
+                    words,
+                }
+            })
+            .collect();
+        Ok::<Vec<ChunkSegment>, String>(segments)
+    })
+    .await
+    .map_err(|e| format!("Whisper task panicked: {}", e))??;
+
+    // Flag hallucinations against the transcript preceding the range
+    let filter = read_hallucination_filter(db_path);
+    let mut context: Vec<serde_json::Value> = existing
+        .iter()
+        .filter(|s| segment_center(s).map(|c| c < start_ms).unwrap_or(false))
+        .cloned()
+        .collect();
+    let mut spliced_in = vec![];
+    for segment in new_segments {
+        let mut entry = serde_json::json!({
+            "text": segment.text,
+            "start_ms": segment.start_ms,
+            "end_ms": segment.end_ms,
+            "model": model_name
+        });
+        if let Some(prompt) = &prompt {
+            entry["prompt"] = prompt.as_str().into();
+        }
+        if !segment.words.is_empty() {
+            entry["words"] = serde_json::to_value(&segment.words).unwrap_or_default();
+        }
+        if let Some(kind) = classify_segment(
+            &segment.text,
+            segment.avg_logprob,
+            segment.no_speech_prob,
+            &context,
+            &filter,
+        ) {
+            entry["hallucination"] = kind.as_str().into();
+        }
+        context.push(entry.clone());
+        spliced_in.push(entry);
+    }
+
+    let segments = splice_segments(existing, spliced_in, start_ms, end_ms);
+    {
+        let conn = rusqlite::Connection::open(db_path).map_err(|e| e.to_string())?;
+        conn.execute(
+            "UPDATE transcripts SET segments_json = ?1, full_text = ?2 WHERE episode_id = ?3",
+            rusqlite::params![
+                serde_json::to_string(&segments).map_err(|e| e.to_string())?,
+                full_text_of(&segments),
+                episode_id
+            ],
+        )
+        .map_err(|e| format!("Transkript konnte nicht gespeichert werden: {}", e))?;
+    }
+
+    // Speaker text of the range follows the new Whisper segments (hand-edited
+    // segments are skipped by the backfill); the FTS triggers and reindex pick
+    // up the text.
+    crate::commands::diarization::backfill_segment_text_in_range(db_path, episode_id, Some((start_ms, end_ms)));
+    crate::commands::search::reindex_episode(db_path, episode_id)?;
+
+    let _ = on_event.send(TranscriptionEvent::Done { episode_id });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seg(text: &str, start_ms: i64, end_ms: i64) -> serde_json::Value {
+        serde_json::json!({ "text": text, "start_ms": start_ms, "end_ms": end_ms })
+    }
+
+    fn texts(segments: &[serde_json::Value]) -> Vec<&str> {
+        segments.iter().filter_map(|s| s["text"].as_str()).collect()
+    }
+
+    #[test]
+    fn splice_replaces_only_segments_inside_the_range() {
+        let existing = vec![
+            seg("A", 0, 4_000),
+            seg("kaputt", 4_000, 9_000),
+            seg("auch kaputt", 9_000, 15_000),
+            seg("B", 15_000, 20_000),
+        ];
+        // New decode includes context before and after the range
+        let new_segments = vec![
+            seg("A (Kontext)", 2_000, 4_000),
+            seg("heile", 4_100, 8_800),
+            seg("auch heile", 9_000, 14_900),
+            seg("B (Kontext)", 15_000, 17_000),
+        ];
+        let spliced = splice_segments(existing, new_segments, 4_000, 15_000);
+        assert_eq!(texts(&spliced), vec!["A", "heile", "auch heile", "B"]);
+    }
+
+    #[test]
+    fn splice_at_the_end_appends() {
+        let existing = vec![seg("A", 0, 4_000), seg("Ende", 4_000, 6_000)];
+        let spliced = splice_segments(existing, vec![seg("Neues Ende", 4_000, 6_500)], 4_000, 10_000);
+        assert_eq!(texts(&spliced), vec!["A", "Neues Ende"]);
+    }
+
+    #[test]
+    fn full_text_skips_flagged_segments() {
+        let mut flagged = seg(" Untertitel im Auftrag des ZDF", 0, 1_000);
+        flagged["hallucination"] = "stock_phrase".into();
+        assert_eq!(full_text_of(&[flagged, seg(" Hallo", 1_000, 2_000)]), " Hallo");
+    }
+}
@@ -1,3 +1,4 @@
+#[cfg(feature = "app")]
 use tauri::Manager;
 use serde::Serialize;
 
@@ -12,6 +13,7 @@ pub struct SearchResult {
     pub segment_type: String,
     pub start_ms: Option<i64>,
     pub end_ms: Option<i64>,
+    pub language: Option<String>,
 }
 
 /// Sanitize user input for FTS5 MATCH syntax.
@@ -44,27 +46,40 @@ fn build_fts_query(input: &str) -> String {
 /// Arguments:
 ///   query: The search term (minimum 2 characters after trimming)
 ///   limit: Maximum results (default 20, capped at 100)
+///   language: Optional transcript language filter (e.g. "de", "en")
 ///
 /// Returns Vec<SearchResult> sorted by BM25 relevance (best first).
 /// Returns Ok([]) for queries shorter than 2 chars or that sanitize to empty.
 /// Returns Ok([]) (not Err) on FTS5 syntax errors — prevents frontend error toasts on bad input.
+#[cfg(feature = "app")]
 #[tauri::command]
 pub fn search_transcripts(
     query: String,
     limit: Option<i64>,
+    language: Option<String>,
     app: tauri::AppHandle,
 ) -> Result<Vec<SearchResult>, String> {
-    let query = query.trim();
-    if query.len() < 2 {
-        return Ok(vec![]);
-    }
-
     let db_path = app
         .path()
         .app_data_dir()
         .map_err(|e| e.to_string())?
         .join("binky.db");
-    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
+    search_index(&db_path, &query, limit, language)
+}
+
+/// Body of search_transcripts, shared with the CLI.
+pub(crate) fn search_index(
+    db_path: &std::path::Path,
+    query: &str,
+    limit: Option<i64>,
+    language: Option<String>,
+) -> Result<Vec<SearchResult>, String> {
+    let query = query.trim();
+    if query.len() < 2 {
+        return Ok(vec![]);
+    }
+
+    let conn = rusqlite::Connection::open(db_path).map_err(|e| e.to_string())?;
 
     let max_results = limit.unwrap_or(20).min(100);
 
@@ -79,21 +94,27 @@ pub fn search_transcripts(
     //   close_markup=''
     //   ellipsis='...'
     //   max_tokens=40 (≈ 250 chars depending on word length)
+    //
+    // transcripts.language is joined in so results can be filtered by language
+    // (?3 = NULL disables the filter).
     let sql = "
-        SELECT episode_id, episode_title, speaker, segment_type, start_ms, end_ms,
-               snippet(search_index, 3, '', '', '...', 40)
-        FROM search_index
+        SELECT si.episode_id, si.episode_title, si.speaker, si.segment_type, si.start_ms, si.end_ms,
+               snippet(search_index, 3, '', '', '...', 40), t.language
+        FROM search_index si
+        LEFT JOIN transcripts t ON t.episode_id = si.episode_id
         WHERE search_index MATCH ?1
+          AND (?3 IS NULL OR t.language = ?3)
         ORDER BY bm25(search_index)
         LIMIT ?2
     ";
+    let language = language.filter(|l| !l.is_empty());
 
     let mut stmt = match conn.prepare(sql) {
         Ok(s) => s,
         Err(_) => return Ok(vec![]),
     };
 
-    let results = match stmt.query_map(rusqlite::params![fts_query, max_results], |row| {
+    let results = match stmt.query_map(rusqlite::params![fts_query, max_results, language], |row| {
         Ok(SearchResult {
             episode_id: row.get(0)?,
             title: row.get(1)?,
@@ -102,6 +123,7 @@ pub fn search_transcripts(
             start_ms: row.get(4)?,
             end_ms: row.get(5)?,
             snippet: row.get(6)?,
+            language: row.get(7)?,
         })
     }) {
         Ok(mapped) => mapped.filter_map(|r| r.ok()).collect::<Vec<_>>(),
@@ -114,6 +136,7 @@ pub fn search_transcripts(
 /// Rebuild the entire FTS5 search index from stored content.
 /// Use when the index is suspected corrupt or after bulk data changes outside triggers.
 /// This is the FTS5 'rebuild' special command — it re-derives all index data.
+#[cfg(feature = "app")]
 #[tauri::command]
 pub fn rebuild_search_index(app: tauri::AppHandle) -> Result<(), String> {
     let db_path = app
@@ -129,8 +152,50 @@ pub fn rebuild_search_index(app: tauri::AppHandle) -> Result<(), String> {
     Ok(())
 }
 
+/// Re-derive the transcript rows of one episode in the search index.
+/// The insert triggers add a full_text fallback row as soon as a transcript is
+/// stored; once diarization segments carry text that fallback is a duplicate.
+/// Used by the job engine's index stage after transcription/diarization.
+pub(crate) fn reindex_episode(db_path: &std::path::Path, episode_id: i64) -> Result<(), String> {
+    let mut conn = rusqlite::Connection::open(db_path).map_err(|e| e.to_string())?;
+    let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+    tx.execute(
+        "DELETE FROM search_index WHERE rowid IN ( \
+             SELECT rowid FROM search_index WHERE episode_id = ?1 AND segment_type = 'transcript' \
+         )",
+        rusqlite::params![episode_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    let segment_rows = tx
+        .execute(
+            "INSERT INTO search_index(rowid, episode_id, episode_title, speaker, segment_text, segment_type, start_ms, end_ms) \
+             SELECT ds.id, ds.episode_id, e.title, COALESCE(ds.corrected_speaker, ds.speaker_label), \
+                    ds.text, 'transcript', ds.start_ms, ds.end_ms \
+             FROM diarization_segments ds JOIN episodes e ON e.id = ds.episode_id \
+             WHERE ds.episode_id = ?1 AND ds.text IS NOT NULL AND ds.text != ''",
+            rusqlite::params![episode_id],
+        )
+        .map_err(|e| e.to_string())?;
+
+    if segment_rows == 0 {
+        tx.execute(
+            "INSERT INTO search_index(episode_id, episode_title, speaker, segment_text, segment_type, start_ms, end_ms) \
+             SELECT t.episode_id, e.title, NULL, t.full_text, 'transcript', NULL, NULL \
+             FROM transcripts t JOIN episodes e ON e.id = t.episode_id \
+             WHERE t.episode_id = ?1 AND t.full_text IS NOT NULL AND t.full_text != ''",
+            rusqlite::params![episode_id],
+        )
+        .map_err(|e| e.to_string())?;
+    }
+
+    tx.commit().map_err(|e| e.to_string())
+}
+
 /// A related episode result returned by fetch_related_episodes.
 /// episode_number may be NULL for episodes without a number assigned.
+#[cfg(feature = "app")]
 #[derive(Debug, Serialize)]
 pub struct RelatedEpisode {
     pub episode_id: i64,
@@ -140,6 +205,7 @@ pub struct RelatedEpisode {
 
 /// German stop-words stripped from topic titles before building the FTS5 query.
 /// Prevents high-frequency words from dominating BM25 scores.
+#[cfg(feature = "app")]
 const STOP_WORDS: &[&str] = &[
     "die", "der", "das", "und", "in", "mit", "für", "von", "zu", "ein",
     "eine", "auf", "an", "ist", "es", "er", "sie", "wir", "ich", "du",
@@ -150,6 +216,7 @@ const STOP_WORDS: &[&str] = &[
 /// Build an OR-query from the significant words in a topic title.
 /// Strips German stop-words and single-character tokens; joins remaining with " OR ".
 /// Returns None if all tokens are stop-words (caller skips the FTS query).
+#[cfg(feature = "app")]
 fn build_topic_fts_query(title: &str) -> Option<String> {
     // Reuse sanitization: keep alphanumeric/whitespace/hyphens
     let sanitized: String = title
@@ -182,6 +249,7 @@ fn build_topic_fts_query(title: &str) -> Option<String> {
 /// Empty query (all stop-words) → store empty Vec for that topic_id, continue.
 /// Any per-topic error → store empty Vec, continue (never propagate per-topic errors).
 /// Returns Err only on db open failure.
+#[cfg(feature = "app")]
 #[tauri::command]
 pub fn fetch_related_episodes(
     topic_ids: Vec<i64>,
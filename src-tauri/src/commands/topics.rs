@@ -1,3 +1,4 @@
+#[cfg(feature = "app")]
 use tauri::Manager;
 use serde::Serialize;
 
@@ -11,6 +12,7 @@ pub struct DetectedTopic {
     pub detected_from_episode_id: i64,
 }
 
+#[cfg(feature = "app")]
 #[derive(Debug, Serialize)]
 pub struct AnalysisStatus {
     pub episode_id: i64,
@@ -34,6 +36,7 @@ struct LlmTopic {
 }
 
 /// Analyze a single episode's transcript for unfinished topics using LLM.
+#[cfg(feature = "app")]
 #[tauri::command]
 pub async fn analyze_episode_topics(
     episode_id: i64,
@@ -43,9 +46,17 @@ pub async fn analyze_episode_topics(
         .map_err(|e| e.to_string())?
         .join("binky.db");
 
+    analyze_topics_for_episode(&db_path, episode_id).await
+}
+
+/// Shared by the command and the job engine's analyze_topics stage.
+pub(crate) async fn analyze_topics_for_episode(
+    db_path: &std::path::Path,
+    episode_id: i64,
+) -> Result<Vec<DetectedTopic>, String> {
     // Step 1: Read API key from settings
     let api_key = {
-        let conn = rusqlite::Connection::open(&db_path)
+        let conn = rusqlite::Connection::open(db_path)
             .map_err(|e| e.to_string())?;
         let key: Option<String> = conn.query_row(
             "SELECT value FROM settings WHERE key = 'openai_api_key'",
@@ -60,7 +71,7 @@ pub async fn analyze_episode_topics(
 
     // Step 2: Read transcript text
     let transcript_text = {
-        let conn = rusqlite::Connection::open(&db_path)
+        let conn = rusqlite::Connection::open(db_path)
             .map_err(|e| e.to_string())?;
         let text: Option<String> = conn.query_row(
             "SELECT full_text FROM transcripts WHERE episode_id = ?",
@@ -75,7 +86,7 @@ pub async fn analyze_episode_topics(
 
     // Step 3: Set episode_analysis status to 'analyzing'
     {
-        let conn = rusqlite::Connection::open(&db_path)
+        let conn = rusqlite::Connection::open(db_path)
             .map_err(|e| e.to_string())?;
         conn.execute(
             "INSERT OR REPLACE INTO episode_analysis (episode_id, status, topics_found, analyzed_at, error)
@@ -85,13 +96,13 @@ pub async fn analyze_episode_topics(
     }
 
     // Step 4: Call LLM (error path updates episode_analysis status='error')
-    let result = run_llm_analysis(episode_id, &api_key, &transcript_text, &db_path).await;
+    let result = run_llm_analysis(episode_id, &api_key, &transcript_text, db_path).await;
 
     match result {
         Ok(topics) => Ok(topics),
         Err(err_msg) => {
             // Update episode_analysis with error status
-            if let Ok(conn) = rusqlite::Connection::open(&db_path) {
+            if let Ok(conn) = rusqlite::Connection::open(db_path) {
                 let _ = conn.execute(
                     "UPDATE episode_analysis SET status='error', error=? WHERE episode_id=?",
                     rusqlite::params![err_msg, episode_id],
@@ -237,6 +248,7 @@ Antworte NUR mit validem JSON im Format: {\"topics\": [{\"title\": \"...\", \"de
 }
 
 /// Check analysis status for a specific episode.
+#[cfg(feature = "app")]
 #[tauri::command]
 pub async fn get_episode_analysis_status(
     episode_id: i64,
@@ -272,6 +284,7 @@ pub async fn get_episode_analysis_status(
 }
 
 /// Check if an OpenAI API key is configured (never returns the key itself).
+#[cfg(feature = "app")]
 #[tauri::command]
 pub async fn has_openai_key_configured(
     app: tauri::AppHandle,
@@ -1,19 +1,44 @@
-use crate::models::transcript::{ModelDownloadEvent, TranscriptionEvent};
-use crate::state::transcription_queue::{TranscriptionJob, TranscriptionState};
+use crate::commands::whisper_callbacks::{full_with_callbacks, WhisperRunError};
+use crate::commands::chunking::{
+    chunk_ranges, stitch_chunk, ChunkSegment, CHUNK_OVERLAP_SAMPLES, CHUNK_SAMPLES,
+};
+use crate::models::transcript::{QualityPreset, TranscriptionEvent};
+#[cfg(feature = "app")]
+use crate::models::transcript::ModelDownloadEvent;
+#[cfg(feature = "app")]
+use crate::models::pipeline::Stage;
+use crate::paths::BinkyPaths;
+use crate::progress::Progress;
+use crate::state::job_engine::StageResult;
+#[cfg(feature = "app")]
+use crate::state::job_engine::{EngineState, PipelineJob};
+#[cfg(feature = "app")]
 use futures_util::StreamExt;
+#[cfg(feature = "app")]
 use serde::{Deserialize, Serialize};
 use std::path::Path;
+#[cfg(feature = "app")]
 use std::sync::Arc;
+#[cfg(feature = "app")]
 use tauri::ipc::Channel;
+#[cfg(feature = "app")]
 use tauri::Manager;
-use tauri_plugin_http::reqwest;
+#[cfg(feature = "app")]
 use tokio::io::AsyncWriteExt;
 use tokio_util::sync::CancellationToken;
 use whisper_rs::{FullParams, SamplingStrategy, WhisperContext, WhisperContextParameters};
 
+#[cfg(feature = "app")]
 const HUGGINGFACE_BASE_URL: &str =
     "https://huggingface.co/ggerganov/whisper.cpp/resolve/main";
 
+const NO_MODEL_MESSAGE: &str =
+    "Kein Whisper-Modell heruntergeladen. Bitte zuerst ein Modell in den Einstellungen herunterladen.";
+
+const DEFAULT_QUALITY_PRESET: &str = "fast";
+const CUSTOM_QUALITY_PRESET: &str = "custom";
+
+#[cfg(feature = "app")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelStatus {
     pub downloaded_model: Option<String>,
@@ -21,6 +46,7 @@ pub struct ModelStatus {
     pub models_dir: String,
 }
 
+#[cfg(feature = "app")]
 #[derive(Debug, Clone, Serialize)]
 pub struct QueueStatus {
     pub active_episode_id: Option<i64>,
@@ -33,6 +59,7 @@ pub struct QueueStatus {
 // ─────────────────────────────────────────────────────────────────────────────
 
 /// Returns the currently downloaded Whisper model (if any) from the app local data directory.
+#[cfg(feature = "app")]
 #[tauri::command]
 pub async fn get_model_status(app: tauri::AppHandle) -> Result<ModelStatus, String> {
     let models_dir = app
@@ -91,6 +118,7 @@ pub async fn get_model_status(app: tauri::AppHandle) -> Result<ModelStatus, Stri
 }
 
 /// Downloads a Whisper model from Hugging Face with streaming progress updates via Channel.
+#[cfg(feature = "app")]
 #[tauri::command]
 pub async fn download_whisper_model(
     model_name: String,
@@ -200,6 +228,7 @@ pub async fn download_whisper_model(
 }
 
 /// Deletes any downloaded Whisper model file from the models directory.
+#[cfg(feature = "app")]
 #[tauri::command]
 pub async fn delete_whisper_model(app: tauri::AppHandle) -> Result<(), String> {
     let models_dir = app
@@ -239,14 +268,8 @@ pub async fn delete_whisper_model(app: tauri::AppHandle) -> Result<(), String> {
 
 /// Find the first ggml-*.bin model file in the models directory.
 /// Returns (model_name, full_path) if found.
-async fn find_model(app: &tauri::AppHandle) -> Option<(String, std::path::PathBuf)> {
-    let models_dir = app
-        .path()
-        .app_local_data_dir()
-        .ok()?
-        .join("models");
-
-    let mut read_dir = tokio::fs::read_dir(&models_dir).await.ok()?;
+pub(crate) async fn find_model(models_dir: &Path) -> Option<(String, std::path::PathBuf)> {
+    let mut read_dir = tokio::fs::read_dir(models_dir).await.ok()?;
 
     while let Ok(Some(entry)) = read_dir.next_entry().await {
         let file_name = entry.file_name();
@@ -263,23 +286,318 @@ async fn find_model(app: &tauri::AppHandle) -> Option<(String, std::path::PathBu
     None
 }
 
+/// All downloaded models as (model_name, full_path), sorted by name.
+#[cfg(feature = "app")]
+pub(crate) async fn installed_models(models_dir: &Path) -> Vec<(String, std::path::PathBuf)> {
+    let mut models = Vec::new();
+    let mut read_dir = match tokio::fs::read_dir(models_dir).await {
+        Ok(read_dir) => read_dir,
+        Err(_) => return models,
+    };
+
+    while let Ok(Some(entry)) = read_dir.next_entry().await {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with("ggml-") && name.ends_with(".bin") {
+            let model_name = name
+                .trim_start_matches("ggml-")
+                .trim_end_matches(".bin")
+                .to_string();
+            models.push((model_name, entry.path()));
+        }
+    }
+    models.sort();
+    models
+}
+
+/// Model to use: `name` (e.g. "large-v3-turbo") if given and downloaded,
+/// otherwise the first downloaded one.
+#[cfg(feature = "app")]
+pub(crate) async fn resolve_model(
+    models_dir: &Path,
+    name: Option<&str>,
+) -> Result<(String, std::path::PathBuf), String> {
+    match name {
+        Some(name) => {
+            let path = models_dir.join(format!("ggml-{}.bin", name));
+            if path.exists() {
+                Ok((name.to_string(), path))
+            } else {
+                Err(format!("Whisper-Modell '{}' ist nicht heruntergeladen", name))
+            }
+        }
+        None => find_model(models_dir).await.ok_or_else(|| NO_MODEL_MESSAGE.to_string()),
+    }
+}
+
+/// Read a single value from the settings table. Returns None if the key is
+/// missing or the database cannot be opened.
+pub(crate) fn read_setting(db_path: &Path, key: &str) -> Option<String> {
+    let conn = rusqlite::Connection::open(db_path).ok()?;
+    conn.query_row(
+        "SELECT value FROM settings WHERE key = ?1 LIMIT 1",
+        rusqlite::params![key],
+        |row| row.get(0),
+    )
+    .ok()
+}
+
 /// Read the whisper_language setting from the SQLite database. Returns "de" by default.
-fn read_language_setting(db_path: &Path) -> String {
-    match rusqlite::Connection::open(db_path) {
-        Ok(conn) => {
-            let result: rusqlite::Result<String> = conn.query_row(
-                "SELECT value FROM settings WHERE key = 'whisper_language' LIMIT 1",
-                [],
+pub(crate) fn read_language_setting(db_path: &Path) -> String {
+    read_setting(db_path, "whisper_language").unwrap_or_else(|| "de".to_string())
+}
+
+/// Resolve the Whisper language for one episode: the per-episode
+/// `language_override` wins over the global setting. "auto" means detect.
+pub(crate) fn resolve_episode_language(db_path: &Path, episode_id: i64) -> String {
+    let override_lang: Option<String> = rusqlite::Connection::open(db_path)
+        .ok()
+        .and_then(|conn| {
+            conn.query_row(
+                "SELECT language_override FROM episodes WHERE id = ?1",
+                rusqlite::params![episode_id],
                 |row| row.get(0),
-            );
-            result.unwrap_or_else(|_| "de".to_string())
+            )
+            .ok()
+        })
+        .flatten();
+
+    match override_lang {
+        Some(lang) if !lang.is_empty() => lang,
+        _ => read_language_setting(db_path),
+    }
+}
+
+/// Detect the spoken language from the first 30 s of the first chunk.
+/// Returns a Whisper language code such as "de" or "en".
+pub(crate) fn detect_language(ctx: &WhisperContext, audio: &[f32], n_threads: usize) -> Result<String, String> {
+    const DETECT_SAMPLES: usize = 30 * 16_000; // one Whisper window
+    let sample = &audio[..audio.len().min(DETECT_SAMPLES)];
+
+    let mut state = ctx
+        .create_state()
+        .map_err(|e| format!("Failed to create Whisper state (language detection): {}", e))?;
+    state
+        .pcm_to_mel(sample, n_threads)
+        .map_err(|e| format!("Language detection failed: {}", e))?;
+    let (lang_id, _probs) = state
+        .lang_detect(0, n_threads)
+        .map_err(|e| format!("Language detection failed: {}", e))?;
+
+    Ok(whisper_rs::get_lang_str(lang_id).unwrap_or("de").to_string())
+}
+
+/// Set or clear (None / "") the Whisper language override for one episode.
+/// Accepts any Whisper language code or "auto".
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn set_episode_language(
+    episode_id: i64,
+    language: Option<String>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let language = language.map(|l| l.trim().to_lowercase()).filter(|l| !l.is_empty());
+    if let Some(lang) = &language {
+        if lang != "auto" && whisper_rs::get_lang_id(lang).is_none() {
+            return Err(format!("Unbekannte Sprache: {}", lang));
+        }
+    }
+
+    let db_path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Cannot resolve app data dir: {}", e))?
+        .join("binky.db");
+    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE episodes SET language_override = ?1, updated_at = datetime('now') WHERE id = ?2",
+        rusqlite::params![language, episode_id],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Decoding quality presets
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Built-in decoding presets. "fast" reproduces the original hardcoded
+/// `Greedy { best_of: 1 }` run; thresholds are the whisper.cpp defaults.
+pub(crate) fn builtin_quality_presets() -> Vec<QualityPreset> {
+    let base = QualityPreset {
+        name: DEFAULT_QUALITY_PRESET.to_string(),
+        beam_size: None,
+        best_of: 1,
+        temperature: 0.0,
+        temperature_inc: 0.2,
+        no_speech_thold: 0.6,
+        entropy_thold: 2.4,
+        logprob_thold: -1.0,
+        suppress_blank: true,
+        n_threads: None,
+    };
+    vec![
+        base.clone(),
+        QualityPreset {
+            name: "balanced".to_string(),
+            best_of: 5,
+            ..base.clone()
+        },
+        QualityPreset {
+            name: "accurate".to_string(),
+            beam_size: Some(5),
+            best_of: 5,
+            ..base
+        },
+    ]
+}
+
+/// Clamp user-supplied preset values into ranges whisper.cpp accepts.
+fn sanitize_quality_preset(mut preset: QualityPreset) -> QualityPreset {
+    preset.beam_size = preset.beam_size.map(|b| b.clamp(1, 16));
+    preset.best_of = preset.best_of.clamp(1, 16);
+    preset.temperature = preset.temperature.clamp(0.0, 1.0);
+    preset.temperature_inc = preset.temperature_inc.clamp(0.0, 1.0);
+    preset.no_speech_thold = preset.no_speech_thold.clamp(0.0, 1.0);
+    preset.n_threads = preset.n_threads.map(|n| n.max(1));
+    preset
+}
+
+/// Read the stored custom preset (settings key `whisper_custom_preset`), if any.
+fn read_custom_quality_preset(db_path: &Path) -> Option<QualityPreset> {
+    let json = read_setting(db_path, "whisper_custom_preset")?;
+    let preset: QualityPreset = serde_json::from_str(&json).ok()?;
+    Some(sanitize_quality_preset(QualityPreset {
+        name: CUSTOM_QUALITY_PRESET.to_string(),
+        ..preset
+    }))
+}
+
+/// Resolve the active preset from `whisper_quality_preset`. Unknown names (or a
+/// missing custom preset) fall back to the default so transcription never fails
+/// because of a bad setting.
+pub(crate) fn read_quality_preset(db_path: &Path) -> QualityPreset {
+    let name = read_setting(db_path, "whisper_quality_preset")
+        .unwrap_or_else(|| DEFAULT_QUALITY_PRESET.to_string());
+
+    if name == CUSTOM_QUALITY_PRESET {
+        if let Some(custom) = read_custom_quality_preset(db_path) {
+            return custom;
+        }
+    }
+
+    let presets = builtin_quality_presets();
+    presets
+        .iter()
+        .find(|p| p.name == name)
+        .or_else(|| presets.iter().find(|p| p.name == DEFAULT_QUALITY_PRESET))
+        .cloned()
+        .expect("default preset is always built in")
+}
+
+/// Build whisper FullParams for one `full()` call from a preset.
+pub(crate) fn build_full_params<'a>(preset: &QualityPreset, language: &'a str) -> FullParams<'a, 'a> {
+    let strategy = match preset.beam_size {
+        Some(beam_size) => SamplingStrategy::BeamSearch {
+            beam_size,
+            patience: -1.0,
+        },
+        None => SamplingStrategy::Greedy {
+            best_of: preset.best_of,
+        },
+    };
+
+    let mut params = FullParams::new(strategy);
+    params.set_language(Some(language));
+    params.set_temperature(preset.temperature);
+    params.set_temperature_inc(preset.temperature_inc);
+    params.set_no_speech_thold(preset.no_speech_thold);
+    params.set_entropy_thold(preset.entropy_thold);
+    params.set_logprob_thold(preset.logprob_thold);
+    params.set_suppress_blank(preset.suppress_blank);
+    // Per-token t0/t1 for word-level speaker attribution
+    params.set_token_timestamps(true);
+    if let Some(n_threads) = preset.n_threads {
+        params.set_n_threads(n_threads);
+    }
+    params.set_print_progress(false);
+    params.set_print_realtime(false);
+    params.set_print_special(false);
+    params.set_print_timestamps(false);
+    params
+}
+
+/// List the built-in presets plus the saved custom preset (if one exists).
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn get_quality_presets(app: tauri::AppHandle) -> Result<Vec<QualityPreset>, String> {
+    let db_path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Cannot resolve app data dir: {}", e))?
+        .join("binky.db");
+
+    let mut presets = builtin_quality_presets();
+    if let Some(custom) = read_custom_quality_preset(&db_path) {
+        presets.push(custom);
+    }
+    Ok(presets)
+}
+
+/// Select the active quality preset. Selecting "custom" requires `custom`
+/// parameters on first use; they are stored as JSON in `whisper_custom_preset`.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn set_quality_preset(
+    name: String,
+    custom: Option<QualityPreset>,
+    app: tauri::AppHandle,
+) -> Result<(), String> {
+    let db_path = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Cannot resolve app data dir: {}", e))?
+        .join("binky.db");
+
+    let is_builtin = builtin_quality_presets().iter().any(|p| p.name == name);
+    if !is_builtin && name != CUSTOM_QUALITY_PRESET {
+        return Err(format!("Unbekanntes Qualitätsprofil: {}", name));
+    }
+
+    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
+
+    if name == CUSTOM_QUALITY_PRESET {
+        match custom {
+            Some(preset) => {
+                let preset = sanitize_quality_preset(QualityPreset {
+                    name: CUSTOM_QUALITY_PRESET.to_string(),
+                    ..preset
+                });
+                let json = serde_json::to_string(&preset).map_err(|e| e.to_string())?;
+                conn.execute(
+                    "INSERT OR REPLACE INTO settings (key, value) VALUES ('whisper_custom_preset', ?1)",
+                    rusqlite::params![json],
+                )
+                .map_err(|e| e.to_string())?;
+            }
+            None if read_custom_quality_preset(&db_path).is_none() => {
+                return Err("Kein benutzerdefiniertes Qualitätsprofil gespeichert".to_string());
+            }
+            None => {}
         }
-        Err(_) => "de".to_string(),
     }
+
+    conn.execute(
+        "INSERT OR REPLACE INTO settings (key, value) VALUES ('whisper_quality_preset', ?1)",
+        rusqlite::params![name],
+    )
+    .map_err(|e| e.to_string())?;
+
+    Ok(())
 }
 
 /// Update episode transcription_status in SQLite.
-fn update_episode_status(db_path: &Path, episode_id: i64, status: &str, error_msg: Option<&str>) {
+pub(crate) fn update_episode_status(db_path: &Path, episode_id: i64, status: &str, error_msg: Option<&str>) {
     if let Ok(conn) = rusqlite::Connection::open(db_path) {
         if let Some(msg) = error_msg {
             let _ = conn.execute(
@@ -305,13 +623,14 @@ fn store_transcript(
     segments_json: &str,
     model_name: &str,
     language: &str,
+    quality_preset: &str,
 ) {
     if let Ok(conn) = rusqlite::Connection::open(db_path) {
         let _ = conn.execute(
             "INSERT OR REPLACE INTO transcripts \
-             (episode_id, full_text, segments_json, whisper_model, language, created_at) \
-             VALUES (?1, ?2, ?3, ?4, ?5, datetime('now'))",
-            rusqlite::params![episode_id, full_text, segments_json, model_name, language],
+             (episode_id, full_text, segments_json, whisper_model, language, quality_preset, created_at) \
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, datetime('now'))",
+            rusqlite::params![episode_id, full_text, segments_json, model_name, language, quality_preset],
         );
     }
 }
@@ -496,214 +815,87 @@ pub(crate) fn push_mono_frames(
     }
 }
 
-/// Process a single transcription job: download audio, decode PCM, run Whisper, store result.
-async fn process_episode(
-    job: &TranscriptionJob,
-    app: &tauri::AppHandle,
-    state: &Arc<TranscriptionState>,
-    on_event: &Channel<TranscriptionEvent>,
-    model_path: &std::path::PathBuf,
-    model_name: &str,
-    db_path: &std::path::PathBuf,
-) {
-    let episode_id = job.episode_id;
-
-    // Update status to 'downloading'
-    update_episode_status(db_path, episode_id, "downloading", None);
-
-    // Create cancellation token and register it in the queue
-    let cancel_token = CancellationToken::new();
-    {
-        let mut q = state.queue.lock().unwrap();
-        q.active_token = Some(cancel_token.clone());
-        q.active_episode_id = Some(episode_id);
-    }
-
-    // Resolve cache directory for temp audio file
-    let cache_dir = match app.path().app_cache_dir() {
-        Ok(d) => d,
-        Err(e) => {
-            let msg = format!("Cannot resolve cache dir: {}", e);
-            update_episode_status(db_path, episode_id, "error", Some(&msg));
-            let _ = on_event.send(TranscriptionEvent::Error { message: msg });
-            return;
-        }
-    };
-
-    if let Err(e) = tokio::fs::create_dir_all(&cache_dir).await {
-        let msg = format!("Cannot create cache dir: {}", e);
-        update_episode_status(db_path, episode_id, "error", Some(&msg));
-        let _ = on_event.send(TranscriptionEvent::Error { message: msg });
-        return;
-    }
-
-    let temp_path = cache_dir.join(format!("episode_{}.mp3", episode_id));
-    let temp_path_for_cleanup = temp_path.clone();
-
-    // Download audio with streaming progress
-    let response = match reqwest::get(&job.audio_url).await {
-        Ok(r) => r,
-        Err(e) => {
-            let msg = format!("Audio download failed: {}", e);
-            update_episode_status(db_path, episode_id, "error", Some(&msg));
-            let _ = on_event.send(TranscriptionEvent::Error { message: msg });
-            return;
-        }
-    };
-
-    let total_bytes = response.content_length().unwrap_or(0);
-    let mut stream = response.bytes_stream();
-    let mut downloaded_bytes: u64 = 0;
-
-    let mut file = match tokio::fs::File::create(&temp_path).await {
-        Ok(f) => f,
-        Err(e) => {
-            let msg = format!("Cannot create temp audio file: {}", e);
-            update_episode_status(db_path, episode_id, "error", Some(&msg));
-            let _ = on_event.send(TranscriptionEvent::Error { message: msg });
-            return;
-        }
+/// Transcribe stage of the job engine: decode the downloaded audio, run Whisper
+/// and store the transcript. Episode status columns and the Done/Error events
+/// are handled by the engine from the returned result.
+pub(crate) async fn run_transcription_stage(
+    paths: &BinkyPaths,
+    episode_id: i64,
+    audio_path: &Path,
+    cancel_token: &CancellationToken,
+    on_event: Option<&Progress<TranscriptionEvent>>,
+) -> StageResult {
+    let db_path = paths.db_path.as_path();
+    let (model_name, model_path) = match find_model(&paths.models_dir).await {
+        Some(found) => found,
+        None => return StageResult::Failed(NO_MODEL_MESSAGE.to_string()),
     };
 
-    while let Some(chunk_result) = stream.next().await {
-        // Check for cancellation between chunks
-        if cancel_token.is_cancelled() {
-            let _ = file.flush().await;
-            drop(file);
-            let _ = tokio::fs::remove_file(&temp_path_for_cleanup).await;
-            update_episode_status(db_path, episode_id, "not_started", None);
-            let _ = on_event.send(TranscriptionEvent::Cancelled);
-            let mut q = state.queue.lock().unwrap();
-            q.active_episode_id = None;
-            q.active_token = None;
-            return;
-        }
-
-        let chunk = match chunk_result {
-            Ok(c) => c,
-            Err(e) => {
-                let _ = file.flush().await;
-                drop(file);
-                let _ = tokio::fs::remove_file(&temp_path_for_cleanup).await;
-                let msg = format!("Download stream error: {}", e);
-                update_episode_status(db_path, episode_id, "error", Some(&msg));
-                let _ = on_event.send(TranscriptionEvent::Error { message: msg });
-                return;
-            }
-        };
-
-        if let Err(e) = file.write_all(&chunk).await {
-            let _ = tokio::fs::remove_file(&temp_path_for_cleanup).await;
-            let msg = format!("Failed to write audio chunk: {}", e);
-            update_episode_status(db_path, episode_id, "error", Some(&msg));
-            let _ = on_event.send(TranscriptionEvent::Error { message: msg });
-            return;
-        }
-
-        downloaded_bytes += chunk.len() as u64;
-        // Map download progress to 0-50 range
-        let download_percent = if total_bytes > 0 {
-            ((downloaded_bytes * 50) / total_bytes) as i32
-        } else {
-            25 // unknown total: show midpoint
-        };
-        let _ = on_event.send(TranscriptionEvent::Downloading {
-            percent: download_percent,
-        });
-    }
-
-    if let Err(e) = file.flush().await {
-        let _ = tokio::fs::remove_file(&temp_path_for_cleanup).await;
-        let msg = format!("Failed to flush audio file: {}", e);
-        update_episode_status(db_path, episode_id, "error", Some(&msg));
-        let _ = on_event.send(TranscriptionEvent::Error { message: msg });
-        return;
-    }
-    drop(file);
-
-    // Check cancellation after download
-    if cancel_token.is_cancelled() {
-        let _ = tokio::fs::remove_file(&temp_path_for_cleanup).await;
-        update_episode_status(db_path, episode_id, "not_started", None);
-        let _ = on_event.send(TranscriptionEvent::Cancelled);
-        let mut q = state.queue.lock().unwrap();
-        q.active_episode_id = None;
-        q.active_token = None;
-        return;
-    }
-
-    // Update status to 'transcribing'
-    update_episode_status(db_path, episode_id, "transcribing", None);
-
     // Decode MP3 to mono f32 PCM at 16 kHz
-    let audio_data = match decode_mp3_to_pcm(&temp_path) {
+    let audio_data = match decode_mp3_to_pcm(audio_path) {
         Ok(data) => data,
-        Err(e) => {
-            let _ = tokio::fs::remove_file(&temp_path_for_cleanup).await;
-            let msg = format!("Audio decode failed: {}", e);
-            update_episode_status(db_path, episode_id, "error", Some(&msg));
-            let _ = on_event.send(TranscriptionEvent::Error { message: msg });
-            return;
-        }
+        Err(e) => return StageResult::Failed(format!("Audio decode failed: {}", e)),
     };
 
-    // Read language setting from DB
-    let language = read_language_setting(db_path);
+    // Read language (episode override → global setting) and decoding preset from DB
+    let language_setting = resolve_episode_language(db_path, episode_id);
+    let mut preset = read_quality_preset(db_path);
+    let preset_name = preset.name.clone();
+    let hallucination_filter = crate::commands::hallucination::read_hallucination_filter(db_path);
+
+    // Thread count within the CPU budget (shared with parallel pipelines)
+    let budget = crate::commands::pipeline::read_cpu_budget(db_path);
+    preset.n_threads = Some(
+        budget.whisper_threads_for(preset.n_threads, crate::commands::pipeline::available_cores()),
+    );
 
     // Run Whisper in spawn_blocking (MUST NOT run on the async runtime thread)
     let model_path_str = model_path.to_string_lossy().to_string();
-    let model_name_owned = model_name.to_string();
-    let language_clone = language.clone();
+    let model_name_owned = model_name;
     let cancel_token_for_whisper = cancel_token.clone();
-    let on_event_for_whisper = on_event.clone();
+    let on_event_for_whisper = on_event.cloned();
 
-    let whisper_result = tauri::async_runtime::spawn_blocking(move || {
+    let whisper_result = tokio::task::spawn_blocking(move || {
         let ctx =
             WhisperContext::new_with_params(&model_path_str, WhisperContextParameters::default())
                 .map_err(|e| format!("Failed to load Whisper model: {}", e))?;
 
+        // "auto": detect once on the first chunk and use the result for every chunk,
+        // so a partly-English episode is not re-detected mid-way.
+        let language = if language_setting == "auto" {
+            let n_threads = preset.n_threads.unwrap_or(1) as usize;
+            detect_language(&ctx, &audio_data, n_threads)?
+        } else {
+            language_setting
+        };
+
         // Process audio in 20-minute chunks so whisper.cpp never needs to allocate
         // the mel spectrogram for the full 60-min episode at once (~115 MB → ~38 MB/chunk).
+        // Chunks overlap by 30 s and are stitched (see commands::chunking), so words
+        // at chunk edges are neither lost nor duplicated.
         //
         // IMPORTANT: a fresh WhisperState is created for each chunk. whisper.cpp stores
         // raw callback pointers (abort/progress) from FullParams inside the state object.
         // Reusing the same state across calls causes those pointers to go stale (dangling)
         // after FullParams is dropped at the end of each loop iteration → SIGSEGV.
-        const CHUNK_SAMPLES: usize = 20 * 60 * 16_000; // 20 min at 16 kHz
-        let num_chunks = (audio_data.len() + CHUNK_SAMPLES - 1) / CHUNK_SAMPLES;
+        let chunks = chunk_ranges(audio_data.len(), CHUNK_SAMPLES, CHUNK_OVERLAP_SAMPLES);
+        let num_chunks = chunks.len();
 
-        let mut full_text = String::new();
-        let mut segments_arr: Vec<serde_json::Value> = Vec::new();
+        let mut stitched: Vec<ChunkSegment> = Vec::new();
+        let mut prev_chunk_end_ms: i64 = 0;
 
-        for chunk_idx in 0..num_chunks {
+        for (chunk_idx, &(start, end)) in chunks.iter().enumerate() {
             // Allow clean cancellation between chunks without waiting for the
             // next full() call — the abort callback handles in-chunk cancellation.
             if cancel_token_for_whisper.is_cancelled() {
                 break;
             }
 
-            let start = chunk_idx * CHUNK_SAMPLES;
-            let end = (start + CHUNK_SAMPLES).min(audio_data.len());
             let chunk = &audio_data[start..end];
             // Timestamp offset for this chunk in centiseconds (samples ÷ 160 at 16 kHz)
             let chunk_offset_cs = (start / 160) as i64;
 
-            let mut params = FullParams::new(SamplingStrategy::Greedy { best_of: 1 });
-            params.set_language(Some(language_clone.as_str()));
-            params.set_print_progress(false);
-            params.set_print_realtime(false);
-            params.set_print_special(false);
-            params.set_print_timestamps(false);
-
-            // No abort callback and no progress callback.
-            //
-            // whisper-rs 0.15 set_abort_callback_safe has a type-confusion bug:
-            //   stored data:    *mut Box<Box<dyn FnMut() -> bool>>  (double-boxed fat ptr)
-            //   trampoline casts as: *mut F  (original concrete closure type)
-            // These differ in layout → UB → SIGSEGV / SIGABRT during transcription.
-            //
-            // Cancellation is handled between chunks via the token check above.
-            // Progress is sent from Rust after each chunk completes (safe, no FFI).
+            let params = build_full_params(&preset, language.as_str());
 
             // Fresh state per chunk: ctx holds model weights (not re-read from disk),
             // state holds only the KV cache and runtime buffers — cheap to recreate.
@@ -711,193 +903,185 @@ async fn process_episode(
                 .create_state()
                 .map_err(|e| format!("Failed to create Whisper state (chunk {}): {}", chunk_idx + 1, e))?;
 
-            whisper_state
-                .full(params, chunk)
-                .map_err(|e| format!("Whisper failed (chunk {}/{}): {}", chunk_idx + 1, num_chunks, e))?;
-
-            // Collect segments with timestamp offset so they align to the full episode.
-            // Whisper often repeats the tail of the previous chunk at the start of the
-            // next one — deduplicate via is_duplicate_segment().
-            for segment in whisper_state.as_iter() {
-                let text = segment.to_string();
-                let t0 = segment.start_timestamp() + chunk_offset_cs;
-                let t1 = segment.end_timestamp() + chunk_offset_cs;
-
-                if is_duplicate_segment(&text, &segments_arr) {
-                    continue;
+            // Abort polling on the token and per-percent progress through our own
+            // trampolines (see commands::whisper_callbacks — whisper-rs' safe
+            // callback setters are unsound). Overall progress: 50–100%.
+            let mut last_sent = -1;
+            let mut on_chunk_progress = |chunk_pct: i32| {
+                let percent = 50 + ((chunk_idx as i32 * 100 + chunk_pct) * 50 / (num_chunks as i32 * 100));
+                if percent != last_sent {
+                    last_sent = percent;
+                    if let Some(ch) = &on_event_for_whisper {
+                        ch.send(TranscriptionEvent::Progress { percent });
+                    }
                 }
+            };
+            match full_with_callbacks(
+                &mut whisper_state,
+                params,
+                chunk,
+                &cancel_token_for_whisper,
+                &mut on_chunk_progress,
+            ) {
+                Ok(()) => {}
+                Err(WhisperRunError::Cancelled) => break,
+                Err(WhisperRunError::Failed(e)) => {
+                    return Err(format!("Whisper failed (chunk {}/{}): {}", chunk_idx + 1, num_chunks, e));
+                }
+            }
 
-                full_text.push_str(&text);
-                segments_arr.push(serde_json::json!({
-                    "text": text,
-                    "start_ms": t0 * 10,
-                    "end_ms": t1 * 10
-                }));
+            // Collect segments with timestamp offset so they align to the full episode.
+            let eot = ctx.token_eot();
+            let chunk_segments: Vec<ChunkSegment> = whisper_state
+                .as_iter()
+                .map(|segment| {
+                    // Text tokens only — timestamp and control tokens sort after EOT
+                    let token_logprobs: Vec<f32> = (0..segment.n_tokens())
+                        .filter_map(|i| segment.get_token(i))
+                        .map(|token| token.token_data())
+                        .filter(|data| data.id < eot)
+                        .map(|data| data.plog)
+                        .collect();
+                    let words = crate::commands::attribution::words_from_tokens(
+                        (0..segment.n_tokens())
+                            .filter_map(|i| segment.get_token(i))
+                            .filter(|token| token.token_data().id < eot)
+                            .map(|token| {
+                                let data = token.token_data();
+                                (
+                                    token.to_bytes().map(|b| b.to_vec()).unwrap_or_default(),
+                                    (data.t0 + chunk_offset_cs) * 10,
+                                    (data.t1 + chunk_offset_cs) * 10,
+                                )
+                            }),
+                    );
+                    ChunkSegment {
+                        text: segment.to_string(),
+                        start_ms: (segment.start_timestamp() + chunk_offset_cs) * 10,
+                        end_ms: (segment.end_timestamp() + chunk_offset_cs) * 10,
+                        avg_logprob: crate::commands::hallucination::average_logprob(&token_logprobs),
+                        no_speech_prob: segment.no_speech_probability(),
+                        words,
+                    }
+                })
+                .collect();
+            // whisper_state drops after this iteration — callbacks + buffers freed cleanly
+
+            if chunk_idx == 0 {
+                stitched = chunk_segments;
+            } else {
+                stitch_chunk(&mut stitched, chunk_segments, chunk_offset_cs * 10, prev_chunk_end_ms);
             }
-            // whisper_state drops here — callbacks + buffers freed cleanly
+            prev_chunk_end_ms = (end / 16) as i64;
+        }
 
-            // Send progress from Rust (safe — no FFI boundary crossing).
-            // Chunks: 50% → 67% → 83% → 100% for a 3-chunk episode.
-            let progress_pct = 50 + ((chunk_idx + 1) * 50 / num_chunks) as i32;
-            let _ = on_event_for_whisper.send(TranscriptionEvent::Progress { percent: progress_pct });
+        // Hallucinations (including repeats Whisper produces after silence) are
+        // kept with their reason but left out of full_text.
+        let mut full_text = String::new();
+        let mut segments_arr: Vec<serde_json::Value> = Vec::new();
+        for segment in stitched {
+            let flagged = crate::commands::hallucination::classify_segment(
+                &segment.text,
+                segment.avg_logprob,
+                segment.no_speech_prob,
+                &segments_arr,
+                &hallucination_filter,
+            );
+            let mut entry = serde_json::json!({
+                "text": segment.text,
+                "start_ms": segment.start_ms,
+                "end_ms": segment.end_ms
+            });
+            if !segment.words.is_empty() {
+                entry["words"] = serde_json::to_value(&segment.words).unwrap_or_default();
+            }
+            match flagged {
+                Some(kind) => entry["hallucination"] = kind.as_str().into(),
+                None => full_text.push_str(&segment.text),
+            }
+            segments_arr.push(entry);
         }
 
         let segments_json = serde_json::to_string(&segments_arr).unwrap_or_default();
-        Ok::<(String, String, String), String>((full_text, segments_json, model_name_owned))
+        Ok::<(String, String, String, String), String>((
+            full_text,
+            segments_json,
+            model_name_owned,
+            language,
+        ))
     })
     .await;
 
-    // Always delete temp audio file
-    let _ = tokio::fs::remove_file(&temp_path_for_cleanup).await;
-
-    // Check if cancelled (abort callback fired before/during whisper)
+    // Check if cancelled (between chunks)
     if cancel_token.is_cancelled() {
-        update_episode_status(db_path, episode_id, "not_started", None);
-        let _ = on_event.send(TranscriptionEvent::Cancelled);
-        let mut q = state.queue.lock().unwrap();
-        q.active_episode_id = None;
-        q.active_token = None;
-        return;
+        return StageResult::Cancelled;
     }
 
     match whisper_result {
-        Ok(Ok((full_text, segments_json, used_model))) => {
-            store_transcript(db_path, episode_id, &full_text, &segments_json, &used_model, &language);
-            update_episode_status(db_path, episode_id, "done", None);
-            let _ = on_event.send(TranscriptionEvent::Done { episode_id });
-
-            // Chain diarization automatically when models are available
-            if let Some(diarize_state) = app.try_state::<Arc<crate::state::diarization_queue::DiarizationState>>() {
-                let diarize_arc = diarize_state.inner().clone();
-                let audio_url_for_diarize = job.audio_url.clone();
-                let episode_id_for_diarize = episode_id;
-                let app_for_diarize = app.clone();
-                tauri::async_runtime::spawn(async move {
-                    crate::commands::diarization::enqueue_diarization_internal(
-                        episode_id_for_diarize,
-                        audio_url_for_diarize,
-                        &app_for_diarize,
-                        &diarize_arc,
-                    )
-                    .await;
-                });
-            }
-        }
-        Ok(Err(e)) => {
-            update_episode_status(db_path, episode_id, "error", Some(&e));
-            let _ = on_event.send(TranscriptionEvent::Error { message: e });
-        }
-        Err(e) => {
-            let msg = format!("Whisper task panicked: {}", e);
-            update_episode_status(db_path, episode_id, "error", Some(&msg));
-            let _ = on_event.send(TranscriptionEvent::Error { message: msg });
+        Ok(Ok((full_text, segments_json, used_model, language))) => {
+            store_transcript(
+                db_path,
+                episode_id,
+                &full_text,
+                &segments_json,
+                &used_model,
+                &language,
+                &preset_name,
+            );
+            StageResult::Done
         }
+        Ok(Err(e)) => StageResult::Failed(e),
+        Err(e) => StageResult::Failed(format!("Whisper task panicked: {}", e)),
     }
-
-    // Clear active episode tracking
-    let mut q = state.queue.lock().unwrap();
-    q.active_episode_id = None;
-    q.active_token = None;
 }
 
-/// Start transcribing an episode. Adds the job to the queue and starts the
-/// processing loop if it is not already running.
+/// Start transcribing an episode. Queues the transcription pipeline in the job
+/// engine (diarization, text backfill and indexing follow automatically).
+#[cfg(feature = "app")]
 #[tauri::command]
 pub async fn start_transcription(
     episode_id: i64,
     audio_url: String,
     on_event: Channel<TranscriptionEvent>,
     app: tauri::AppHandle,
-    state: tauri::State<'_, Arc<TranscriptionState>>,
+    state: tauri::State<'_, Arc<EngineState>>,
 ) -> Result<(), String> {
-    // Check that a model is downloaded
-    let (model_name, model_path) = find_model(&app).await.ok_or_else(|| {
-        "Kein Whisper-Modell heruntergeladen. Bitte zuerst ein Modell in den Einstellungen herunterladen.".to_string()
-    })?;
-
-    // Resolve the SQLite DB path (same file tauri-plugin-sql uses)
-    let db_path = app
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Cannot resolve app data dir: {}", e))?
-        .join("binky.db");
-
-    // Enqueue the job; detect if we need to start the processing loop
-    let already_processing = {
-        let mut q = state.queue.lock().unwrap();
-        q.enqueue(TranscriptionJob {
-            episode_id,
-            audio_url,
-        });
-        let was = q.is_processing;
-        if !was {
-            q.is_processing = true;
-        }
-        was
-    };
-
-    // Mark the episode as 'queued' immediately so the UI reflects it
-    update_episode_status(&db_path, episode_id, "queued", None);
-
-    if already_processing {
-        // Loop already running — it will pick up the new job automatically
-        return Ok(());
-    }
-
-    // Clone the Arc so the async task owns it independently
-    let state_arc: Arc<TranscriptionState> = state.inner().clone();
+    let paths = BinkyPaths::from_app(&app)?;
 
-    tauri::async_runtime::spawn(async move {
-        loop {
-            let job = {
-                let mut q = state_arc.queue.lock().unwrap();
-                q.dequeue()
-            };
-
-            match job {
-                None => {
-                    // Queue empty — stop processing
-                    state_arc.queue.lock().unwrap().is_processing = false;
-                    break;
-                }
-                Some(j) => {
-                    process_episode(
-                        &j,
-                        &app,
-                        &state_arc,
-                        &on_event,
-                        &model_path,
-                        &model_name,
-                        &db_path,
-                    )
-                    .await;
-                }
-            }
-        }
-    });
+    // Check that a model is downloaded
+    find_model(&paths.models_dir).await.ok_or_else(|| NO_MODEL_MESSAGE.to_string())?;
 
-    Ok(())
+    let stages = crate::commands::pipeline::transcription_stages(&paths.db_path);
+    let mut job = PipelineJob::new(episode_id, audio_url, &stages);
+    job.transcription_events = Some(Arc::new(on_event));
+    crate::commands::pipeline::enqueue_job(&paths, state.inner(), job)
 }
 
-/// Cancel the currently active transcription.
+/// Cancel the pipeline of the episode that is currently being transcribed.
+#[cfg(feature = "app")]
 #[tauri::command]
 pub async fn cancel_transcription(
-    state: tauri::State<'_, Arc<TranscriptionState>>,
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<EngineState>>,
 ) -> Result<(), String> {
-    state.queue.lock().unwrap().cancel_active();
+    let active = state.engine.lock().unwrap().active_for(Stage::Transcribe);
+    if let Some(episode_id) = active {
+        crate::commands::pipeline::cancel_job(&BinkyPaths::from_app(&app)?, state.inner(), episode_id);
+    }
     Ok(())
 }
 
 /// Return the current queue status.
+#[cfg(feature = "app")]
 #[tauri::command]
 pub async fn get_queue_status(
-    state: tauri::State<'_, Arc<TranscriptionState>>,
+    state: tauri::State<'_, Arc<EngineState>>,
 ) -> Result<QueueStatus, String> {
-    let q = state.queue.lock().unwrap();
+    let engine = state.engine.lock().unwrap();
     Ok(QueueStatus {
-        active_episode_id: q.active_episode_id,
-        queue_length: q.queue_len(),
-        is_processing: q.is_processing,
+        active_episode_id: engine.active_for(Stage::Transcribe),
+        queue_length: engine.waiting_for(Stage::Transcribe),
+        is_processing: engine.is_processing(),
     })
 }
 
@@ -906,8 +1090,8 @@ pub async fn get_queue_status(
 // ─────────────────────────────────────────────────────────────────────────────
 
 /// Returns true if `text` (normalised: trimmed + lowercased) already appears
-/// in the last 20 entries of `segments`. Used to drop Whisper's chunk-boundary
-/// hallucinations where it repeats the tail of the previous chunk verbatim.
+/// in the last 20 entries of `segments`. Used to flag Whisper hallucinations
+/// where it repeats a recent segment verbatim (chunk seams are stitched separately).
 pub(crate) fn is_duplicate_segment(text: &str, segments: &[serde_json::Value]) -> bool {
     let normalized = text.trim().to_lowercase();
     if normalized.is_empty() {
@@ -0,0 +1,306 @@
+use crate::commands::overlap::{load_turns, Turn};
+use crate::models::diarization::{EpisodeTurnTaking, SpeakerTurnTaking};
+use crate::paths::BinkyPaths;
+use std::collections::{BTreeMap, HashMap};
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Turn-taking
+//
+// A speaker holds the floor until another speaker talks past the end of their
+// turn; consecutive segments of one speaker form a single turn, and short
+// segments that lie inside someone else's turn ("mhm", laughter) never take
+// the floor. Each floor change is either an interruption (the new speaker
+// starts before, or right as, the previous one stops) or a reply after a gap.
+// Results are cached per episode in turn_taking_stats (migration 030), whose
+// triggers drop an episode's row whenever its segments change.
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// A floor change with less silence than this, or with overlap, counts as the
+/// new speaker interrupting the previous one.
+const INTERRUPTION_GAP_MS: i64 = 200;
+
+/// Longer silences are breaks (jingles, cuts), not time taken to reply.
+const MAX_REPLY_GAP_MS: i64 = 5_000;
+
+/// One turn on the floor and the silence before it (negative: overlap,
+/// None: first turn of the episode).
+struct FloorTurn<'a> {
+    speaker: &'a str,
+    start_ms: i64,
+    end_ms: i64,
+    gap_ms: Option<i64>,
+}
+
+fn floor_turns(segments: &[Turn]) -> Vec<FloorTurn<'_>> {
+    let mut sorted: Vec<&Turn> = segments.iter().filter(|(start, end, _)| end > start).collect();
+    sorted.sort_by_key(|(start, end, _)| (*start, *end));
+
+    let mut turns: Vec<FloorTurn> = Vec::new();
+    for (start, end, speaker) in sorted {
+        match turns.last_mut() {
+            Some(last) if last.speaker == speaker => last.end_ms = last.end_ms.max(*end),
+            // Inside the current turn: a backchannel, not a floor change
+            Some(last) if *end <= last.end_ms => {}
+            last => {
+                let gap_ms = last.map(|last| start - last.end_ms);
+                turns.push(FloorTurn {
+                    speaker,
+                    start_ms: *start,
+                    end_ms: *end,
+                    gap_ms,
+                });
+            }
+        }
+    }
+    turns
+}
+
+/// Median of unsorted values; 0 when empty.
+fn median(values: &mut [i64]) -> i64 {
+    if values.is_empty() {
+        return 0;
+    }
+    values.sort_unstable();
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2
+    } else {
+        values[mid]
+    }
+}
+
+fn mean(values: &[i64]) -> Option<i64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().sum::<i64>() / values.len() as i64)
+    }
+}
+
+#[derive(Default)]
+struct SpeakerTally {
+    lengths: Vec<i64>,
+    interrupts: BTreeMap<String, i64>,
+    interrupted: i64,
+    reply_gaps: Vec<i64>,
+}
+
+/// Turn-taking statistics of one episode from its (effective-speaker) segments.
+pub(crate) fn turn_taking(episode_id: i64, segments: &[Turn]) -> EpisodeTurnTaking {
+    let turns = floor_turns(segments);
+
+    let mut tally: BTreeMap<&str, SpeakerTally> = BTreeMap::new();
+    let mut previous: Option<&str> = None;
+    for turn in &turns {
+        tally.entry(turn.speaker).or_default().lengths.push(turn.end_ms - turn.start_ms);
+        if let (Some(gap_ms), Some(previous)) = (turn.gap_ms, previous) {
+            if gap_ms < INTERRUPTION_GAP_MS {
+                let entry = tally.entry(turn.speaker).or_default();
+                *entry.interrupts.entry(previous.to_string()).or_insert(0) += 1;
+                tally.entry(previous).or_default().interrupted += 1;
+            } else if gap_ms <= MAX_REPLY_GAP_MS {
+                tally.entry(turn.speaker).or_default().reply_gaps.push(gap_ms);
+            }
+        }
+        previous = Some(turn.speaker);
+    }
+
+    let mut all_lengths: Vec<i64> = turns.iter().map(|t| t.end_ms - t.start_ms).collect();
+    let all_gaps: Vec<i64> = tally.values().flat_map(|t| t.reply_gaps.iter().copied()).collect();
+
+    let speakers: Vec<SpeakerTurnTaking> = tally
+        .into_iter()
+        .map(|(speaker, mut t)| SpeakerTurnTaking {
+            speaker: speaker.to_string(),
+            turns: t.lengths.len() as i64,
+            median_turn_ms: median(&mut t.lengths),
+            longest_turn_ms: t.lengths.iter().copied().max().unwrap_or(0),
+            interrupts: t.interrupts,
+            interrupted: t.interrupted,
+            avg_reply_gap_ms: mean(&t.reply_gaps),
+        })
+        .collect();
+
+    EpisodeTurnTaking {
+        episode_id,
+        turns: turns.len() as i64,
+        median_turn_ms: median(&mut all_lengths),
+        longest_turn_ms: all_lengths.iter().copied().max().unwrap_or(0),
+        interruptions: speakers.iter().map(|s| s.interrupted).sum(),
+        avg_reply_gap_ms: mean(&all_gaps),
+        speakers,
+    }
+}
+
+/// Cached statistics of one episode, or of every episode, by episode id.
+/// Rows that no longer parse are left out and get recomputed.
+fn load_cached(
+    conn: &rusqlite::Connection,
+    episode_id: Option<i64>,
+) -> Result<HashMap<i64, EpisodeTurnTaking>, String> {
+    let mut stmt = conn
+        .prepare("SELECT episode_id, stats FROM turn_taking_stats WHERE ?1 IS NULL OR episode_id = ?1")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params![episode_id], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+        })
+        .map_err(|e| e.to_string())?;
+    Ok(rows
+        .filter_map(|r| r.ok())
+        .filter_map(|(id, json)| serde_json::from_str(&json).ok().map(|stats| (id, stats)))
+        .collect())
+}
+
+/// Turn-taking statistics of one episode, or of every diarized episode when
+/// `episode_id` is None, in episode id order. Episodes without a cached row
+/// are computed and cached.
+#[tauri::command]
+pub async fn get_turn_taking(
+    episode_id: Option<i64>,
+    app: tauri::AppHandle,
+) -> Result<Vec<EpisodeTurnTaking>, String> {
+    let paths = BinkyPaths::from_app(&app)?;
+    let conn = rusqlite::Connection::open(&paths.db_path).map_err(|e| e.to_string())?;
+    let mut cached = load_cached(&conn, episode_id)?;
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT DISTINCT episode_id FROM diarization_segments \
+             WHERE ?1 IS NULL OR episode_id = ?1 ORDER BY episode_id",
+        )
+        .map_err(|e| e.to_string())?;
+    let episode_ids: Vec<i64> = stmt
+        .query_map(rusqlite::params![episode_id], |row| row.get(0))
+        .map_err(|e| e.to_string())?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut result = Vec::with_capacity(episode_ids.len());
+    for episode_id in episode_ids {
+        if let Some(stats) = cached.remove(&episode_id) {
+            result.push(stats);
+            continue;
+        }
+        let segments: Vec<Turn> = load_turns(&conn, Some(episode_id))?
+            .into_iter()
+            .map(|(_, turn)| turn)
+            .collect();
+        let stats = turn_taking(episode_id, &segments);
+        let json = serde_json::to_string(&stats).map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT OR REPLACE INTO turn_taking_stats (episode_id, stats, computed_at) \
+             VALUES (?1, ?2, datetime('now'))",
+            rusqlite::params![episode_id, json],
+        )
+        .map_err(|e| format!("Gesprächsstatistik konnte nicht gespeichert werden: {}", e))?;
+        result.push(stats);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn turn(start_ms: i64, end_ms: i64, speaker: &str) -> Turn {
+        (start_ms, end_ms, speaker.to_string())
+    }
+
+    #[test]
+    fn counts_turns_interruptions_and_reply_gaps() {
+        let segments = vec![
+            turn(0, 4_000, "SPEAKER_0"),
+            turn(4_500, 10_000, "SPEAKER_0"),
+            // Backchannel inside SPEAKER_0's turn
+            turn(6_000, 6_500, "SPEAKER_1"),
+            // Reply after one second
+            turn(11_000, 14_000, "SPEAKER_1"),
+            // Interruption by overlap
+            turn(13_000, 20_000, "SPEAKER_0"),
+            // Interruption with a near-zero gap
+            turn(20_100, 22_000, "SPEAKER_1"),
+            // Break, not a reply
+            turn(40_000, 42_000, "SPEAKER_0"),
+        ];
+        let stats = turn_taking(7, &segments);
+        assert_eq!(stats.turns, 5);
+        assert_eq!(stats.interruptions, 2);
+        assert_eq!(stats.longest_turn_ms, 10_000);
+        assert_eq!(stats.median_turn_ms, 3_000);
+        assert_eq!(stats.avg_reply_gap_ms, Some(1_000));
+
+        let host0 = &stats.speakers[0];
+        assert_eq!((host0.turns, host0.median_turn_ms, host0.longest_turn_ms), (3, 7_000, 10_000));
+        assert_eq!(host0.interrupts.get("SPEAKER_1"), Some(&1));
+        assert_eq!(host0.interrupted, 1);
+        assert_eq!(host0.avg_reply_gap_ms, None);
+
+        let host1 = &stats.speakers[1];
+        assert_eq!(host1.turns, 2);
+        assert_eq!(host1.interrupts.get("SPEAKER_0"), Some(&1));
+        assert_eq!(host1.avg_reply_gap_ms, Some(1_000));
+    }
+
+    #[test]
+    fn backchannel_running_past_the_turn_takes_the_floor() {
+        let segments = vec![
+            turn(0, 10_000, "SPEAKER_0"),
+            // Starts inside SPEAKER_0's turn but outlasts it
+            turn(9_800, 10_300, "SPEAKER_1"),
+            turn(10_400, 15_000, "SPEAKER_0"),
+        ];
+        let stats = turn_taking(7, &segments);
+        assert_eq!(stats.turns, 3);
+        assert_eq!(stats.speakers[1].interrupts.get("SPEAKER_0"), Some(&1));
+        assert_eq!(stats.speakers[0].interrupted, 1);
+    }
+
+    fn cached_episodes(conn: &rusqlite::Connection) -> Vec<i64> {
+        let mut stmt = conn
+            .prepare("SELECT episode_id FROM turn_taking_stats ORDER BY episode_id")
+            .unwrap();
+        let rows = stmt.query_map([], |row| row.get(0)).unwrap();
+        rows.map(|r| r.unwrap()).collect()
+    }
+
+    fn cache(conn: &rusqlite::Connection) {
+        conn.execute_batch(
+            "INSERT OR REPLACE INTO turn_taking_stats (episode_id, stats) VALUES (1, '{}'), (2, '{}');",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn segment_changes_drop_the_cached_stats_of_their_episode() {
+        let mut conn = rusqlite::Connection::open_in_memory().unwrap();
+        crate::migrations::apply_pending(&mut conn).unwrap();
+        conn.execute_batch(
+            "INSERT INTO episodes (id, title) VALUES (1, 'Folge 1'), (2, 'Folge 2');
+             INSERT INTO diarization_segments (id, episode_id, start_ms, end_ms, speaker_label, text)
+             VALUES (1, 1, 0, 4000, 'SPEAKER_0', 'hallo'), (2, 2, 0, 4000, 'SPEAKER_0', 'moin');",
+        )
+        .unwrap();
+
+        cache(&conn);
+        // Text edits don't change turns
+        conn.execute("UPDATE diarization_segments SET text = 'servus' WHERE id = 1", []).unwrap();
+        assert_eq!(cached_episodes(&conn), vec![1, 2]);
+        conn.execute("UPDATE diarization_segments SET corrected_speaker = 'SPEAKER_1' WHERE id = 1", [])
+            .unwrap();
+        assert_eq!(cached_episodes(&conn), vec![2]);
+
+        cache(&conn);
+        conn.execute(
+            "INSERT INTO diarization_segments (episode_id, start_ms, end_ms, speaker_label) \
+             VALUES (1, 4000, 8000, 'SPEAKER_1')",
+            [],
+        )
+        .unwrap();
+        assert_eq!(cached_episodes(&conn), vec![2]);
+
+        cache(&conn);
+        conn.execute("DELETE FROM diarization_segments WHERE id = 2", []).unwrap();
+        assert_eq!(cached_episodes(&conn), vec![1]);
+    }
+}
@@ -0,0 +1,478 @@
+use crate::commands::diarization_models::DiarizationSetup;
+use crate::models::diarization::DiarizationSegment;
+#[cfg(feature = "app")]
+use crate::models::diarization::{DiarizationEvent, HostVoiceprint};
+#[cfg(feature = "app")]
+use crate::paths::BinkyPaths;
+use sherpa_rs::speaker_id::{EmbeddingExtractor, ExtractorConfig};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+#[cfg(feature = "app")]
+use tauri::ipc::Channel;
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Host voiceprints
+//
+// A host is enrolled from segments confirmed as theirs: each segment is turned
+// into an embedding with the model diarization uses, and the normalised mean
+// is stored in the host's global host_profiles row together with the model id
+// (voiceprints of another model are ignored). When an episode is diarized,
+// every segment is embedded the same way (and stored, see clustering.rs); each
+// cluster's centroid is matched to the enrolled hosts by cosine similarity
+// (see label_speakers).
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Shorter segments give unreliable embeddings.
+const MIN_EMBEDDING_MS: i64 = 2_000;
+
+/// Only the first part of a long segment is embedded; sherpa windows can
+/// envelop short interjections of the other speaker.
+const MAX_EMBEDDING_MS: i64 = 15_000;
+
+/// Diarization segments shorter than this are stored without an embedding.
+const MIN_SEGMENT_EMBEDDING_MS: i64 = 1_000;
+
+/// Segments embedded per episode when enrolling a host.
+#[cfg(feature = "app")]
+const ENROLL_SEGMENTS_PER_EPISODE: usize = 20;
+
+/// Episodes used for enrollment when none are given.
+#[cfg(feature = "app")]
+const ENROLL_MAX_EPISODES: usize = 3;
+
+/// A cluster below this cosine similarity is not taken for an enrolled host
+/// (sherpa-onnx's default speaker match threshold).
+pub(crate) const MIN_HOST_SIMILARITY: f32 = 0.5;
+
+const HOST_LABELS: [&str; 2] = ["SPEAKER_0", "SPEAKER_1"];
+
+/// Embeddings are stored as little-endian f32s.
+pub(crate) fn encode_embedding(embedding: &[f32]) -> Vec<u8> {
+    embedding.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+pub(crate) fn decode_embedding(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+pub(crate) fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+pub(crate) fn normalized(embedding: &[f32]) -> Vec<f32> {
+    let norm = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm == 0.0 {
+        embedding.to_vec()
+    } else {
+        embedding.iter().map(|x| x / norm).collect()
+    }
+}
+
+/// Mean of the normalised embeddings, normalised again. None for no input.
+pub(crate) fn mean_embedding(embeddings: &[Vec<f32>]) -> Option<Vec<f32>> {
+    let dim = embeddings.first()?.len();
+    let mut sum = vec![0.0f32; dim];
+    for embedding in embeddings.iter().filter(|e| e.len() == dim) {
+        for (s, v) in sum.iter_mut().zip(normalized(embedding)) {
+            *s += v;
+        }
+    }
+    Some(normalized(&sum))
+}
+
+/// The longest `max` ranges of at least MIN_EMBEDDING_MS, cut to MAX_EMBEDDING_MS.
+#[cfg(feature = "app")]
+pub(crate) fn pick_ranges(mut ranges: Vec<(i64, i64)>, max: usize) -> Vec<(i64, i64)> {
+    ranges.retain(|(start, end)| end - start >= MIN_EMBEDDING_MS);
+    ranges.sort_by_key(|(start, end)| std::cmp::Reverse(end - start));
+    ranges.truncate(max);
+    ranges
+        .into_iter()
+        .map(|(start, end)| (start, end.min(start + MAX_EMBEDDING_MS)))
+        .collect()
+}
+
+pub(crate) fn embedding_extractor(setup: &DiarizationSetup) -> Result<EmbeddingExtractor, String> {
+    EmbeddingExtractor::new(ExtractorConfig {
+        model: setup.emb_path.to_string_lossy().to_string(),
+        provider: Some(setup.provider.clone()),
+        num_threads: Some(setup.num_threads.max(1) as usize),
+        debug: false,
+    })
+    .map_err(|e| format!("Failed to initialize embedding extractor: {:?}", e))
+}
+
+/// Embed `[start_ms, end_ms)` of 16 kHz `samples`. None if the range is out
+/// of bounds or the extractor fails on it.
+pub(crate) fn embed_range(
+    extractor: &mut EmbeddingExtractor,
+    samples: &[f32],
+    start_ms: i64,
+    end_ms: i64,
+) -> Option<Vec<f32>> {
+    let from = (start_ms.max(0) * 16) as usize;
+    let to = ((end_ms.max(0) * 16) as usize).min(samples.len());
+    if from >= to {
+        return None;
+    }
+    extractor
+        .compute_speaker_embedding(samples[from..to].to_vec(), 16_000)
+        .ok()
+}
+
+/// Embed every diarization segment of at least MIN_SEGMENT_EMBEDDING_MS
+/// (cut to MAX_EMBEDDING_MS); the others keep `embedding: None`.
+pub(crate) fn embed_segments(
+    extractor: &mut EmbeddingExtractor,
+    samples: &[f32],
+    segments: &mut [DiarizationSegment],
+) {
+    for s in segments.iter_mut() {
+        if s.end_ms - s.start_ms >= MIN_SEGMENT_EMBEDDING_MS {
+            let end_ms = s.end_ms.min(s.start_ms + MAX_EMBEDDING_MS);
+            s.embedding = embed_range(extractor, samples, s.start_ms, end_ms);
+        }
+    }
+}
+
+/// Centroid embedding of every cluster (raw sherpa label → embedding), from
+/// the segment embeddings. Segments of at least MIN_EMBEDDING_MS are used when
+/// a cluster has any, all embedded ones otherwise.
+pub(crate) fn cluster_centroids(segments: &[DiarizationSegment]) -> Vec<(String, Vec<f32>)> {
+    let mut embedded: HashMap<&str, Vec<(i64, Vec<f32>)>> = HashMap::new();
+    for s in segments {
+        if let Some(embedding) = &s.embedding {
+            embedded
+                .entry(s.speaker_label.as_str())
+                .or_default()
+                .push((s.end_ms - s.start_ms, embedding.clone()));
+        }
+    }
+
+    let mut centroids: Vec<(String, Vec<f32>)> = embedded
+        .into_iter()
+        .filter_map(|(label, embeddings)| {
+            let long: Vec<Vec<f32>> = embeddings
+                .iter()
+                .filter(|(ms, _)| *ms >= MIN_EMBEDDING_MS)
+                .map(|(_, e)| e.clone())
+                .collect();
+            let used = if long.is_empty() {
+                embeddings.into_iter().map(|(_, e)| e).collect()
+            } else {
+                long
+            };
+            mean_embedding(&used).map(|centroid| (label.to_string(), centroid))
+        })
+        .collect();
+    centroids.sort_by(|a, b| a.0.cmp(&b.0));
+    centroids
+}
+
+/// Assign clusters to enrolled hosts, most similar pairs first, each host and
+/// cluster at most once. Returns raw cluster label → (host index, similarity).
+pub(crate) fn match_hosts(
+    centroids: &[(String, Vec<f32>)],
+    voiceprints: &[(usize, Vec<f32>)],
+) -> HashMap<String, (usize, f32)> {
+    let mut pairs: Vec<(f32, &str, usize)> = centroids
+        .iter()
+        .flat_map(|(label, centroid)| {
+            voiceprints
+                .iter()
+                .map(move |(host, voiceprint)| (cosine_similarity(centroid, voiceprint), label.as_str(), *host))
+        })
+        .filter(|(similarity, _, _)| *similarity >= MIN_HOST_SIMILARITY)
+        .collect();
+    pairs.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    let mut matched = HashMap::new();
+    let mut hosts_taken = HashSet::new();
+    for (similarity, label, host) in pairs {
+        if matched.contains_key(label) || hosts_taken.contains(&host) {
+            continue;
+        }
+        hosts_taken.insert(host);
+        matched.insert(label.to_string(), (host, similarity));
+    }
+    matched
+}
+
+/// Enrolled voiceprints as (host index, embedding). Only voiceprints of the
+/// selected embedding model: embeddings of different models don't compare.
+pub(crate) fn load_voiceprints(db_path: &Path) -> Vec<(usize, Vec<f32>)> {
+    let (_, embedding) = crate::commands::diarization_models::selected_models(db_path);
+    let conn = match rusqlite::Connection::open(db_path) {
+        Ok(c) => c,
+        Err(_) => return vec![],
+    };
+    let mut stmt = match conn.prepare(
+        "SELECT speaker_label, voiceprint FROM host_profiles \
+         WHERE episode_id IS NULL AND voiceprint IS NOT NULL AND voiceprint_model = ?1",
+    ) {
+        Ok(s) => s,
+        Err(_) => return vec![],
+    };
+    let collected: Vec<(usize, Vec<f32>)> = match stmt.query_map(rusqlite::params![embedding.id], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, Vec<u8>>(1)?))
+    }) {
+        Ok(rows) => rows
+            .filter_map(|r| r.ok())
+            .filter_map(|(label, blob)| {
+                let host = HOST_LABELS.iter().position(|l| *l == label)?;
+                Some((host, decode_embedding(&blob)))
+            })
+            .collect(),
+        Err(_) => vec![],
+    };
+    collected
+}
+
+/// Display name for a host: the host_N_name setting, else the label.
+#[cfg(feature = "app")]
+fn host_name_setting(db_path: &Path, host: usize) -> String {
+    crate::commands::transcription::read_setting(db_path, &format!("host_{}_name", host))
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| HOST_LABELS[host].to_string())
+}
+
+/// Episodes to enroll from when none are given: diarized episodes with speaker
+/// corrections (reviewed by hand), newest first.
+#[cfg(feature = "app")]
+fn reviewed_episodes(conn: &rusqlite::Connection) -> Result<Vec<i64>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT DISTINCT e.id FROM episodes e \
+             JOIN diarization_segments ds ON ds.episode_id = e.id \
+             WHERE e.diarization_status = 'done' \
+               AND (ds.corrected_speaker IS NOT NULL OR ds.edited = 1) \
+             ORDER BY e.publish_date DESC LIMIT ?1",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(rusqlite::params![ENROLL_MAX_EPISODES as i64], |row| row.get(0))
+        .map_err(|e| e.to_string())?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+/// Enroll a host (`SPEAKER_0` or `SPEAKER_1`) from the hand-reviewed segments
+/// attributed to them in the given episodes, or in the latest hand-reviewed ones. Replaces an
+/// earlier voiceprint. Newly diarized episodes are then matched against it.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn enroll_host_voiceprint(
+    speaker_label: String,
+    episode_ids: Option<Vec<i64>>,
+    on_event: Channel<DiarizationEvent>,
+    app: tauri::AppHandle,
+) -> Result<HostVoiceprint, String> {
+    let host = HOST_LABELS
+        .iter()
+        .position(|l| *l == speaker_label)
+        .ok_or_else(|| format!("Unbekannter Host: {}", speaker_label))?;
+
+    let paths = BinkyPaths::from_app(&app)?;
+    let db_path = paths.db_path.clone();
+    let setup = crate::commands::diarization::find_diarization_models(&paths).await?;
+
+    // (episode id, audio URL, segment ranges to embed)
+    let episodes = {
+        let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
+        let episode_ids = match episode_ids.filter(|ids| !ids.is_empty()) {
+            Some(ids) => ids,
+            None => reviewed_episodes(&conn)?,
+        };
+
+        let mut episodes = Vec::new();
+        for episode_id in episode_ids {
+            let audio_url: Option<String> = conn
+                .query_row(
+                    "SELECT audio_url FROM episodes WHERE id = ?1",
+                    rusqlite::params![episode_id],
+                    |row| row.get(0),
+                )
+                .ok()
+                .flatten();
+            // Only segments a person confirmed; raw diarizer labels would
+            // teach the voiceprint the diarizer's own mistakes
+            let mut stmt = conn
+                .prepare(
+                    "SELECT start_ms, end_ms FROM diarization_segments \
+                     WHERE episode_id = ?1 AND COALESCE(corrected_speaker, speaker_label) = ?2 \
+                       AND (corrected_speaker IS NOT NULL OR edited = 1)",
+                )
+                .map_err(|e| e.to_string())?;
+            let episode_ranges: Vec<(i64, i64)> = stmt
+                .query_map(rusqlite::params![episode_id, speaker_label], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })
+                .map_err(|e| e.to_string())?
+                .filter_map(|r| r.ok())
+                .collect();
+            let episode_ranges = pick_ranges(episode_ranges, ENROLL_SEGMENTS_PER_EPISODE);
+            if let (Some(url), false) = (audio_url, episode_ranges.is_empty()) {
+                episodes.push((episode_id, url, episode_ranges));
+            }
+        }
+        episodes
+    };
+    if episodes.is_empty() {
+        return Err("Keine bestätigten Segmente für diesen Host gefunden".to_string());
+    }
+
+    let total = episodes.len() as i32;
+    let mut embeddings: Vec<Vec<f32>> = Vec::new();
+    for (index, (episode_id, audio_url, episode_ranges)) in episodes.into_iter().enumerate() {
+        let index = index as i32;
+        let samples = crate::commands::pipeline::decode_episode_audio(&paths, episode_id, &audio_url, |percent| {
+            let _ = on_event.send(DiarizationEvent::Progress {
+                percent: (index * 100 + percent) / total,
+            });
+        })
+        .await?;
+
+        let episode_setup = setup.clone();
+        let episode_embeddings = tauri::async_runtime::spawn_blocking(move || {
+            let mut extractor = embedding_extractor(&episode_setup)?;
+            Ok::<Vec<Vec<f32>>, String>(
+                episode_ranges
+                    .into_iter()
+                    .filter_map(|(start, end)| embed_range(&mut extractor, &samples, start, end))
+                    .collect(),
+            )
+        })
+        .await
+        .map_err(|e| format!("Embedding task panicked: {}", e))??;
+        embeddings.extend(episode_embeddings);
+
+        let _ = on_event.send(DiarizationEvent::Progress {
+            percent: ((index + 1) * 100) / total,
+        });
+    }
+
+    let voiceprint = mean_embedding(&embeddings)
+        .ok_or_else(|| "Aus den Segmenten konnte kein Stimmprofil berechnet werden".to_string())?;
+
+    let conn = rusqlite::Connection::open(&db_path).map_err(|e| e.to_string())?;
+    let existing: Option<i64> = conn
+        .query_row(
+            "SELECT id FROM host_profiles WHERE episode_id IS NULL AND speaker_label = ?1 \
+             ORDER BY id LIMIT 1",
+            rusqlite::params![speaker_label],
+            |row| row.get(0),
+        )
+        .ok();
+    match existing {
+        Some(id) => conn.execute(
+            "UPDATE host_profiles SET voiceprint = ?1, voiceprint_segments = ?2, voiceprint_model = ?3, \
+             voiceprint_updated_at = datetime('now') WHERE id = ?4",
+            rusqlite::params![encode_embedding(&voiceprint), embeddings.len() as i64, setup.embedding.id, id],
+        ),
+        None => conn.execute(
+            "INSERT INTO host_profiles \
+             (speaker_label, host_name, confirmed, voiceprint, voiceprint_segments, voiceprint_model, \
+              voiceprint_updated_at) \
+             VALUES (?1, ?2, 1, ?3, ?4, ?5, datetime('now'))",
+            rusqlite::params![
+                speaker_label,
+                host_name_setting(&db_path, host),
+                encode_embedding(&voiceprint),
+                embeddings.len() as i64,
+                setup.embedding.id
+            ],
+        ),
+    }
+    .map_err(|e| format!("Stimmprofil konnte nicht gespeichert werden: {}", e))?;
+
+    drop(conn);
+    list_voiceprints(&db_path)?
+        .into_iter()
+        .find(|v| v.speaker_label == speaker_label)
+        .ok_or_else(|| "Stimmprofil nicht gefunden".to_string())
+}
+
+#[cfg(feature = "app")]
+fn list_voiceprints(db_path: &Path) -> Result<Vec<HostVoiceprint>, String> {
+    let conn = rusqlite::Connection::open(db_path).map_err(|e| e.to_string())?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT speaker_label, host_name, voiceprint_segments, voiceprint_updated_at, voiceprint_model \
+             FROM host_profiles WHERE episode_id IS NULL AND voiceprint IS NOT NULL \
+             ORDER BY speaker_label",
+        )
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(HostVoiceprint {
+                speaker_label: row.get(0)?,
+                host_name: row.get(1)?,
+                segment_count: row.get::<_, Option<i64>>(2)?.unwrap_or(0),
+                updated_at: row.get(3)?,
+                model: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+    Ok(rows.filter_map(|r| r.ok()).collect())
+}
+
+/// Enrolled host voiceprints.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn list_host_voiceprints(app: tauri::AppHandle) -> Result<Vec<HostVoiceprint>, String> {
+    list_voiceprints(&BinkyPaths::from_app(&app)?.db_path)
+}
+
+/// Forget a host's voiceprint; their episodes fall back to speaking-time order.
+#[cfg(feature = "app")]
+#[tauri::command]
+pub async fn delete_host_voiceprint(speaker_label: String, app: tauri::AppHandle) -> Result<(), String> {
+    let paths = BinkyPaths::from_app(&app)?;
+    let conn = rusqlite::Connection::open(&paths.db_path).map_err(|e| e.to_string())?;
+    conn.execute(
+        "UPDATE host_profiles SET voiceprint = NULL, voiceprint_segments = NULL, voiceprint_model = NULL, \
+         voiceprint_updated_at = NULL WHERE episode_id IS NULL AND speaker_label = ?1",
+        rusqlite::params![speaker_label],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn embeddings_round_trip_through_blobs() {
+        let embedding = vec![0.25, -1.5, 3.0e-7];
+        assert_eq!(decode_embedding(&encode_embedding(&embedding)), embedding);
+    }
+
+    #[test]
+    fn matches_each_host_to_its_most_similar_cluster() {
+        let centroids = vec![
+            ("SPEAKER_0".to_string(), vec![0.1, 1.0, 0.0]),
+            ("SPEAKER_1".to_string(), vec![1.0, 0.2, 0.0]),
+            ("SPEAKER_2".to_string(), vec![0.0, 0.0, 1.0]),
+        ];
+        let voiceprints = vec![(0, vec![1.0, 0.0, 0.0]), (1, vec![0.0, 1.0, 0.1])];
+        let matched = match_hosts(&centroids, &voiceprints);
+        assert_eq!(matched["SPEAKER_1"].0, 0);
+        assert_eq!(matched["SPEAKER_0"].0, 1);
+        // The guest is below MIN_HOST_SIMILARITY for both hosts
+        assert!(!matched.contains_key("SPEAKER_2"));
+        assert!(matched["SPEAKER_1"].1 > 0.9);
+    }
+}
@@ -0,0 +1,221 @@
+use std::ffi::c_void;
+use std::os::raw::c_int;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use tokio_util::sync::CancellationToken;
+use whisper_rs::{FullParams, WhisperState, WhisperSysContext, WhisperSysState};
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Abort and progress callbacks for whisper.cpp
+//
+// whisper-rs 0.15's set_abort_callback_safe stores a double-boxed closure but
+// its trampoline casts the pointer back to the concrete closure type (UB →
+// SIGSEGV). Here each user-data pointer is cast back to exactly the type it was
+// created from, and both targets live on the stack of the one `full()` call
+// that uses them:
+//   * abort:    *const CancellationToken — Sync, may be polled from ggml threads
+//   * progress: *mut ProgressRelay      — only called on the whisper_full thread
+// Panics never unwind into C: a panicking abort check aborts the decode, a
+// panicking progress handler is ignored.
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Progress handler target. Forwards whisper's 0–100 progress once per change.
+struct ProgressRelay<'a> {
+    last_percent: i32,
+    on_progress: &'a mut dyn FnMut(i32),
+}
+
+pub(crate) type AbortFn = unsafe extern "C" fn(*mut c_void) -> bool;
+pub(crate) type ProgressFn =
+    unsafe extern "C" fn(*mut WhisperSysContext, *mut WhisperSysState, c_int, *mut c_void);
+
+/// C callbacks plus user data, valid only inside `with_callbacks`.
+#[derive(Clone, Copy)]
+pub(crate) struct RawCallbacks {
+    pub abort: AbortFn,
+    pub abort_data: *mut c_void,
+    pub progress: ProgressFn,
+    pub progress_data: *mut c_void,
+}
+
+unsafe extern "C" fn abort_trampoline(user_data: *mut c_void) -> bool {
+    if user_data.is_null() {
+        return false;
+    }
+    // SAFETY: abort_data is created from a &CancellationToken that outlives the
+    // call (with_callbacks); CancellationToken is Sync.
+    let token = unsafe { &*(user_data as *const CancellationToken) };
+    catch_unwind(AssertUnwindSafe(|| token.is_cancelled())).unwrap_or(true)
+}
+
+unsafe extern "C" fn progress_trampoline(
+    _ctx: *mut WhisperSysContext,
+    _state: *mut WhisperSysState,
+    progress: c_int,
+    user_data: *mut c_void,
+) {
+    if user_data.is_null() {
+        return;
+    }
+    // SAFETY: progress_data is created from a &mut ProgressRelay that outlives
+    // the call; whisper.cpp reports progress only on the thread running
+    // whisper_full, so this is the only live reference.
+    let relay = unsafe { &mut *(user_data as *mut ProgressRelay) };
+    let percent = progress.clamp(0, 100);
+    if percent == relay.last_percent {
+        return;
+    }
+    relay.last_percent = percent;
+    let _ = catch_unwind(AssertUnwindSafe(|| (relay.on_progress)(percent)));
+}
+
+/// Run `run` with abort and progress callbacks bound to `cancel_token` and
+/// `on_progress` (chunk progress 0–100, once per change). The pointers in
+/// `RawCallbacks` must not be used after `run` returns.
+pub(crate) fn with_callbacks<R>(
+    cancel_token: &CancellationToken,
+    on_progress: &mut dyn FnMut(i32),
+    run: impl FnOnce(RawCallbacks) -> R,
+) -> R {
+    let mut relay = ProgressRelay {
+        last_percent: -1,
+        on_progress,
+    };
+    let callbacks = RawCallbacks {
+        abort: abort_trampoline,
+        abort_data: cancel_token as *const CancellationToken as *mut c_void,
+        progress: progress_trampoline,
+        progress_data: &mut relay as *mut ProgressRelay as *mut c_void,
+    };
+    run(callbacks)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum WhisperRunError {
+    Cancelled,
+    Failed(String),
+}
+
+/// `state.full(params, audio)` that stops mid-chunk once `cancel_token` is
+/// cancelled and reports chunk progress (0–100) through `on_progress`.
+pub(crate) fn full_with_callbacks(
+    state: &mut WhisperState,
+    mut params: FullParams<'_, '_>,
+    audio: &[f32],
+    cancel_token: &CancellationToken,
+    on_progress: &mut dyn FnMut(i32),
+) -> Result<(), WhisperRunError> {
+    let result = with_callbacks(cancel_token, on_progress, |callbacks| {
+        // SAFETY: the callbacks match whisper.cpp's signatures and their user
+        // data stays valid until with_callbacks returns, i.e. after full()
+        // (which consumes params) has finished. A fresh WhisperState per
+        // chunk keeps whisper.cpp from reusing the pointers later.
+        unsafe {
+            params.set_abort_callback(Some(callbacks.abort));
+            params.set_abort_callback_user_data(callbacks.abort_data);
+            params.set_progress_callback(Some(callbacks.progress));
+            params.set_progress_callback_user_data(callbacks.progress_data);
+        }
+        state.full(params, audio)
+    });
+
+    match result {
+        _ if cancel_token.is_cancelled() => Err(WhisperRunError::Cancelled),
+        Ok(_) => Ok(()),
+        Err(e) => Err(WhisperRunError::Failed(e.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Stand-in for whisper_full: reports progress per step and polls abort
+    /// between compute steps, like the encoder/decoder graph loop does.
+    /// Returns the number of completed steps.
+    fn fake_whisper_full(callbacks: RawCallbacks, steps: i32, on_step: impl Fn(i32)) -> i32 {
+        for step in 0..steps {
+            unsafe {
+                (callbacks.progress)(
+                    std::ptr::null_mut(),
+                    std::ptr::null_mut(),
+                    step * 100 / steps,
+                    callbacks.progress_data,
+                );
+                if (callbacks.abort)(callbacks.abort_data) {
+                    return step;
+                }
+            }
+            on_step(step);
+        }
+        steps
+    }
+
+    #[test]
+    fn cancel_mid_chunk_stops_at_next_poll() {
+        let token = CancellationToken::new();
+        let mut reported = vec![];
+        let completed = with_callbacks(&token, &mut |p| reported.push(p), |callbacks| {
+            fake_whisper_full(callbacks, 100, |step| {
+                if step == 41 {
+                    token.cancel();
+                }
+            })
+        });
+        assert_eq!(completed, 42);
+        assert_eq!(reported.last(), Some(&42));
+    }
+
+    #[test]
+    fn uncancelled_run_reports_each_percent_once() {
+        let token = CancellationToken::new();
+        let mut reported = vec![];
+        let completed = with_callbacks(&token, &mut |p| reported.push(p), |callbacks| {
+            unsafe {
+                for progress in [0, 0, 5, 5, 5, 50, 120] {
+                    (callbacks.progress)(
+                        std::ptr::null_mut(),
+                        std::ptr::null_mut(),
+                        progress,
+                        callbacks.progress_data,
+                    );
+                }
+            }
+            fake_whisper_full(callbacks, 10, |_| {})
+        });
+        assert_eq!(completed, 10);
+        assert_eq!(&reported[..4], &[0, 5, 50, 100]);
+    }
+
+    #[test]
+    fn abort_polled_from_another_thread() {
+        let token = CancellationToken::new();
+        let polls = Arc::new(Mutex::new(vec![]));
+        with_callbacks(&token, &mut |_| {}, |callbacks| {
+            // ggml may poll the abort callback from a compute thread
+            let abort = callbacks.abort;
+            let data = callbacks.abort_data as usize;
+            let poll = |polls: &Arc<Mutex<Vec<bool>>>| {
+                let polls = polls.clone();
+                std::thread::spawn(move || {
+                    polls.lock().unwrap().push(unsafe { abort(data as *mut c_void) });
+                })
+                .join()
+                .unwrap();
+            };
+            poll(&polls);
+            token.cancel();
+            poll(&polls);
+        });
+        assert_eq!(*polls.lock().unwrap(), vec![false, true]);
+    }
+
+    #[test]
+    fn panicking_progress_handler_does_not_unwind_into_c() {
+        let token = CancellationToken::new();
+        let completed = with_callbacks(&token, &mut |_| panic!("handler bug"), |callbacks| {
+            fake_whisper_full(callbacks, 3, |_| {})
+        });
+        assert_eq!(completed, 3);
+    }
+}
@@ -1,12 +1,19 @@
+#[cfg(feature = "app")]
 use std::sync::Arc;
+#[cfg(feature = "app")]
 use tauri::Manager;
+#[cfg(feature = "app")]
 use tauri_plugin_sql::{Builder as SqlBuilder, Migration, MigrationKind};
-use state::diarization_queue::DiarizationState;
 
+pub mod cli;
 mod commands;
+mod migrations;
 mod models;
+pub mod paths;
+pub mod progress;
 mod state;
 
+#[cfg(feature = "app")]
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     // Initialize Sentry before Tauri (only when DSN is provided at compile time)
@@ -20,92 +27,15 @@ pub fn run() {
         ))
     });
 
-    let migrations = vec![
-        Migration {
-            version: 1,
-            description: "create_initial_tables",
-            sql: include_str!("../migrations/001_initial.sql"),
+    let migrations = migrations::MIGRATIONS
+        .iter()
+        .map(|&(version, description, sql)| Migration {
+            version,
+            description,
+            sql,
             kind: MigrationKind::Up,
-        },
-        Migration {
-            version: 2,
-            description: "episodes_transcripts",
-            sql: include_str!("../migrations/002_episodes_transcripts.sql"),
-            kind: MigrationKind::Up,
-        },
-        Migration {
-            version: 3,
-            description: "diarization_schema",
-            sql: include_str!("../migrations/003_diarization.sql"),
-            kind: MigrationKind::Up,
-        },
-        Migration {
-            version: 4,
-            description: "topics_enhanced",
-            sql: include_str!("../migrations/004_topics_enhanced.sql"),
-            kind: MigrationKind::Up,
-        },
-        Migration {
-            version: 5,
-            description: "birds_enhanced",
-            sql: include_str!("../migrations/005_birds_enhanced.sql"),
-            kind: MigrationKind::Up,
-        },
-        Migration {
-            version: 6,
-            description: "Clear test bird history",
-            sql: include_str!("../migrations/006_clear_test_bird_history.sql"),
-            kind: MigrationKind::Up,
-        },
-        Migration {
-            version: 7,
-            description: "Clean test birds mark eisente",
-            sql: include_str!("../migrations/007_clean_test_birds_mark_eisente.sql"),
-            kind: MigrationKind::Up,
-        },
-        Migration {
-            version: 8,
-            description: "Fix bird history",
-            sql: include_str!("../migrations/008_fix_bird_history.sql"),
-            kind: MigrationKind::Up,
-        },
-        Migration {
-            version: 9,
-            description: "Reset episodes for AssemblyAI re-transcription",
-            sql: include_str!("../migrations/009_reset_diarization_episodes.sql"),
-            kind: MigrationKind::Up,
-        },
-        Migration {
-            version: 10,
-            description: "Clean test bird history entries",
-            sql: include_str!("../migrations/010_clean_test_birds.sql"),
-            kind: MigrationKind::Up,
-        },
-        Migration {
-            version: 11,
-            description: "add_utterance_text_to_diarization_segments",
-            sql: include_str!("../migrations/011_utterance_text.sql"),
-            kind: MigrationKind::Up,
-        },
-        Migration {
-            version: 12,
-            description: "fts_search_index",
-            sql: include_str!("../migrations/012_fts.sql"),
-            kind: MigrationKind::Up,
-        },
-        Migration {
-            version: 13,
-            description: "fix_fts_triggers",
-            sql: include_str!("../migrations/013_fix_fts_triggers.sql"),
-            kind: MigrationKind::Up,
-        },
-        Migration {
-            version: 14,
-            description: "backfill_topics_fts",
-            sql: include_str!("../migrations/014_backfill_topics_fts.sql"),
-            kind: MigrationKind::Up,
-        },
-    ];
+        })
+        .collect();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_http::init())
@@ -118,13 +48,13 @@ pub fn run() {
         .plugin(tauri_plugin_process::init())
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_opener::init())
-        // Manage Arc<TranscriptionState> so we can clone it into async tasks
-        .manage(Arc::new(state::transcription_queue::TranscriptionState::new()))
-        .manage(Arc::new(DiarizationState::new()))
+        // Manage Arc<EngineState> so we can clone it into async tasks
+        .manage(Arc::new(state::job_engine::EngineState::new()))
         .setup(|app| {
             // Reset any in-flight transcription statuses left over from a previous
             // crashed/force-quit session. The in-memory queue is empty on every
             // startup, so 'queued'/'downloading'/'transcribing' states are stale.
+            // Pipelines persisted in `pipeline_jobs` are re-queued right after.
             if let Ok(db_path) = app.path().app_data_dir().map(|d: std::path::PathBuf| d.join("binky.db")) {
                 if let Ok(conn) = rusqlite::Connection::open(&db_path) {
                     let _ = conn.execute(
@@ -155,6 +85,12 @@ pub fn run() {
                 // were diarized before the Whisper backfill was introduced. Idempotent.
                 commands::diarization::backfill_all_whisper_segment_text(&db_path);
             }
+
+            // Resume the pipelines of the previous session
+            let app_handle = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                commands::pipeline::resume_persisted_jobs(app_handle).await;
+            });
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -165,12 +101,41 @@ pub fn run() {
             commands::transcription::start_transcription,
             commands::transcription::cancel_transcription,
             commands::transcription::get_queue_status,
+            commands::transcription::get_quality_presets,
+            commands::transcription::set_quality_preset,
+            commands::transcription::set_episode_language,
+            commands::hallucination::get_hallucination_filter,
+            commands::hallucination::set_hallucination_filter,
+            commands::hallucination::list_flagged_segments,
+            commands::retranscribe::retranscribe_range,
+            commands::retranscribe::cancel_retranscribe_range,
+            commands::comparison::list_transcript_alternatives,
+            commands::comparison::compare_transcripts,
+            commands::comparison::delete_transcript_alternative,
+            commands::benchmark::run_model_benchmark,
+            commands::benchmark::list_model_benchmarks,
+            commands::benchmark::recommend_model,
             commands::diarization::get_diarization_model_status,
             commands::diarization::download_diarization_models,
             commands::diarization::delete_diarization_models,
+            commands::diarization_models::list_diarization_models,
+            commands::diarization_models::get_diarization_settings,
+            commands::diarization_models::set_diarization_settings,
             commands::diarization::start_diarization,
             commands::diarization::cancel_diarization,
             commands::diarization::get_diarization_queue_status,
+            commands::diarization::set_episode_speaker_count,
+            commands::voiceprint::enroll_host_voiceprint,
+            commands::voiceprint::list_host_voiceprints,
+            commands::voiceprint::delete_host_voiceprint,
+            commands::clustering::recluster_episode,
+            commands::overlap::list_overlap_segments,
+            commands::overlap::get_speaking_time,
+            commands::propagation::suggest_speaker_propagation,
+            commands::propagation::apply_speaker_propagation,
+            commands::evaluation::evaluate_diarization,
+            commands::evaluation::list_diarization_evaluations,
+            commands::turn_taking::get_turn_taking,
             commands::topics::analyze_episode_topics,
             commands::topics::get_episode_analysis_status,
             commands::topics::has_openai_key_configured,
@@ -188,6 +153,27 @@ pub fn run() {
             commands::search::search_transcripts,
             commands::search::rebuild_search_index,
             commands::search::fetch_related_episodes,
+            commands::export::export_transcript,
+            commands::export::export_all_transcripts,
+            commands::editing::edit_segment_text,
+            commands::editing::split_segment,
+            commands::editing::merge_segments,
+            commands::editing::relabel_segment_range,
+            commands::editing::relabel_cluster,
+            commands::editing::list_transcript_revisions,
+            commands::editing::undo_last_revision,
+            commands::pipeline::start_pipeline,
+            commands::pipeline::get_job_queue,
+            commands::pipeline::get_pipeline_status,
+            commands::pipeline::move_queued_job,
+            commands::pipeline::set_queued_job_priority,
+            commands::pipeline::remove_queued_job,
+            commands::pipeline::cancel_pipeline,
+            commands::pipeline::retry_pipeline_stage,
+            commands::pipeline::pause_job_queue,
+            commands::pipeline::resume_job_queue,
+            commands::pipeline::get_cpu_budget,
+            commands::pipeline::set_cpu_budget,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,95 @@
+use rusqlite::Connection;
+use sha2::{Digest, Sha384};
+
+/// Schema migrations as (version, description, SQL). The app hands them to
+/// tauri-plugin-sql; the CLI applies them itself with `apply_pending`. Both
+/// record them in sqlx's `_sqlx_migrations` table, so either can open a
+/// database the other created. Append only.
+pub(crate) const MIGRATIONS: &[(i64, &str, &str)] = &[
+    (1, "create_initial_tables", include_str!("../migrations/001_initial.sql")),
+    (2, "episodes_transcripts", include_str!("../migrations/002_episodes_transcripts.sql")),
+    (3, "diarization_schema", include_str!("../migrations/003_diarization.sql")),
+    (4, "topics_enhanced", include_str!("../migrations/004_topics_enhanced.sql")),
+    (5, "birds_enhanced", include_str!("../migrations/005_birds_enhanced.sql")),
+    (6, "Clear test bird history", include_str!("../migrations/006_clear_test_bird_history.sql")),
+    (7, "Clean test birds mark eisente", include_str!("../migrations/007_clean_test_birds_mark_eisente.sql")),
+    (8, "Fix bird history", include_str!("../migrations/008_fix_bird_history.sql")),
+    (9, "Reset episodes for AssemblyAI re-transcription", include_str!("../migrations/009_reset_diarization_episodes.sql")),
+    (10, "Clean test bird history entries", include_str!("../migrations/010_clean_test_birds.sql")),
+    (11, "add_utterance_text_to_diarization_segments", include_str!("../migrations/011_utterance_text.sql")),
+    (12, "fts_search_index", include_str!("../migrations/012_fts.sql")),
+    (13, "fix_fts_triggers", include_str!("../migrations/013_fix_fts_triggers.sql")),
+    (14, "backfill_topics_fts", include_str!("../migrations/014_backfill_topics_fts.sql")),
+    (15, "whisper_quality_preset", include_str!("../migrations/015_quality_preset.sql")),
+    (16, "episode_language_override", include_str!("../migrations/016_episode_language.sql")),
+    (17, "transcript_revisions", include_str!("../migrations/017_transcript_revisions.sql")),
+    (18, "persistent_jobs", include_str!("../migrations/018_jobs.sql")),
+    (19, "job_order_and_pause", include_str!("../migrations/019_job_order.sql")),
+    (20, "job_engine", include_str!("../migrations/020_job_engine.sql")),
+    (21, "cpu_budget", include_str!("../migrations/021_cpu_budget.sql")),
+    (22, "transcript_alternatives", include_str!("../migrations/022_transcript_alternatives.sql")),
+    (23, "model_benchmarks", include_str!("../migrations/023_model_benchmarks.sql")),
+    (24, "episode_speaker_count", include_str!("../migrations/024_episode_speaker_count.sql")),
+    (25, "host_voiceprints", include_str!("../migrations/025_host_voiceprints.sql")),
+    (26, "segment_embeddings", include_str!("../migrations/026_segment_embeddings.sql")),
+    (27, "overlap_segments", include_str!("../migrations/027_overlap_segments.sql")),
+    (28, "diarization_evaluations", include_str!("../migrations/028_diarization_evaluations.sql")),
+    (29, "voiceprint_model", include_str!("../migrations/029_voiceprint_model.sql")),
+    (30, "turn_taking_stats", include_str!("../migrations/030_turn_taking_stats.sql")),
+    (31, "benchmark_wer_reset", include_str!("../migrations/031_benchmark_wer_reset.sql")),
+    (32, "evaluation_speaker_count", include_str!("../migrations/032_evaluation_speaker_count.sql")),
+];
+
+/// Apply migrations missing from `_sqlx_migrations`, each in its own
+/// transaction. Returns the number applied.
+pub(crate) fn apply_pending(conn: &mut Connection) -> Result<usize, String> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS _sqlx_migrations ( \
+             version BIGINT PRIMARY KEY, \
+             description TEXT NOT NULL, \
+             installed_on TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP, \
+             success BOOLEAN NOT NULL, \
+             checksum BLOB NOT NULL, \
+             execution_time BIGINT NOT NULL \
+         )",
+    )
+    .map_err(|e| e.to_string())?;
+
+    let applied: Vec<(i64, bool)> = {
+        let mut stmt = conn
+            .prepare("SELECT version, success FROM _sqlx_migrations")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?;
+        rows.filter_map(|r| r.ok()).collect()
+    };
+    if let Some((version, _)) = applied.iter().find(|(_, success)| !success) {
+        return Err(format!("Migration {} ist unvollständig (Datenbank beschädigt)", version));
+    }
+
+    let mut count = 0;
+    for &(version, description, sql) in MIGRATIONS {
+        if applied.iter().any(|(v, _)| *v == version) {
+            continue;
+        }
+        let started = std::time::Instant::now();
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+        tx.execute_batch(sql)
+            .map_err(|e| format!("Migration {} ({}) fehlgeschlagen: {}", version, description, e))?;
+        tx.execute(
+            "INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) \
+             VALUES (?1, ?2, 1, ?3, ?4)",
+            rusqlite::params![
+                version,
+                description,
+                Sha384::digest(sql.as_bytes()).to_vec(),
+                started.elapsed().as_nanos() as i64
+            ],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.commit().map_err(|e| e.to_string())?;
+        count += 1;
+    }
+    Ok(count)
+}
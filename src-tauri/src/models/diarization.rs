@@ -1,4 +1,6 @@
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "app")]
+use std::collections::BTreeMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiarizationSegment {
@@ -6,6 +8,9 @@ pub struct DiarizationSegment {
     pub end_ms: i64,
     pub speaker_label: String,
     pub confidence: Option<f64>,
+    /// Speaker embedding, stored with the segment but never sent to the UI.
+    #[serde(skip)]
+    pub embedding: Option<Vec<f32>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +22,34 @@ pub enum DiarizationEvent {
     Cancelled,
 }
 
+/// A registered diarization model (see diarization_models.rs).
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiarizationModelInfo {
+    pub id: String,
+    /// "segmentation" or "embedding".
+    pub role: String,
+    pub name: String,
+    pub url: String,
+    pub sha256: Option<String>,
+    pub language: String,
+    pub size_mb: u32,
+    pub downloaded: bool,
+    pub selected: bool,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiarizationSettings {
+    pub segmentation_model: String,
+    pub embedding_model: String,
+    /// ONNX Runtime execution provider: "cpu", "cuda" or "coreml".
+    pub provider: String,
+    /// Threads per sherpa-onnx model (cpu_sherpa_threads of the CPU budget).
+    pub num_threads: u32,
+}
+
+#[cfg(feature = "app")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiarizationModelStatus {
     pub segmentation_downloaded: bool,
@@ -24,6 +57,7 @@ pub struct DiarizationModelStatus {
     pub models_dir: String,
 }
 
+#[cfg(feature = "app")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DiarizationQueueStatus {
     pub active_episode_id: Option<i64>,
@@ -31,6 +65,7 @@ pub struct DiarizationQueueStatus {
     pub is_processing: bool,
 }
 
+#[cfg(feature = "app")]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "event", content = "data")]
 pub enum DiarizationModelDownloadEvent {
@@ -38,3 +73,164 @@ pub enum DiarizationModelDownloadEvent {
     Done,
     Error { message: String },
 }
+
+/// Enrolled voiceprint of a host (global `host_profiles` row).
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HostVoiceprint {
+    pub speaker_label: String,
+    pub host_name: String,
+    /// Segments averaged into the voiceprint.
+    pub segment_count: i64,
+    pub updated_at: Option<String>,
+    /// Registry id of the embedding model that computed it.
+    pub model: Option<String>,
+}
+
+/// Full copy of one diarization_segments row, used for revision snapshots.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SegmentSnapshot {
+    pub id: i64,
+    pub episode_id: i64,
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub speaker_label: String,
+    pub corrected_speaker: Option<String>,
+    pub confidence: Option<f64>,
+    pub text: Option<String>,
+    pub edited: bool,
+    /// Stored speaker embedding (migration 026), restored with the row so an
+    /// undone edit stays usable for re-clustering and propagation.
+    #[serde(default)]
+    pub embedding: Option<Vec<u8>>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptRevision {
+    pub id: i64,
+    pub episode_id: i64,
+    pub action: String,
+    pub before: Vec<SegmentSnapshot>,
+    pub after: Vec<SegmentSnapshot>,
+    pub reverted: bool,
+    pub created_at: Option<String>,
+}
+
+/// Outcome of re-clustering an episode from its stored segment embeddings.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReclusterSummary {
+    pub speakers: usize,
+    pub changed_segments: usize,
+    /// New diarization status: "done" or "solo".
+    pub status: String,
+}
+
+/// Stretch of an episode in which two or more speakers talk at once.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OverlapSegment {
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub speakers: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeakerTime {
+    pub speaker: String,
+    pub ms: i64,
+    /// Share of the episode's speech time (all shares add up to 100).
+    pub percent: f64,
+}
+
+/// Speaking time of an episode with overlapping speech split evenly among
+/// the speakers talking at once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EpisodeSpeakingTime {
+    pub episode_id: i64,
+    /// Time in which anyone speaks; overlaps count once.
+    pub speech_ms: i64,
+    pub overlap_ms: i64,
+    pub speakers: Vec<SpeakerTime>,
+}
+
+/// A segment whose embedding is close to segments relabelled by hand, with the
+/// relabel it would get.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropagationSuggestion {
+    pub segment_id: i64,
+    pub start_ms: i64,
+    pub end_ms: i64,
+    pub text: Option<String>,
+    pub current_speaker: String,
+    pub suggested_speaker: String,
+    /// Cosine similarity to the relabelled segments of that speaker.
+    pub similarity: f32,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeakerAssignment {
+    pub segment_id: i64,
+    pub speaker: String,
+}
+
+/// Diarization error of one evaluation run (`diarization_evaluations`): the
+/// config re-run on manually corrected episodes, scored against the corrected
+/// speakers. Times are summed over the episodes.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiarizationEvaluation {
+    pub id: i64,
+    pub segmentation_model: String,
+    pub embedding_model: String,
+    /// Clustering threshold; a tried threshold clusters every episode by it,
+    /// otherwise only episodes with speaker count "auto" use it.
+    pub threshold: f64,
+    /// "auto" when a tried threshold clustered every episode, "episode" when
+    /// each episode used its own speaker count setting.
+    pub speaker_count: String,
+    pub episode_ids: Vec<i64>,
+    /// Speech in the reviewed segments; overlapping speakers count twice.
+    pub reference_ms: i64,
+    /// Reference speech the diarization found no speaker for.
+    pub missed_ms: i64,
+    /// Speech the diarization found where the reference has none.
+    pub false_alarm_ms: i64,
+    /// Speech attributed to the wrong speaker.
+    pub confusion_ms: i64,
+    /// (missed + false alarm + confusion) / reference speech.
+    pub der: f64,
+    pub created_at: Option<String>,
+}
+
+/// Turn-taking of one speaker in an episode.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SpeakerTurnTaking {
+    pub speaker: String,
+    pub turns: i64,
+    pub median_turn_ms: i64,
+    pub longest_turn_ms: i64,
+    /// Times this speaker took the floor from another one, by speaker interrupted.
+    pub interrupts: BTreeMap<String, i64>,
+    /// Times another speaker took the floor from this one.
+    pub interrupted: i64,
+    /// Mean silence before this speaker's replies that were not interruptions.
+    pub avg_reply_gap_ms: Option<i64>,
+}
+
+/// Turns, interruptions and reply gaps of an episode (`turn_taking_stats`).
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EpisodeTurnTaking {
+    pub episode_id: i64,
+    pub turns: i64,
+    pub median_turn_ms: i64,
+    pub longest_turn_ms: i64,
+    pub interruptions: i64,
+    pub avg_reply_gap_ms: Option<i64>,
+    pub speakers: Vec<SpeakerTurnTaking>,
+}
@@ -12,6 +12,8 @@ pub struct Episode {
     pub transcription_status: String, // not_started | queued | downloading | transcribing | done | error
     pub transcription_error: Option<String>,
     pub podcast_name: Option<String>,
+    pub language_override: Option<String>, // NULL = global whisper_language setting
+    pub speaker_count: Option<String>,     // NULL = two hosts | 'auto' | '1'..'10'
     pub created_at: Option<String>,
     pub updated_at: Option<String>,
 }
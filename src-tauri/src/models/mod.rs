@@ -1,3 +1,4 @@
 pub mod episode;
 pub mod transcript;
 pub mod diarization;
+pub mod pipeline;
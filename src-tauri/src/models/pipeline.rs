@@ -0,0 +1,253 @@
+use serde::{Deserialize, Serialize};
+
+/// One step of the episode processing pipeline. Declaration order is the
+/// canonical execution order; `dependencies()` declares what must finish first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stage {
+    Download,
+    Transcribe,
+    Diarize,
+    BackfillText,
+    AnalyzeTopics,
+    Index,
+}
+
+impl Stage {
+    #[cfg(feature = "app")]
+    pub const ALL: [Stage; 6] = [
+        Stage::Download,
+        Stage::Transcribe,
+        Stage::Diarize,
+        Stage::BackfillText,
+        Stage::AnalyzeTopics,
+        Stage::Index,
+    ];
+
+    /// Value stored in `job_stages.stage`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Stage::Download => "download",
+            Stage::Transcribe => "transcribe",
+            Stage::Diarize => "diarize",
+            Stage::BackfillText => "backfill_text",
+            Stage::AnalyzeTopics => "analyze_topics",
+            Stage::Index => "index",
+        }
+    }
+
+    #[cfg(feature = "app")]
+    pub fn parse(value: &str) -> Option<Stage> {
+        Stage::ALL.iter().copied().find(|s| s.as_str() == value)
+    }
+
+    /// Stages that must be done (or skipped) before this one may run.
+    /// A dependency that is not part of a job counts as satisfied — e.g. a
+    /// diarization-only job backfills text from an earlier transcript.
+    pub fn dependencies(&self) -> &'static [Stage] {
+        match self {
+            Stage::Download => &[],
+            Stage::Transcribe => &[Stage::Download],
+            Stage::Diarize => &[Stage::Download],
+            Stage::BackfillText => &[Stage::Transcribe, Stage::Diarize],
+            Stage::AnalyzeTopics => &[Stage::Transcribe],
+            Stage::Index => &[Stage::BackfillText, Stage::AnalyzeTopics],
+        }
+    }
+
+    /// Attempts before a failure becomes final. Network-bound stages retry.
+    pub fn max_attempts(&self) -> u32 {
+        match self {
+            Stage::Download => 3,
+            Stage::AnalyzeTopics => 2,
+            Stage::Transcribe | Stage::Diarize => 2,
+            Stage::BackfillText | Stage::Index => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StageStatus {
+    Pending,
+    Running,
+    Done,
+    /// Not applicable (e.g. no OpenAI key, diarization models missing).
+    /// Counts as satisfied for dependent stages.
+    Skipped,
+    Failed,
+    /// A dependency failed; this stage will not run.
+    Blocked,
+    Cancelled,
+}
+
+impl StageStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StageStatus::Pending => "pending",
+            StageStatus::Running => "running",
+            StageStatus::Done => "done",
+            StageStatus::Skipped => "skipped",
+            StageStatus::Failed => "failed",
+            StageStatus::Blocked => "blocked",
+            StageStatus::Cancelled => "cancelled",
+        }
+    }
+
+    #[cfg(feature = "app")]
+    pub fn parse(value: &str) -> Option<StageStatus> {
+        [
+            StageStatus::Pending,
+            StageStatus::Running,
+            StageStatus::Done,
+            StageStatus::Skipped,
+            StageStatus::Failed,
+            StageStatus::Blocked,
+            StageStatus::Cancelled,
+        ]
+        .into_iter()
+        .find(|s| s.as_str() == value)
+    }
+
+    /// True once the stage will not change any more without a manual retry.
+    pub fn is_final(&self) -> bool {
+        !matches!(self, StageStatus::Pending | StageStatus::Running)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageInfo {
+    pub stage: Stage,
+    pub status: StageStatus,
+    pub attempts: u32,
+    pub error: Option<String>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QueuedPipeline {
+    pub episode_id: i64,
+    pub title: Option<String>,
+    /// 0-based position in the queue (0 = runs next / is running).
+    pub position: usize,
+    pub priority: i32,
+    pub stages: Vec<StageInfo>,
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobQueueSnapshot {
+    pub is_processing: bool,
+    pub paused: bool,
+    pub jobs: Vec<QueuedPipeline>,
+}
+
+/// How much of the machine local processing may use. Stored in `settings`
+/// (`cpu_worker_slots`, `cpu_whisper_threads`, `cpu_sherpa_threads`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CpuBudget {
+    /// Pipelines whose stages may run at the same time. Each parallel Whisper
+    /// stage loads its own model copy, so memory grows with this too.
+    pub worker_slots: u32,
+    /// Threads per Whisper context. 0 = auto (cores minus one, split across slots).
+    pub whisper_threads: u32,
+    /// Threads for sherpa-onnx segmentation and embedding.
+    pub sherpa_threads: u32,
+}
+
+impl CpuBudget {
+    /// Budget when nothing is configured: two slots, so a transcription and a
+    /// diarization still run side by side, unless `cores` is too few for that.
+    pub fn default_for(cores: u32) -> Self {
+        let two_slots = Self {
+            worker_slots: 2,
+            whisper_threads: 0,
+            sherpa_threads: 1,
+        };
+        if two_slots.validate(cores).is_ok() {
+            two_slots
+        } else {
+            Self {
+                worker_slots: 1,
+                ..two_slots
+            }
+        }
+    }
+
+    /// Whisper threads per stage on a machine with `cores` cores. A thread
+    /// count of the active quality preset is honoured but capped by the budget.
+    pub fn whisper_threads_for(&self, preset_threads: Option<i32>, cores: u32) -> i32 {
+        let budget = if self.whisper_threads > 0 {
+            self.whisper_threads
+        } else {
+            // Keep one core free for the UI and split the rest across slots
+            (cores.saturating_sub(1) / self.worker_slots.max(1)).max(1)
+        };
+        let budget = budget as i32;
+        preset_threads.map(|n| n.clamp(1, budget)).unwrap_or(budget)
+    }
+
+    /// Reject budgets that cannot work or would oversubscribe the CPU.
+    pub fn validate(&self, cores: u32) -> Result<(), String> {
+        if self.worker_slots == 0 || self.sherpa_threads == 0 {
+            return Err("Worker-Slots und Diarisierungs-Threads müssen mindestens 1 sein".to_string());
+        }
+        let per_slot = self
+            .whisper_threads_for(None, cores)
+            .max(self.sherpa_threads as i32) as u32;
+        if self.worker_slots * per_slot > cores {
+            return Err(format!(
+                "Das CPU-Budget ({} Slots × {} Threads) überschreitet die {} verfügbaren Kerne",
+                self.worker_slots, per_slot, cores
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CpuBudgetInfo {
+    pub budget: CpuBudget,
+    pub available_cores: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_whisper_threads_keep_a_core_free_and_split_across_slots() {
+        let mut budget = CpuBudget::default_for(8);
+        budget.worker_slots = 1;
+        assert_eq!(budget.whisper_threads_for(None, 8), 7);
+        budget.worker_slots = 2;
+        assert_eq!(budget.whisper_threads_for(None, 8), 3);
+        assert_eq!(budget.whisper_threads_for(Some(16), 8), 3);
+        assert_eq!(budget.whisper_threads_for(Some(2), 8), 2);
+        assert_eq!(budget.whisper_threads_for(None, 1), 1);
+    }
+
+    #[test]
+    fn budgets_beyond_the_core_count_are_rejected() {
+        let fits = CpuBudget {
+            worker_slots: 2,
+            whisper_threads: 4,
+            sherpa_threads: 1,
+        };
+        assert!(fits.validate(8).is_ok());
+        assert!(fits.validate(6).is_err());
+        let zero_slots = CpuBudget {
+            worker_slots: 0,
+            ..fits
+        };
+        assert!(zero_slots.validate(8).is_err());
+    }
+
+    #[test]
+    fn default_budget_uses_two_slots_where_they_fit() {
+        assert_eq!(CpuBudget::default_for(8).worker_slots, 2);
+        assert_eq!(CpuBudget::default_for(2).worker_slots, 2);
+        assert_eq!(CpuBudget::default_for(1).worker_slots, 1);
+    }
+}
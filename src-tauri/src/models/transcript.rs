@@ -8,9 +8,146 @@ pub struct Transcript {
     pub segments_json: Option<String>,
     pub whisper_model: Option<String>,
     pub language: Option<String>,
+    pub quality_preset: Option<String>,
     pub created_at: Option<String>,
 }
 
+/// Named set of Whisper decoding parameters, stored under `whisper_quality_preset`.
+/// `beam_size: None` selects greedy sampling with `best_of` candidates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QualityPreset {
+    pub name: String,
+    pub beam_size: Option<i32>,
+    pub best_of: i32,
+    pub temperature: f32,
+    pub temperature_inc: f32,
+    pub no_speech_thold: f32,
+    pub entropy_thold: f32,
+    pub logprob_thold: f32,
+    pub suppress_blank: bool,
+    pub n_threads: Option<i32>,
+}
+
+/// Settings of the Whisper hallucination filter, stored as JSON under
+/// `whisper_hallucination_filter`. Flagged segments stay in `segments_json`
+/// with a `hallucination` reason but are left out of the transcript text.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HallucinationFilter {
+    pub enabled: bool,
+    /// A phrase of up to six words repeated back to back more often than this
+    /// marks a looping segment.
+    pub max_ngram_repeats: usize,
+    /// Silence rule (as in whisper.cpp): no_speech_prob at or above this while
+    /// the average token logprob is below `logprob_thold`.
+    pub no_speech_thold: f32,
+    pub logprob_thold: f32,
+    /// Segments whose average token logprob is below this are flagged as
+    /// low-confidence once no_speech_prob is not negligible either.
+    pub min_avg_logprob: f32,
+    /// Stock phrases Whisper produces on silence (matched case-insensitively).
+    pub stock_phrases: Vec<String>,
+}
+
+/// Optional overrides for re-transcribing a time range.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetranscribeOptions {
+    /// Downloaded model to use instead of the default, e.g. "large-v3".
+    pub model: Option<String>,
+    /// Initial prompt with names or terms Whisper should expect.
+    pub prompt: Option<String>,
+}
+
+/// One stored transcription run of an episode (see `transcript_alternatives`).
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptAlternative {
+    pub id: i64,
+    pub episode_id: i64,
+    pub engine: String,
+    pub model: Option<String>,
+    pub quality_preset: Option<String>,
+    pub language: Option<String>,
+    pub word_count: usize,
+    pub created_at: Option<String>,
+}
+
+/// Run of consecutive words with the same alignment operation.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WordDiffChunk {
+    /// "equal" | "substitute" | "delete" (only in reference) | "insert" (only in hypothesis)
+    pub op: String,
+    pub reference: Vec<String>,
+    pub hypothesis: Vec<String>,
+}
+
+/// Word error rate of `hypothesis` measured against `reference`, with the
+/// aligned word diff. WER = (substitutions + deletions + insertions) / reference words.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptComparison {
+    pub reference_id: i64,
+    pub hypothesis_id: i64,
+    pub reference_words: usize,
+    pub hypothesis_words: usize,
+    pub substitutions: usize,
+    pub deletions: usize,
+    pub insertions: usize,
+    pub wer: f64,
+    pub diff: Vec<WordDiffChunk>,
+}
+
+/// Audio a model benchmark runs on. Without one, a clip of the most recently
+/// transcribed episode is used; no sample clip ships with the app.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum BenchmarkClip {
+    /// Part of an episode. WER is only measured if every segment in that range
+    /// was corrected by hand; those texts are then the reference.
+    Episode { episode_id: i64, start_ms: i64, duration_ms: i64 },
+    /// Local audio file with an optional reference transcript.
+    File { path: String, reference_text: Option<String> },
+}
+
+/// Result of running one Whisper model on a benchmark clip (`model_benchmarks`).
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelBenchmark {
+    pub id: i64,
+    pub model: String,
+    /// "episode 12 @ 300 s" or the file name.
+    pub clip: String,
+    pub clip_ms: i64,
+    pub load_ms: i64,
+    pub processing_ms: i64,
+    /// processing_ms / clip_ms; below 1 is faster than real time.
+    pub real_time_factor: f64,
+    /// Memory the process grew by while the model was loaded and running.
+    pub peak_memory_bytes: Option<i64>,
+    /// Only with a human reference: hand-corrected segments or a transcript
+    /// supplied with the file.
+    pub wer: Option<f64>,
+    pub quality_preset: Option<String>,
+    pub n_threads: i32,
+    pub created_at: Option<String>,
+}
+
+/// Model suggested for a target processing time per episode.
+#[cfg(feature = "app")]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelRecommendation {
+    pub model: String,
+    /// Expected processing time for an episode of `episode_ms`.
+    pub estimated_ms: i64,
+    pub episode_ms: i64,
+    pub target_ms: i64,
+    /// False when no benchmarked model is fast enough; `model` is then the fastest.
+    pub meets_target: bool,
+    pub wer: Option<f64>,
+}
+
 #[derive(Clone, Serialize)]
 #[serde(tag = "event", content = "data")]
 pub enum TranscriptionEvent {
@@ -22,6 +159,7 @@ pub enum TranscriptionEvent {
     Cancelled,
 }
 
+#[cfg(feature = "app")]
 #[derive(Clone, Serialize)]
 #[serde(tag = "event", content = "data")]
 pub enum ModelDownloadEvent {
@@ -29,3 +167,15 @@ pub enum ModelDownloadEvent {
     Done { model_name: String },
     Error { message: String },
 }
+
+#[cfg(feature = "app")]
+#[derive(Clone, Serialize)]
+#[serde(tag = "event", content = "data")]
+pub enum BenchmarkEvent {
+    Downloading { percent: i32 },
+    ModelStarted { model: String, index: usize, total: usize },
+    Progress { model: String, percent: i32 },
+    ModelDone { result: ModelBenchmark },
+    Done,
+    Error { message: String },
+}
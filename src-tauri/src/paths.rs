@@ -0,0 +1,44 @@
+use std::path::PathBuf;
+#[cfg(feature = "app")]
+use tauri::Manager;
+
+/// Where Binky keeps its database, models and audio cache. The app resolves
+/// them from its Tauri directories, the CLI takes them from arguments; the
+/// pipeline stages only ever see this struct, never an AppHandle.
+#[derive(Debug, Clone)]
+pub struct BinkyPaths {
+    pub db_path: PathBuf,
+    /// Whisper `ggml-*.bin` files; diarization models live in `diarization/`.
+    pub models_dir: PathBuf,
+    /// Episode audio downloaded for a running pipeline.
+    pub cache_dir: PathBuf,
+}
+
+impl BinkyPaths {
+    #[cfg(feature = "app")]
+    pub fn from_app(app: &tauri::AppHandle) -> Result<Self, String> {
+        let path = app.path();
+        Ok(Self {
+            db_path: path
+                .app_data_dir()
+                .map_err(|e| format!("Cannot resolve app data dir: {}", e))?
+                .join("binky.db"),
+            models_dir: path
+                .app_local_data_dir()
+                .map_err(|e| format!("Cannot resolve app local data dir: {}", e))?
+                .join("models"),
+            cache_dir: path
+                .app_cache_dir()
+                .map_err(|e| format!("Cannot resolve cache dir: {}", e))?,
+        })
+    }
+
+    pub fn diarization_models_dir(&self) -> PathBuf {
+        self.models_dir.join("diarization")
+    }
+
+    /// Cached audio file shared by the transcribe and diarize stages.
+    pub fn audio_cache_path(&self, episode_id: i64) -> PathBuf {
+        self.cache_dir.join(format!("episode_{}.mp3", episode_id))
+    }
+}
@@ -0,0 +1,19 @@
+use std::sync::Arc;
+
+/// Receiver of the progress events of a long-running operation. The app hands
+/// in the `Channel` of the command that started it, the CLI prints to stderr;
+/// pipeline stages only ever see this trait.
+pub trait ProgressSink<E>: Send + Sync {
+    fn send(&self, event: E);
+}
+
+/// Shared sink, cloned into jobs and blocking tasks.
+pub type Progress<E> = Arc<dyn ProgressSink<E>>;
+
+#[cfg(feature = "app")]
+impl<E: tauri::ipc::IpcResponse + Send + Sync> ProgressSink<E> for tauri::ipc::Channel<E> {
+    fn send(&self, event: E) {
+        // The webview may already be gone; progress is best-effort
+        let _ = tauri::ipc::Channel::send(self, event);
+    }
+}
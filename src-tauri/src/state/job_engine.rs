@@ -0,0 +1,550 @@
+use crate::models::diarization::DiarizationEvent;
+use crate::models::pipeline::{Stage, StageInfo, StageStatus};
+use crate::models::transcript::TranscriptionEvent;
+use crate::progress::Progress;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+
+/// Outcome of running one stage.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StageResult {
+    Done,
+    Skipped(String),
+    Failed(String),
+    Cancelled,
+}
+
+/// All stages requested for one episode, processed in dependency order.
+pub struct PipelineJob {
+    pub episode_id: i64,
+    pub audio_url: String,
+    /// Higher runs first. Jobs with equal priority keep FIFO order.
+    pub priority: i32,
+    /// Requested stages in canonical order (see `Stage::ALL`).
+    pub stages: Vec<StageInfo>,
+    /// Progress of the command that queued the job: the frontend channel in
+    /// the app, stderr in the CLI (None when resumed after a restart or
+    /// queued internally).
+    pub transcription_events: Option<Progress<TranscriptionEvent>>,
+    pub diarization_events: Option<Progress<DiarizationEvent>>,
+    pub cancel_token: CancellationToken,
+}
+
+impl PipelineJob {
+    pub fn new(episode_id: i64, audio_url: String, stages: &[Stage]) -> Self {
+        let mut job = Self {
+            episode_id,
+            audio_url,
+            priority: 0,
+            stages: vec![],
+            transcription_events: None,
+            diarization_events: None,
+            cancel_token: CancellationToken::new(),
+        };
+        job.request(stages);
+        job
+    }
+
+    /// Add stages (or reset them if they already finished) so they run again.
+    pub fn request(&mut self, stages: &[Stage]) {
+        for stage in stages {
+            match self.stages.iter_mut().find(|s| s.stage == *stage) {
+                Some(info) if info.status.is_final() => {
+                    info.status = StageStatus::Pending;
+                    info.attempts = 0;
+                    info.error = None;
+                }
+                Some(_) => {}
+                None => self.stages.push(StageInfo {
+                    stage: *stage,
+                    status: StageStatus::Pending,
+                    attempts: 0,
+                    error: None,
+                }),
+            }
+        }
+        self.stages.sort_by_key(|s| s.stage);
+    }
+
+    pub fn stage(&self, stage: Stage) -> Option<&StageInfo> {
+        self.stages.iter().find(|s| s.stage == stage)
+    }
+
+    pub fn has_pending(&self, stage: Stage) -> bool {
+        self.stage(stage).map(|s| s.status == StageStatus::Pending).unwrap_or(false)
+    }
+
+    pub fn running_stage(&self) -> Option<Stage> {
+        self.stages
+            .iter()
+            .find(|s| s.status == StageStatus::Running)
+            .map(|s| s.stage)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.stages.iter().all(|s| s.status.is_final())
+    }
+
+    /// Stages of this job that `stage` transitively depends on.
+    fn requirements(&self, stage: Stage) -> Vec<&StageInfo> {
+        self.stages
+            .iter()
+            .filter(|s| depends_on(stage, s.stage))
+            .collect()
+    }
+
+    /// First pending stage whose requirements are all done or skipped.
+    pub fn next_runnable(&self) -> Option<Stage> {
+        if self.running_stage().is_some() {
+            return None;
+        }
+        self.stages
+            .iter()
+            .filter(|s| s.status == StageStatus::Pending)
+            .find(|s| {
+                self.requirements(s.stage)
+                    .iter()
+                    .all(|r| matches!(r.status, StageStatus::Done | StageStatus::Skipped))
+            })
+            .map(|s| s.stage)
+    }
+}
+
+/// True if `stage` needs `other` to finish first (transitively).
+pub fn depends_on(stage: Stage, other: Stage) -> bool {
+    stage
+        .dependencies()
+        .iter()
+        .any(|dep| *dep == other || depends_on(*dep, other))
+}
+
+pub struct JobEngine {
+    pub jobs: VecDeque<PipelineJob>,
+    /// Worker loops currently alive; never more than `worker_slots`.
+    pub workers: usize,
+    /// Parallel pipelines allowed by the CPU budget.
+    pub worker_slots: usize,
+    /// When set, workers finish their running stage and then stop.
+    pub paused: bool,
+}
+
+impl Default for JobEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl JobEngine {
+    pub fn new() -> Self {
+        Self {
+            jobs: VecDeque::new(),
+            workers: 0,
+            worker_slots: 1,
+            paused: false,
+        }
+    }
+
+    #[cfg(feature = "app")]
+    pub fn is_processing(&self) -> bool {
+        self.jobs.iter().any(|j| j.running_stage().is_some())
+    }
+
+    /// Additional worker loops needed: one per job that could start a stage
+    /// now, limited by the free slots. Zero while paused.
+    pub fn workers_wanted(&self) -> usize {
+        if self.paused {
+            return 0;
+        }
+        let runnable = self.jobs.iter().filter(|j| j.next_runnable().is_some()).count();
+        runnable.min(self.worker_slots.saturating_sub(self.workers))
+    }
+
+    pub fn job(&self, episode_id: i64) -> Option<&PipelineJob> {
+        self.jobs.iter().find(|j| j.episode_id == episode_id)
+    }
+
+    fn job_mut(&mut self, episode_id: i64) -> Option<&mut PipelineJob> {
+        self.jobs.iter_mut().find(|j| j.episode_id == episode_id)
+    }
+
+    /// Queue a job behind all jobs with the same or a higher priority. If the
+    /// episode is already queued, the requested stages are merged into it.
+    pub fn enqueue(&mut self, job: PipelineJob) {
+        if let Some(existing) = self.job_mut(job.episode_id) {
+            let stages: Vec<Stage> = job.stages.iter().map(|s| s.stage).collect();
+            existing.request(&stages);
+            if job.transcription_events.is_some() {
+                existing.transcription_events = job.transcription_events;
+            }
+            if job.diarization_events.is_some() {
+                existing.diarization_events = job.diarization_events;
+            }
+            return;
+        }
+        let idx = self
+            .jobs
+            .iter()
+            .position(|j| j.priority < job.priority)
+            .unwrap_or(self.jobs.len());
+        self.jobs.insert(idx, job);
+    }
+
+    /// Pick the next stage to run, mark it running and count the attempt.
+    /// Jobs are served in queue order and run one stage at a time, so with a
+    /// single worker one episode's pipeline completes before the next starts;
+    /// further workers pick up the next jobs in the queue.
+    pub fn start_next_stage(&mut self) -> Option<(i64, Stage)> {
+        for job in self.jobs.iter_mut() {
+            if let Some(stage) = job.next_runnable() {
+                let info = job.stages.iter_mut().find(|s| s.stage == stage)?;
+                info.status = StageStatus::Running;
+                info.attempts += 1;
+                return Some((job.episode_id, stage));
+            }
+        }
+        None
+    }
+
+    /// Record the outcome of a stage. Failures are retried until
+    /// `Stage::max_attempts`; a final failure blocks every dependent stage and
+    /// a cancellation cancels the rest of the job. Returns all changed stages.
+    pub fn finish_stage(&mut self, episode_id: i64, stage: Stage, result: StageResult) -> Vec<StageInfo> {
+        let job = match self.job_mut(episode_id) {
+            Some(j) => j,
+            None => return vec![],
+        };
+        let mut changed = vec![];
+
+        let (status, error) = match result {
+            StageResult::Done => (StageStatus::Done, None),
+            StageResult::Skipped(reason) => (StageStatus::Skipped, Some(reason)),
+            StageResult::Cancelled => (StageStatus::Cancelled, None),
+            StageResult::Failed(e) => {
+                let attempts = job.stage(stage).map(|s| s.attempts).unwrap_or(0);
+                if attempts < stage.max_attempts() && !job.cancel_token.is_cancelled() {
+                    (StageStatus::Pending, Some(e))
+                } else {
+                    (StageStatus::Failed, Some(e))
+                }
+            }
+        };
+
+        if let Some(info) = job.stages.iter_mut().find(|s| s.stage == stage) {
+            info.status = status;
+            info.error = error;
+            changed.push(info.clone());
+        }
+
+        match status {
+            StageStatus::Failed => {
+                let reason = format!(
+                    "Vorherige Stufe '{}' fehlgeschlagen: {}",
+                    stage.as_str(),
+                    changed
+                        .first()
+                        .and_then(|c| c.error.as_deref())
+                        .unwrap_or("unbekannter Fehler")
+                );
+                for info in job.stages.iter_mut() {
+                    if info.status == StageStatus::Pending && depends_on(info.stage, stage) {
+                        info.status = StageStatus::Blocked;
+                        info.error = Some(reason.clone());
+                        changed.push(info.clone());
+                    }
+                }
+            }
+            StageStatus::Cancelled => {
+                for info in job.stages.iter_mut() {
+                    if info.status == StageStatus::Pending {
+                        info.status = StageStatus::Cancelled;
+                        changed.push(info.clone());
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        changed
+    }
+
+    /// Cancel a job: the running stage is signalled through the job's token,
+    /// pending stages are cancelled right away. Returns the changed stages.
+    #[cfg(feature = "app")]
+    pub fn cancel(&mut self, episode_id: i64) -> Vec<StageInfo> {
+        let job = match self.job_mut(episode_id) {
+            Some(j) => j,
+            None => return vec![],
+        };
+        job.cancel_token.cancel();
+        let mut changed = vec![];
+        for info in job.stages.iter_mut() {
+            if info.status == StageStatus::Pending {
+                info.status = StageStatus::Cancelled;
+                changed.push(info.clone());
+            }
+        }
+        changed
+    }
+
+    /// Remove all jobs whose stages are final.
+    pub fn take_finished(&mut self) -> Vec<PipelineJob> {
+        let (finished, remaining): (Vec<_>, Vec<_>) =
+            self.jobs.drain(..).partition(|j| j.is_finished());
+        self.jobs = remaining.into();
+        finished
+    }
+
+    /// Remove a job that has no running stage.
+    #[cfg(feature = "app")]
+    pub fn remove(&mut self, episode_id: i64) -> Result<PipelineJob, String> {
+        let idx = self
+            .jobs
+            .iter()
+            .position(|j| j.episode_id == episode_id)
+            .ok_or_else(|| format!("Episode {} ist nicht in der Warteschlange", episode_id))?;
+        if self.jobs[idx].running_stage().is_some() {
+            return Err("Laufende Jobs können nur abgebrochen werden".to_string());
+        }
+        Ok(self.jobs.remove(idx).unwrap())
+    }
+
+    /// Move a job to `position` (0 = next). The job adopts the priority of its
+    /// new predecessor (or successor at the front) so that later
+    /// priority-based inserts keep the manual order intact.
+    #[cfg(feature = "app")]
+    pub fn move_to(&mut self, episode_id: i64, position: usize) -> bool {
+        let idx = match self.jobs.iter().position(|j| j.episode_id == episode_id) {
+            Some(i) => i,
+            None => return false,
+        };
+        let mut job = self.jobs.remove(idx).unwrap();
+        let position = position.min(self.jobs.len());
+        let neighbour = if position > 0 {
+            self.jobs.get(position - 1)
+        } else {
+            self.jobs.front()
+        };
+        if let Some(n) = neighbour {
+            job.priority = n.priority;
+        }
+        self.jobs.insert(position, job);
+        true
+    }
+
+    /// Change the priority of a job and re-position it accordingly.
+    #[cfg(feature = "app")]
+    pub fn set_priority(&mut self, episode_id: i64, priority: i32) -> bool {
+        let idx = match self.jobs.iter().position(|j| j.episode_id == episode_id) {
+            Some(i) => i,
+            None => return false,
+        };
+        let mut job = self.jobs.remove(idx).unwrap();
+        job.priority = priority;
+        self.enqueue(job);
+        true
+    }
+
+    /// Reset a failed, blocked or cancelled stage (and everything that depends
+    /// on it) of a queued job. Returns the changed stages.
+    #[cfg(feature = "app")]
+    pub fn retry(&mut self, episode_id: i64, stage: Stage) -> Vec<StageInfo> {
+        let job = match self.job_mut(episode_id) {
+            Some(j) => j,
+            None => return vec![],
+        };
+        if job.cancel_token.is_cancelled() {
+            job.cancel_token = CancellationToken::new();
+        }
+        let mut changed = vec![];
+        for info in job.stages.iter_mut() {
+            let affected = info.stage == stage || depends_on(info.stage, stage);
+            if affected
+                && matches!(
+                    info.status,
+                    StageStatus::Failed | StageStatus::Blocked | StageStatus::Cancelled
+                )
+            {
+                info.status = StageStatus::Pending;
+                info.attempts = 0;
+                info.error = None;
+                changed.push(info.clone());
+            }
+        }
+        changed
+    }
+
+    /// (episode_id, priority) of all jobs in execution order.
+    pub fn order(&self) -> Vec<(i64, i32)> {
+        self.jobs.iter().map(|j| (j.episode_id, j.priority)).collect()
+    }
+
+    /// Episode currently working towards `stage`: running it, or running one
+    /// of its prerequisites (e.g. downloading audio for it).
+    #[cfg(feature = "app")]
+    pub fn active_for(&self, stage: Stage) -> Option<i64> {
+        self.jobs
+            .iter()
+            .find(|j| match j.running_stage() {
+                Some(running) if running == stage => true,
+                Some(running) => j.has_pending(stage) && depends_on(stage, running),
+                None => false,
+            })
+            .map(|j| j.episode_id)
+    }
+
+    /// Number of jobs waiting to run `stage` that have not started yet.
+    #[cfg(feature = "app")]
+    pub fn waiting_for(&self, stage: Stage) -> usize {
+        self.jobs
+            .iter()
+            .filter(|j| j.running_stage().is_none() && j.has_pending(stage))
+            .count()
+    }
+}
+
+pub struct EngineState {
+    pub engine: Mutex<JobEngine>,
+    /// Cancellation tokens of running range re-transcriptions, by episode
+    /// (they run outside the job queue).
+    pub range_runs: Mutex<HashMap<i64, CancellationToken>>,
+}
+
+impl Default for EngineState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EngineState {
+    pub fn new() -> Self {
+        Self {
+            engine: Mutex::new(JobEngine::new()),
+            range_runs: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FULL: [Stage; 5] = [
+        Stage::Download,
+        Stage::Transcribe,
+        Stage::Diarize,
+        Stage::BackfillText,
+        Stage::Index,
+    ];
+
+    fn status(engine: &JobEngine, episode_id: i64, stage: Stage) -> StageStatus {
+        engine.job(episode_id).unwrap().stage(stage).unwrap().status
+    }
+
+    #[test]
+    fn stages_run_in_dependency_order() {
+        let mut engine = JobEngine::new();
+        engine.enqueue(PipelineJob::new(1, String::new(), &[Stage::Index, Stage::Download, Stage::Transcribe]));
+
+        let mut order = vec![];
+        while let Some((id, stage)) = engine.start_next_stage() {
+            order.push(stage);
+            engine.finish_stage(id, stage, StageResult::Done);
+        }
+        assert_eq!(order, vec![Stage::Download, Stage::Transcribe, Stage::Index]);
+    }
+
+    #[test]
+    fn one_pipeline_completes_before_the_next_starts() {
+        let mut engine = JobEngine::new();
+        engine.enqueue(PipelineJob::new(1, String::new(), &FULL));
+        engine.enqueue(PipelineJob::new(2, String::new(), &FULL));
+
+        let mut episodes = vec![];
+        while let Some((id, stage)) = engine.start_next_stage() {
+            episodes.push(id);
+            engine.finish_stage(id, stage, StageResult::Done);
+        }
+        assert_eq!(episodes, vec![1, 1, 1, 1, 1, 2, 2, 2, 2, 2]);
+    }
+
+    #[test]
+    fn free_slots_start_the_next_pipelines_in_parallel() {
+        let mut engine = JobEngine::new();
+        engine.worker_slots = 2;
+        for id in 1..=3 {
+            engine.enqueue(PipelineJob::new(id, String::new(), &FULL));
+        }
+        assert_eq!(engine.workers_wanted(), 2);
+
+        engine.workers = 2;
+        assert_eq!(engine.start_next_stage(), Some((1, Stage::Download)));
+        assert_eq!(engine.start_next_stage(), Some((2, Stage::Download)));
+        assert_eq!(engine.workers_wanted(), 0);
+
+        engine.paused = true;
+        engine.workers = 0;
+        assert_eq!(engine.workers_wanted(), 0);
+    }
+
+    #[test]
+    fn failures_retry_then_block_dependents() {
+        let mut engine = JobEngine::new();
+        engine.enqueue(PipelineJob::new(1, String::new(), &FULL));
+
+        for _ in 0..Stage::Download.max_attempts() {
+            let (id, stage) = engine.start_next_stage().unwrap();
+            assert_eq!(stage, Stage::Download);
+            engine.finish_stage(id, stage, StageResult::Failed("404".to_string()));
+        }
+
+        assert_eq!(status(&engine, 1, Stage::Download), StageStatus::Failed);
+        assert_eq!(status(&engine, 1, Stage::Transcribe), StageStatus::Blocked);
+        assert_eq!(status(&engine, 1, Stage::Index), StageStatus::Blocked);
+        assert!(engine.start_next_stage().is_none());
+        assert_eq!(engine.take_finished().len(), 1);
+    }
+
+    #[test]
+    fn skipped_stage_satisfies_dependents() {
+        let mut engine = JobEngine::new();
+        engine.enqueue(PipelineJob::new(1, String::new(), &FULL));
+
+        while let Some((id, stage)) = engine.start_next_stage() {
+            let result = if stage == Stage::Diarize {
+                StageResult::Skipped("keine Modelle".to_string())
+            } else {
+                StageResult::Done
+            };
+            engine.finish_stage(id, stage, result);
+        }
+        assert_eq!(status(&engine, 1, Stage::Index), StageStatus::Done);
+    }
+
+    #[cfg(feature = "app")]
+    #[test]
+    fn cancel_stops_pending_stages_and_retry_resets_them() {
+        let mut engine = JobEngine::new();
+        engine.enqueue(PipelineJob::new(1, String::new(), &FULL));
+        let (id, stage) = engine.start_next_stage().unwrap();
+
+        engine.cancel(1);
+        engine.finish_stage(id, stage, StageResult::Cancelled);
+        assert!(engine.job(1).unwrap().is_finished());
+
+        let changed = engine.retry(1, Stage::Download);
+        assert_eq!(changed.len(), FULL.len());
+        assert_eq!(engine.start_next_stage(), Some((1, Stage::Download)));
+    }
+
+    #[test]
+    fn merging_a_queued_episode_adds_stages() {
+        let mut engine = JobEngine::new();
+        engine.enqueue(PipelineJob::new(1, String::new(), &[Stage::Download, Stage::Diarize]));
+        engine.enqueue(PipelineJob::new(1, String::new(), &[Stage::Download, Stage::Transcribe]));
+
+        assert_eq!(engine.jobs.len(), 1);
+        let stages: Vec<Stage> = engine.jobs[0].stages.iter().map(|s| s.stage).collect();
+        assert_eq!(stages, vec![Stage::Download, Stage::Transcribe, Stage::Diarize]);
+    }
+}
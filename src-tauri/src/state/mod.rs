@@ -1,2 +1 @@
-pub mod transcription_queue;
-pub mod diarization_queue;
+pub mod job_engine;
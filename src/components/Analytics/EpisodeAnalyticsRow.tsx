@@ -32,6 +32,17 @@ export default function EpisodeAnalyticsRow({
       year: 'numeric',
     });
 
+  const seconds = (ms: number) => Math.round(ms / 100) / 10;
+
+  // Turn-taking per host: turn lengths, reply gap and whom they interrupt
+  const hostTurnTaking = [
+    { speaker: 'SPEAKER_0', name: hostProfile.host0Name, other: 'SPEAKER_1', otherName: hostProfile.host1Name },
+    { speaker: 'SPEAKER_1', name: hostProfile.host1Name, other: 'SPEAKER_0', otherName: hostProfile.host0Name },
+  ].flatMap(({ speaker, name, other, otherName }) => {
+    const s = stats.turnTaking?.speakers.find((sp) => sp.speaker === speaker);
+    return s ? [{ name, otherName, stats: s, interrupts: s.interrupts[other] ?? 0 }] : [];
+  });
+
   const isSolo = stats.diarizationStatus === 'solo';
   const isDone = stats.diarizationStatus === 'done';
   const isNotStarted = stats.diarizationStatus === 'not_started' || stats.diarizationStatus === 'error';
@@ -109,6 +120,26 @@ export default function EpisodeAnalyticsRow({
             {hostProfile.host1Name}: {stats.host1Minutes} Min. &middot; {stats.host1Turns}{' '}
             {t('pages.analytics.turns')}
           </div>
+          {stats.overlapMinutes > 0 && (
+            <div>
+              {t('pages.analytics.overlap')}: {stats.overlapMinutes} Min.
+            </div>
+          )}
+          {hostTurnTaking.map(({ name, otherName, stats: s, interrupts }) => (
+            <div key={name} style={{ fontSize: 12, color: 'var(--text-secondary, #888)' }}>
+              {name}:{' '}
+              {t('pages.analytics.turn_lengths', {
+                median: seconds(s.median_turn_ms),
+                longest: seconds(s.longest_turn_ms),
+              })}
+              {s.avg_reply_gap_ms !== null && (
+                <> &middot; {t('pages.analytics.reply_gap', { seconds: seconds(s.avg_reply_gap_ms) })}</>
+              )}
+              {interrupts > 0 && (
+                <> &middot; {t('pages.analytics.interrupts', { name: otherName, count: interrupts })}</>
+              )}
+            </div>
+          ))}
 
           <div className="analytics-episode-actions">
             <button
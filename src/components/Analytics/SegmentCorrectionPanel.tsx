@@ -1,5 +1,6 @@
 import { useState, useEffect } from 'react';
 import { useTranslation } from 'react-i18next';
+import { invoke } from '@tauri-apps/api/core';
 import { SegmentRow } from '../../hooks/useAnalytics';
 
 interface HostProfile {
@@ -9,6 +10,16 @@ interface HostProfile {
   host1Color: string;
 }
 
+interface PropagationSuggestion {
+  segment_id: number;
+  start_ms: number;
+  end_ms: number;
+  text: string | null;
+  current_speaker: string;
+  suggested_speaker: string;
+  similarity: number;
+}
+
 interface Props {
   episodeId: number;
   hostProfile: HostProfile;
@@ -19,6 +30,14 @@ interface Props {
   onClose: () => void;
 }
 
+// Guests have no profile colour; cycle a fixed palette by guest number
+const GUEST_COLORS = ['#8e7cc3', '#6aa84f', '#e69138', '#3d85c6', '#c27ba0', '#999999'];
+
+function guestIndex(speaker: string): number {
+  const match = /^SPEAKER_(\d+)$/.exec(speaker);
+  return match ? Number(match[1]) - 2 : -1;
+}
+
 function formatTime(ms: number): string {
   const totalSecs = Math.floor(ms / 1000);
   const mins = Math.floor(totalSecs / 60);
@@ -40,6 +59,9 @@ export default function SegmentCorrectionPanel({
   const [loading, setLoading] = useState(true);
   const [visibleCount, setVisibleCount] = useState(100);
   const [confirmReanalyze, setConfirmReanalyze] = useState(false);
+  const [suggestions, setSuggestions] = useState<PropagationSuggestion[] | null>(null);
+  const [accepted, setAccepted] = useState<Set<number>>(new Set());
+  const [propagationError, setPropagationError] = useState<string | null>(null);
 
   useEffect(() => {
     loadSegments(episodeId)
@@ -50,9 +72,8 @@ export default function SegmentCorrectionPanel({
       .catch(console.error);
   }, [episodeId]);
 
-  const handleSegmentClick = async (seg: SegmentRow) => {
-    const current = seg.corrected_speaker ?? seg.speaker_label;
-    const next = current === 'SPEAKER_0' ? 'SPEAKER_1' : 'SPEAKER_0';
+  const handleSpeakerChange = async (seg: SegmentRow, next: string) => {
+    if (next === (seg.corrected_speaker ?? seg.speaker_label)) return;
     await correctSegment(seg.id, next);
     // Update local state optimistically
     setSegments((prev) =>
@@ -66,22 +87,84 @@ export default function SegmentCorrectionPanel({
     setSegments(refreshed);
   };
 
-  const getSpeakerName = (seg: SegmentRow) => {
-    const effective = seg.corrected_speaker ?? seg.speaker_label;
-    return effective === 'SPEAKER_0' ? hostProfile.host0Name : hostProfile.host1Name;
+  // Segments whose voice matches the ones corrected by hand
+  const handleSuggest = async () => {
+    setPropagationError(null);
+    try {
+      const result = await invoke<PropagationSuggestion[]>('suggest_speaker_propagation', {
+        episodeId,
+        minSimilarity: null,
+      });
+      setSuggestions(result);
+      setAccepted(new Set(result.map((s) => s.segment_id)));
+    } catch (e) {
+      setSuggestions(null);
+      setPropagationError(String(e));
+    }
+  };
+
+  const handleApplySuggestions = async () => {
+    if (!suggestions) return;
+    const assignments = suggestions
+      .filter((s) => accepted.has(s.segment_id))
+      .map((s) => ({ segment_id: s.segment_id, speaker: s.suggested_speaker }));
+    try {
+      await invoke('apply_speaker_propagation', { episodeId, assignments });
+      setSuggestions(null);
+      const refreshed = await loadSegments(episodeId);
+      setSegments(refreshed);
+    } catch (e) {
+      setPropagationError(String(e));
+    }
+  };
+
+  const toggleAccepted = (segmentId: number) => {
+    setAccepted((prev) => {
+      const next = new Set(prev);
+      if (next.has(segmentId)) next.delete(segmentId);
+      else next.add(segmentId);
+      return next;
+    });
+  };
+
+  // SPEAKER_0/1 are the hosts, SPEAKER_2 and up are guests numbered from 1
+  const speakerName = (speaker: string) => {
+    if (speaker === 'SPEAKER_0') return hostProfile.host0Name;
+    if (speaker === 'SPEAKER_1') return hostProfile.host1Name;
+    const index = guestIndex(speaker);
+    return index >= 0 ? t('pages.analytics.guest_n', { n: index + 1 }) : speaker;
   };
 
   const getSpeakerColor = (seg: SegmentRow) => {
     const effective = seg.corrected_speaker ?? seg.speaker_label;
-    return effective === 'SPEAKER_0' ? hostProfile.host0Color : hostProfile.host1Color;
+    if (effective === 'SPEAKER_0') return hostProfile.host0Color;
+    if (effective === 'SPEAKER_1') return hostProfile.host1Color;
+    const index = guestIndex(effective);
+    return index >= 0 ? GUEST_COLORS[index % GUEST_COLORS.length] : 'var(--color-border)';
   };
 
+  // Both hosts, every guest seen in this episode and one fresh guest label
+  const guestLabels = Array.from(
+    new Set(
+      segments
+        .flatMap((s) => [s.speaker_label, s.corrected_speaker])
+        .filter((l): l is string => !!l && guestIndex(l) >= 0)
+    )
+  ).sort((a, b) => guestIndex(a) - guestIndex(b));
+  const nextGuest = `SPEAKER_${
+    guestLabels.reduce((max, l) => Math.max(max, guestIndex(l)), -1) + 3
+  }`;
+  const speakerOptions = ['SPEAKER_0', 'SPEAKER_1', ...guestLabels, nextGuest];
+
   return (
     <div className="segment-correction-panel" onClick={(e) => e.stopPropagation()}>
       <div className="correction-actions">
         <button className="btn-outline" onClick={handleFlipAll}>
           {t('pages.analytics.flip_speakers')}
         </button>
+        <button className="btn-outline" onClick={handleSuggest}>
+          {t('pages.analytics.propagate_suggest')}
+        </button>
         <button
           className="btn-outline btn-danger-outline"
           onClick={() => setConfirmReanalyze(true)}
@@ -117,6 +200,57 @@ export default function SegmentCorrectionPanel({
         </div>
       )}
 
+      {propagationError && (
+        <div style={{ padding: 8, color: 'var(--text-secondary)', fontSize: 12 }}>
+          {propagationError}
+        </div>
+      )}
+
+      {suggestions && (
+        <div className="model-confirm-box">
+          {suggestions.length === 0 ? (
+            <p>{t('pages.analytics.propagate_none')}</p>
+          ) : (
+            <>
+              <p>{t('pages.analytics.propagate_preview', { count: suggestions.length })}</p>
+              {suggestions.map((s) => (
+                <label key={s.segment_id} className="segment-row">
+                  <input
+                    type="checkbox"
+                    checked={accepted.has(s.segment_id)}
+                    onChange={() => toggleAccepted(s.segment_id)}
+                  />
+                  <span className="segment-time">
+                    {formatTime(s.start_ms)} – {formatTime(s.end_ms)}
+                  </span>
+                  <span style={{ fontSize: 12 }}>
+                    {speakerName(s.current_speaker)} → {speakerName(s.suggested_speaker)}
+                  </span>
+                  <span style={{ fontSize: 10, color: 'var(--text-secondary)' }}>
+                    {Math.round(s.similarity * 100)}%
+                  </span>
+                  {s.text && (
+                    <span style={{ fontSize: 11, color: 'var(--text-secondary)', overflow: 'hidden', textOverflow: 'ellipsis', whiteSpace: 'nowrap' }}>
+                      {s.text}
+                    </span>
+                  )}
+                </label>
+              ))}
+            </>
+          )}
+          <div className="model-confirm-actions">
+            {suggestions.length > 0 && (
+              <button className="btn-outline" disabled={accepted.size === 0} onClick={handleApplySuggestions}>
+                {t('pages.analytics.propagate_apply', { count: accepted.size })}
+              </button>
+            )}
+            <button className="btn-outline" onClick={() => setSuggestions(null)}>
+              Abbrechen
+            </button>
+          </div>
+        </div>
+      )}
+
       {loading ? (
         <div style={{ padding: 8, color: 'var(--text-secondary)', fontSize: 12 }}>
           Lade Segmente...
@@ -127,7 +261,6 @@ export default function SegmentCorrectionPanel({
             <div
               key={seg.id}
               className={`segment-row ${seg.corrected_speaker ? 'segment-row-corrected' : ''}`}
-              onClick={() => handleSegmentClick(seg)}
             >
               <span className="segment-time">
                 {formatTime(seg.start_ms)} – {formatTime(seg.end_ms)}
@@ -136,7 +269,19 @@ export default function SegmentCorrectionPanel({
                 className="segment-speaker-dot"
                 style={{ backgroundColor: getSpeakerColor(seg) }}
               />
-              <span style={{ fontSize: 12 }}>{getSpeakerName(seg)}</span>
+              <select
+                value={seg.corrected_speaker ?? seg.speaker_label}
+                onChange={(e) => handleSpeakerChange(seg, e.target.value)}
+                style={{ fontSize: 12 }}
+              >
+                {speakerOptions.map((label) => (
+                  <option key={label} value={label}>
+                    {label === nextGuest
+                      ? t('pages.analytics.guest_new')
+                      : speakerName(label)}
+                  </option>
+                ))}
+              </select>
               {seg.corrected_speaker && (
                 <span style={{ fontSize: 10, color: 'var(--text-secondary)', marginLeft: 4 }}>
                   ↩
@@ -0,0 +1,120 @@
+import { useState } from 'react';
+import { LineChart, Line, XAxis, YAxis, Tooltip, Legend } from 'recharts';
+import { useTranslation } from 'react-i18next';
+import { EpisodeStats, HostProfile } from '../../hooks/useAnalytics';
+
+type Metric = 'interruptions' | 'median_turn' | 'reply_gap';
+
+interface Props {
+  episodes: EpisodeStats[];
+  hostProfile: HostProfile;
+}
+
+/** Value of one host in one episode; null when the host has no turns or replies */
+function metricValue(episode: EpisodeStats, speaker: string, metric: Metric): number | null {
+  const s = episode.turnTaking?.speakers.find((sp) => sp.speaker === speaker);
+  if (!s) return null;
+  switch (metric) {
+    case 'interruptions':
+      return Object.values(s.interrupts).reduce((sum, n) => sum + n, 0);
+    case 'median_turn':
+      return Math.round(s.median_turn_ms / 100) / 10;
+    case 'reply_gap':
+      return s.avg_reply_gap_ms === null ? null : Math.round(s.avg_reply_gap_ms / 100) / 10;
+  }
+}
+
+export default function TurnTakingTrendChart({ episodes, hostProfile }: Props) {
+  const { t } = useTranslation();
+  const [metric, setMetric] = useState<Metric>('interruptions');
+
+  const data = episodes
+    .filter((e) => e.turnTaking !== null && e.diarizationStatus === 'done')
+    .sort((a, b) => a.publishDate.localeCompare(b.publishDate))
+    .map((e) => ({
+      label: e.title.length > 20 ? e.title.slice(0, 18) + '…' : e.title,
+      title: e.title,
+      host0: metricValue(e, 'SPEAKER_0', metric),
+      host1: metricValue(e, 'SPEAKER_1', metric),
+    }));
+
+  if (data.length < 2) {
+    return (
+      <div style={{ color: 'var(--text-secondary, #888)', fontSize: 12, padding: '12px 0' }}>
+        {t('pages.analytics.trend_min_episodes')}
+      </div>
+    );
+  }
+
+  const chartWidth = Math.max(600, data.length * 24);
+  const unit = metric === 'interruptions' ? '×' : ' s';
+
+  return (
+    <div className="analytics-trend-section">
+      <h3
+        style={{
+          margin: '0 0 12px 0',
+          fontSize: 13,
+          fontWeight: 600,
+          textTransform: 'uppercase',
+          letterSpacing: '0.05em',
+          color: 'var(--text-secondary, #888)',
+          display: 'flex',
+          alignItems: 'center',
+          gap: 8,
+        }}
+      >
+        {t('pages.analytics.turn_taking_title')}
+        <select
+          value={metric}
+          onChange={(e) => setMetric(e.target.value as Metric)}
+          style={{ marginLeft: 'auto', fontSize: 12, textTransform: 'none' }}
+        >
+          <option value="interruptions">{t('pages.analytics.turn_taking_interruptions')}</option>
+          <option value="median_turn">{t('pages.analytics.turn_taking_median_turn')}</option>
+          <option value="reply_gap">{t('pages.analytics.turn_taking_reply_gap')}</option>
+        </select>
+      </h3>
+      <div style={{ overflowX: 'auto', width: '100%' }}>
+        <LineChart
+          width={chartWidth}
+          height={200}
+          data={data}
+          margin={{ top: 8, right: 16, left: -20, bottom: 0 }}
+        >
+          <XAxis dataKey="label" tick={false} axisLine={false} tickLine={false} />
+          <YAxis
+            tickFormatter={(v: number) => `${v}${unit}`}
+            tick={{ fontSize: 11 }}
+            axisLine={false}
+            tickLine={false}
+          />
+          <Tooltip
+            labelFormatter={(_, payload) => payload?.[0]?.payload?.title ?? ''}
+            formatter={(value) => `${value ?? ''}${unit}`}
+            contentStyle={{ fontSize: 12 }}
+          />
+          <Legend wrapperStyle={{ fontSize: 12 }} />
+          <Line
+            dataKey="host0"
+            name={hostProfile.host0Name}
+            stroke={hostProfile.host0Color}
+            strokeWidth={2}
+            dot={{ r: 3 }}
+            activeDot={{ r: 5 }}
+            connectNulls
+          />
+          <Line
+            dataKey="host1"
+            name={hostProfile.host1Name}
+            stroke={hostProfile.host1Color}
+            strokeWidth={2}
+            dot={{ r: 3 }}
+            activeDot={{ r: 5 }}
+            connectNulls
+          />
+        </LineChart>
+      </div>
+    </div>
+  );
+}
@@ -1,10 +1,19 @@
 import { useState } from 'react';
 import { useTranslation } from 'react-i18next';
-import { useDiarizationModel } from '../../hooks/useDiarizationModel';
+import { useDiarizationModel, DiarizationModelInfo } from '../../hooks/useDiarizationModel';
+
+const PROVIDER_OPTIONS = ['cpu', 'cuda', 'coreml'];
+
+function modelLabel(model: DiarizationModelInfo): string {
+  return `${model.name} · ${model.language} · ${model.size_mb} MB`;
+}
 
 export default function DiarizationModelManager() {
   const { t } = useTranslation();
   const {
+    models,
+    settings,
+    updateSettings,
     segmentationDownloaded,
     embeddingDownloaded,
     allDownloaded,
@@ -43,6 +52,74 @@ export default function DiarizationModelManager() {
         </span>
       </div>
 
+      {/* Model selection */}
+      {settings && (
+        <>
+          <div className="settings-row">
+            <span className="settings-row-label">
+              {t('pages.settings.diarization_segmentation_model')}
+            </span>
+            <select
+              className="model-language-select"
+              value={settings.segmentation_model}
+              disabled={downloading}
+              onChange={(e) => void updateSettings({ segmentationModel: e.target.value })}
+            >
+              {models
+                .filter((m) => m.role === 'segmentation')
+                .map((m) => (
+                  <option key={m.id} value={m.id}>
+                    {modelLabel(m)}
+                  </option>
+                ))}
+            </select>
+          </div>
+
+          <div className="settings-row">
+            <span className="settings-row-label">
+              {t('pages.settings.diarization_embedding_model')}
+            </span>
+            <select
+              className="model-language-select"
+              value={settings.embedding_model}
+              disabled={downloading}
+              onChange={(e) => void updateSettings({ embeddingModel: e.target.value })}
+            >
+              {models
+                .filter((m) => m.role === 'embedding')
+                .map((m) => (
+                  <option key={m.id} value={m.id}>
+                    {modelLabel(m)}
+                  </option>
+                ))}
+            </select>
+          </div>
+
+          <div className="settings-row">
+            <span className="settings-row-label">
+              {t('pages.settings.diarization_provider')}
+            </span>
+            <select
+              className="model-language-select"
+              value={settings.provider}
+              onChange={(e) => void updateSettings({ provider: e.target.value })}
+            >
+              {PROVIDER_OPTIONS.map((provider) => (
+                <option key={provider} value={provider}>
+                  {provider.toUpperCase()}
+                </option>
+              ))}
+            </select>
+          </div>
+
+          <div className="settings-row">
+            <span className="settings-row-label" style={{ color: 'var(--text-muted, #888)', fontSize: '0.85em' }}>
+              {t('pages.settings.diarization_threads', { count: settings.num_threads })}
+            </span>
+          </div>
+        </>
+      )}
+
       {/* Model status rows */}
       {loading ? (
         <div className="settings-row">
@@ -100,7 +177,11 @@ export default function DiarizationModelManager() {
         {/* Size info */}
         <div className="settings-row">
           <span className="settings-row-label" style={{ color: 'var(--text-muted, #888)', fontSize: '0.85em' }}>
-            {t('pages.settings.diarization_size_info')}
+            {t('pages.settings.diarization_size_info', {
+              size: models
+                .filter((m) => m.selected)
+                .reduce((sum, m) => sum + m.size_mb, 0),
+            })}
           </span>
         </div>
 
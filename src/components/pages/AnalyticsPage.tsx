@@ -6,6 +6,7 @@ import { useDiarization } from '../../hooks/useDiarization';
 import HostConfirmation from '../Analytics/HostConfirmation';
 import DashboardSummary from '../Analytics/DashboardSummary';
 import EpisodeAnalyticsList from '../Analytics/EpisodeAnalyticsList';
+import TurnTakingTrendChart from '../Analytics/TurnTakingTrendChart';
 
 export default function AnalyticsPage() {
   const { t } = useTranslation();
@@ -107,6 +108,7 @@ export default function AnalyticsPage() {
           ) : (
             <>
               <DashboardSummary aggregate={aggregate} hostProfile={hostProfile} />
+              <TurnTakingTrendChart episodes={episodes} hostProfile={hostProfile} />
               <EpisodeAnalyticsList
                 episodes={episodes}
                 hostProfile={hostProfile}
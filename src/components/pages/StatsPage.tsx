@@ -3,7 +3,9 @@ import { useTranslation } from 'react-i18next';
 import { BarChart, Bar, XAxis, YAxis, Tooltip, ResponsiveContainer, Cell } from 'recharts';
 import HostTrendChart from '../Analytics/HostTrendChart';
 import Database from '@tauri-apps/plugin-sql';
+import { invoke } from '@tauri-apps/api/core';
 import { getSetting, setSetting } from '../../lib/settings';
+import type { EpisodeSpeakingTime } from '../../hooks/useAnalytics';
 
 interface SpeechStat {
   speaker: string;
@@ -34,6 +36,22 @@ interface InnuendoResult {
   count: number;
 }
 
+interface TrackedText {
+  text: string;
+  language: string | null;
+}
+
+/** Matches of every word group in the (lower-cased) text */
+function countWordGroups(groups: WordGroup[], text: string): InnuendoResult[] {
+  return groups.map(group => ({
+    label: group.label,
+    count: group.words.reduce((sum, word) => {
+      const escaped = word.toLowerCase().replace(/[.*+?^${}()|[\]\\]/g, '\\$&');
+      return sum + (text.match(new RegExp(escaped, 'g')) ?? []).length;
+    }, 0),
+  }));
+}
+
 /** Parse innuendo_words setting — handles both old string[] and new WordGroup[] format */
 export function parseWordGroups(raw: string | null): WordGroup[] {
   if (!raw) return [];
@@ -57,7 +75,9 @@ export default function StatsPage() {
   const [trend, setTrend] = useState<TrendPoint[]>([]);
   const [topics, setTopics] = useState<Topic[]>([]);
   const [groups, setGroups] = useState<WordGroup[]>([]);
-  const [innuendos, setInnuendos] = useState<InnuendoResult[]>([]);
+  const [trackedTexts, setTrackedTexts] = useState<TrackedText[]>([]);
+  // Transcript language the word tracker counts in ('' = all languages)
+  const [trackerLanguage, setTrackerLanguage] = useState('');
   const [host0Name, setHost0Name] = useState('Sprecher 1');
   const [host1Name, setHost1Name] = useState('Sprecher 2');
   const [host0Color, setHost0Color] = useState('#d97757');
@@ -81,41 +101,36 @@ export default function StatsPage() {
       setHost0Color(c0 ?? '#d97757');
       setHost1Color(c1 ?? '#5B8C5A');
 
-      // Aggregate speech proportion
-      const rows = await db.select<{ spk: string; total_ms: number }[]>(
-        `SELECT COALESCE(corrected_speaker, speaker_label) as spk,
-                SUM(end_ms - start_ms) as total_ms
-         FROM diarization_segments GROUP BY spk`
-      );
-      const totalMs = rows.reduce((s, r) => s + r.total_ms, 0);
+      // Aggregate speech proportion. Overlapping speech is split evenly among
+      // the speakers talking at once (get_speaking_time).
+      const times = await invoke<EpisodeSpeakingTime[]>('get_speaking_time', { episodeId: null });
+      const totals = new Map<string, number>();
+      for (const time of times) {
+        for (const s of time.speakers) totals.set(s.speaker, (totals.get(s.speaker) ?? 0) + s.ms);
+      }
+      const totalMs = [...totals.values()].reduce((sum, ms) => sum + ms, 0);
       setSpeech(
-        rows
-          .map(r => ({
-            speaker: r.spk,
-            ms: r.total_ms,
-            percent: totalMs > 0 ? Math.round((r.total_ms / totalMs) * 100) : 0,
+        [...totals.entries()]
+          .sort(([a], [b]) => a.localeCompare(b))
+          .map(([speaker, ms]) => ({
+            speaker,
+            ms,
+            percent: totalMs > 0 ? Math.round((ms / totalMs) * 100) : 0,
           }))
           .filter(s => s.percent >= 1)
       );
 
       // Per-episode trend
-      const epRows = await db.select<{ title: string; publish_date: string; spk: string; ms: number }[]>(
-        `SELECT e.title, e.publish_date,
-                COALESCE(ds.corrected_speaker, ds.speaker_label) as spk,
-                SUM(ds.end_ms - ds.start_ms) as ms
-         FROM episodes e
-         JOIN diarization_segments ds ON ds.episode_id = e.id
-         GROUP BY e.id, spk
-         ORDER BY e.publish_date ASC`
+      const epRows = await db.select<{ id: number; title: string }[]>(
+        'SELECT id, title FROM episodes ORDER BY publish_date ASC'
       );
-      // Group by episode
-      const epMap = new Map<string, { title: string; s0: number; s1: number }>();
+      const timeById = new Map(times.map(time => [time.episode_id, time]));
+      const epMap = new Map<number, { title: string; s0: number; s1: number }>();
       for (const r of epRows) {
-        const key = r.publish_date + r.title;
-        if (!epMap.has(key)) epMap.set(key, { title: r.title, s0: 0, s1: 0 });
-        const ep = epMap.get(key)!;
-        if (r.spk === 'SPEAKER_0') ep.s0 += r.ms;
-        else if (r.spk === 'SPEAKER_1') ep.s1 += r.ms;
+        const time = timeById.get(r.id);
+        if (!time) continue;
+        const msOf = (speaker: string) => time.speakers.find(s => s.speaker === speaker)?.ms ?? 0;
+        epMap.set(r.id, { title: r.title, s0: msOf('SPEAKER_0'), s1: msOf('SPEAKER_1') });
       }
       const trendPoints: TrendPoint[] = [];
       for (const ep of epMap.values()) {
@@ -145,17 +160,10 @@ export default function StatsPage() {
       const loadedGroups = parseWordGroups(wordsRaw);
       setGroups(loadedGroups);
       if (loadedGroups.length > 0) {
-        const texts = await db.select<{ full_text: string }[]>('SELECT full_text FROM transcripts');
-        const allText = texts.map(r => r.full_text).join('\n').toLowerCase();
-        setInnuendos(
-          loadedGroups.map(group => ({
-            label: group.label,
-            count: group.words.reduce((sum, word) => {
-              const escaped = word.toLowerCase().replace(/[.*+?^${}()|[\]\\]/g, '\\$&');
-              return sum + (allText.match(new RegExp(escaped, 'g')) ?? []).length;
-            }, 0),
-          }))
+        const texts = await db.select<{ full_text: string; language: string | null }[]>(
+          'SELECT full_text, language FROM transcripts'
         );
+        setTrackedTexts(texts.map(r => ({ text: r.full_text.toLowerCase(), language: r.language })));
       }
 
       setLoading(false);
@@ -176,17 +184,24 @@ export default function StatsPage() {
     return 'var(--color-primary)';
   };
 
+  // Word tracker counts, limited to transcripts in the selected language
+  const trackerLanguages = [...new Set(trackedTexts.map(r => r.language).filter((l): l is string => !!l))].sort();
+  const innuendos = countWordGroups(
+    groups,
+    trackedTexts
+      .filter(r => !trackerLanguage || r.language === trackerLanguage)
+      .map(r => r.text)
+      .join('\n')
+  );
+
   async function handleDeleteGroup(label: string) {
     const previousGroups = groups;
-    const previousInnuendos = innuendos;
     const nextGroups = groups.filter(g => g.label !== label);
     setGroups(nextGroups);
-    setInnuendos(prev => prev.filter(i => i.label !== label));
     try {
       await setSetting('innuendo_words', JSON.stringify(nextGroups));
     } catch {
       setGroups(previousGroups);
-      setInnuendos(previousInnuendos);
     }
   }
 
@@ -276,7 +291,21 @@ export default function StatsPage() {
       {/* Word-Tracker — horizontal bar chart sorted by count */}
       {groups.length > 0 && (
         <div className="settings-section">
-          <h3 className="settings-section-title">{t('pages.stats.innuendo_title')}</h3>
+          <h3 className="settings-section-title" style={{ display: 'flex', alignItems: 'center', gap: 8 }}>
+            {t('pages.stats.innuendo_title')}
+            {trackerLanguages.length > 1 && (
+              <select
+                value={trackerLanguage}
+                onChange={e => setTrackerLanguage(e.target.value)}
+                style={{ marginLeft: 'auto', fontSize: '0.75rem' }}
+              >
+                <option value="">{t('pages.stats.innuendo_all_languages')}</option>
+                {trackerLanguages.map(language => (
+                  <option key={language} value={language}>{language.toUpperCase()}</option>
+                ))}
+              </select>
+            )}
+          </h3>
           {(() => {
             const chartData = groups
               .map(item => ({
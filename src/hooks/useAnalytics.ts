@@ -1,4 +1,5 @@
 import Database from '@tauri-apps/plugin-sql';
+import { invoke } from '@tauri-apps/api/core';
 import { useState, useEffect, useCallback } from 'react';
 import { getSetting, setSetting } from '../lib/settings';
 import { detectSpeakerSwap } from '../lib/speakerDetection';
@@ -14,6 +15,37 @@ export interface SegmentRow {
   confidence: number | null;
 }
 
+/** get_speaking_time result for one episode */
+export interface EpisodeSpeakingTime {
+  episode_id: number;
+  speech_ms: number;
+  overlap_ms: number;
+  speakers: Array<{ speaker: string; ms: number; percent: number }>;
+}
+
+/** get_turn_taking result for one speaker of an episode */
+export interface SpeakerTurnTaking {
+  speaker: string;
+  turns: number;
+  median_turn_ms: number;
+  longest_turn_ms: number;
+  /** Interruptions by this speaker, keyed by the speaker interrupted */
+  interrupts: Record<string, number>;
+  interrupted: number;
+  avg_reply_gap_ms: number | null;
+}
+
+/** get_turn_taking result for one episode */
+export interface EpisodeTurnTaking {
+  episode_id: number;
+  turns: number;
+  median_turn_ms: number;
+  longest_turn_ms: number;
+  interruptions: number;
+  avg_reply_gap_ms: number | null;
+  speakers: SpeakerTurnTaking[];
+}
+
 export interface EpisodeStats {
   episodeId: number;
   title: string;
@@ -24,8 +56,12 @@ export interface EpisodeStats {
   host0Minutes: number;
   host1Minutes: number;
   totalSpeakingMs: number;
+  /** Time in which both hosts (or a guest) talk at once */
+  overlapMinutes: number;
   host0Turns: number;
   host1Turns: number;
+  /** Turns, interruptions and reply gaps; null without diarization segments */
+  turnTaking: EpisodeTurnTaking | null;
   diarizationStatus: string;
 }
 
@@ -110,13 +146,11 @@ export function useAnalytics() {
         audio_url: string;
         diarization_status: string;
         effective_speaker: string | null;
-        speaking_ms: number;
         turn_count: number;
       }>
     >(`
       SELECT e.id, e.title, e.publish_date, e.audio_url, e.diarization_status,
              COALESCE(ds.corrected_speaker, ds.speaker_label) AS effective_speaker,
-             SUM(ds.end_ms - ds.start_ms) AS speaking_ms,
              COUNT(ds.id) AS turn_count
       FROM episodes e
       LEFT JOIN diarization_segments ds ON ds.episode_id = e.id
@@ -125,10 +159,22 @@ export function useAnalytics() {
       ORDER BY e.publish_date DESC
     `);
 
+    // Speaking time with overlapping speech split evenly among the speakers
+    // talking at once, so the host shares are not decided by segment order
+    const times = await invoke<EpisodeSpeakingTime[]>('get_speaking_time', { episodeId: null });
+    const timeById = new Map(times.map((time) => [time.episode_id, time]));
+    const turnTaking = await invoke<EpisodeTurnTaking[]>('get_turn_taking', { episodeId: null });
+    const turnTakingById = new Map(turnTaking.map((stats) => [stats.episode_id, stats]));
+
     // Group by episode
     const episodeMap = new Map<number, EpisodeStats>();
 
     for (const row of rows) {
+      const time = timeById.get(row.id);
+      const speakingMs = time?.speakers.find((s) => s.speaker === row.effective_speaker)?.ms ?? 0;
+      const turns = turnTakingById
+        .get(row.id)
+        ?.speakers.find((s) => s.speaker === row.effective_speaker)?.turns ?? row.turn_count;
       if (!episodeMap.has(row.id)) {
         episodeMap.set(row.id, {
           episodeId: row.id,
@@ -140,8 +186,10 @@ export function useAnalytics() {
           host0Minutes: 0,
           host1Minutes: 0,
           totalSpeakingMs: 0,
+          overlapMinutes: Math.round(((time?.overlap_ms ?? 0) / 60000) * 10) / 10,
           host0Turns: 0,
           host1Turns: 0,
+          turnTaking: turnTakingById.get(row.id) ?? null,
           diarizationStatus: row.diarization_status,
         });
       }
@@ -149,13 +197,13 @@ export function useAnalytics() {
       const stats = episodeMap.get(row.id)!;
 
       if (row.effective_speaker === 'SPEAKER_0') {
-        stats.host0Minutes = Math.round(((row.speaking_ms ?? 0) / 60000) * 10) / 10;
-        stats.host0Turns = row.turn_count;
-        stats.totalSpeakingMs += row.speaking_ms ?? 0;
+        stats.host0Minutes = Math.round((speakingMs / 60000) * 10) / 10;
+        stats.host0Turns = turns;
+        stats.totalSpeakingMs += speakingMs;
       } else if (row.effective_speaker === 'SPEAKER_1') {
-        stats.host1Minutes = Math.round(((row.speaking_ms ?? 0) / 60000) * 10) / 10;
-        stats.host1Turns = row.turn_count;
-        stats.totalSpeakingMs += row.speaking_ms ?? 0;
+        stats.host1Minutes = Math.round((speakingMs / 60000) * 10) / 10;
+        stats.host1Turns = turns;
+        stats.totalSpeakingMs += speakingMs;
       }
     }
 
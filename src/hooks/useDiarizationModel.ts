@@ -7,6 +7,25 @@ interface DiarizationModelStatus {
   models_dir: string;
 }
 
+export interface DiarizationModelInfo {
+  id: string;
+  role: 'segmentation' | 'embedding';
+  name: string;
+  url: string;
+  sha256: string | null;
+  language: string;
+  size_mb: number;
+  downloaded: boolean;
+  selected: boolean;
+}
+
+export interface DiarizationSettings {
+  segmentation_model: string;
+  embedding_model: string;
+  provider: string;
+  num_threads: number;
+}
+
 type DiarizationModelDownloadEvent =
   | { event: 'Progress'; data: { percent: number; bytes: number } }
   | { event: 'Done'; data: Record<string, never> }
@@ -19,16 +38,22 @@ export function useDiarizationModel() {
   const [downloadProgress, setDownloadProgress] = useState(0);
   const [loading, setLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
+  const [models, setModels] = useState<DiarizationModelInfo[]>([]);
+  const [settings, setSettings] = useState<DiarizationSettings | null>(null);
 
   const checkStatus = useCallback(async () => {
     setLoading(true);
     setError(null);
     try {
-      const status = await invoke<DiarizationModelStatus>(
-        'get_diarization_model_status',
-      );
+      const [status, registry, current] = await Promise.all([
+        invoke<DiarizationModelStatus>('get_diarization_model_status'),
+        invoke<DiarizationModelInfo[]>('list_diarization_models'),
+        invoke<DiarizationSettings>('get_diarization_settings'),
+      ]);
       setSegmentationDownloaded(status.segmentation_downloaded);
       setEmbeddingDownloaded(status.embedding_downloaded);
+      setModels(registry);
+      setSettings(current);
     } catch (err) {
       setError(String(err));
     } finally {
@@ -79,7 +104,33 @@ export function useDiarizationModel() {
     }
   }, []);
 
+  // Select models or the execution provider; the selected models then need
+  // downloading if they aren't there yet
+  const updateSettings = useCallback(
+    async (update: {
+      segmentationModel?: string;
+      embeddingModel?: string;
+      provider?: string;
+    }) => {
+      setError(null);
+      try {
+        await invoke('set_diarization_settings', {
+          segmentationModel: update.segmentationModel ?? null,
+          embeddingModel: update.embeddingModel ?? null,
+          provider: update.provider ?? null,
+        });
+        await checkStatus();
+      } catch (err) {
+        setError(String(err));
+      }
+    },
+    [checkStatus],
+  );
+
   return {
+    models,
+    settings,
+    updateSettings,
     segmentationDownloaded,
     embeddingDownloaded,
     allDownloaded: segmentationDownloaded && embeddingDownloaded,
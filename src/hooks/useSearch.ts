@@ -9,6 +9,7 @@ export interface SearchResult {
   segment_type: string; // 'transcript' | 'topic'
   start_ms: number | null;
   end_ms: number | null;
+  language: string | null;
 }
 
 export interface EpisodeGroup {
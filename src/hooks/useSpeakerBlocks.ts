@@ -26,6 +26,8 @@ interface WhisperSegment {
   text: string;
   start_ms: number;
   end_ms: number;
+  /** Set on segments flagged by the hallucination filter (kept, not shown). */
+  hallucination?: string;
 }
 
 // ─── Constants ─────────────────────────────────────────────────────────────────
@@ -81,7 +83,7 @@ function assignSpeakersToWhisperSegments(
   diarSegs: DiarizationSegmentRow[],
 ): Array<{ text: string; speaker: string; startMs: number }> {
   return whisperSegs
-    .filter((ws) => ws.text.trim() !== '')
+    .filter((ws) => ws.text.trim() !== '' && !ws.hallucination)
     .map((ws) => {
       const wsDur = ws.end_ms - ws.start_ms;
       if (wsDur <= 0) return null;
@@ -6,6 +6,8 @@ export interface TranscriptSegment {
   text: string;
   start_ms: number;
   end_ms: number;
+  /** Set on segments flagged by the hallucination filter (kept, not shown). */
+  hallucination?: string;
 }
 
 export interface Transcript {
@@ -35,7 +37,11 @@ export function groupIntoParagraphs(segmentsJson: string): TranscriptParagraph[]
     return [];
   }
 
-  if (!Array.isArray(segments) || segments.length === 0) {
+  if (!Array.isArray(segments)) {
+    return [];
+  }
+  segments = segments.filter((seg) => !seg.hallucination);
+  if (segments.length === 0) {
     return [];
   }
 